@@ -0,0 +1,177 @@
+//! Compile time validation of properties files through `props_check!`.
+//!
+//! The derive generates a hidden `const fn __props_check` for every type. `props_check!` reads the file while
+//! expanding and calls that function in a `const` item, so missing keys and unparseable values become compile errors.
+
+use quote::quote;
+use syn::{Error, Field, LitStr, Token, parse::Parse, parse::ParseStream, punctuated::Punctuated, token::Comma};
+
+use crate::{EmptyPolicy, StructOptions, array_element, parse_field_options, syntax, wrapped_type};
+
+pub(crate) struct PropsCheckInput {
+    ty: syn::Type,
+    path: LitStr,
+}
+
+impl Parse for PropsCheckInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ty = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let path = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self { ty, path })
+    }
+}
+
+pub(crate) fn generate_props_check(input: &PropsCheckInput) -> syn::Result<proc_macro2::TokenStream> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").map_err(|_| Error::new_spanned(&input.path, "CARGO_MANIFEST_DIR is not set"))?;
+    let full_path = std::path::Path::new(&manifest_dir).join(input.path.value());
+    let content = std::fs::read_to_string(&full_path).map_err(|e| Error::new_spanned(&input.path, format!("Error Reading File : {} ({e})", full_path.display())))?;
+
+    // The values are passed as written, the generated check cleans them like the field does
+    let mut pairs = Vec::new();
+    for (line_num, line) in content.lines().enumerate() {
+        match syntax::split_line(line) {
+            Ok(Some(pair)) => pairs.push(pair),
+            Ok(None) => {}
+            Err(line) => return Err(Error::new_spanned(&input.path, format!("Malformed line {} in '{}' (missing '='): {}", line_num + 1, input.path.value(), line))),
+        }
+    }
+
+    let ty = &input.ty;
+    let keys = pairs.iter().map(|(key, _)| key);
+    let values = pairs.iter().map(|(_, value)| value);
+    let full_path = full_path.to_string_lossy();
+
+    Ok(quote! {
+        const _: () = {
+            // Makes cargo rebuild when the checked file changes
+            const _: &[u8] = include_bytes!(#full_path);
+            <#ty>::__props_check(&[ #( (#keys, #values) ),* ]);
+        };
+    })
+}
+
+/// Generates `__props_check`, which panics at compile time for missing required keys and values that can't parse.
+pub(crate) fn generate_check_fn(struct_options: &StructOptions, fields: &Punctuated<Field, Comma>) -> syn::Result<proc_macro2::TokenStream> {
+    // Keys are looked up the way `case_insensitive` matches them
+    let find = match struct_options.case_insensitive {
+        true => quote! { ::props_util::__private::check::find_ignore_case },
+        false => quote! { ::props_util::__private::check::find },
+    };
+    let mut checks = Vec::new();
+
+    for field in fields {
+        let options = parse_field_options(field)?;
+        if options.rest {
+            continue;
+        }
+        let key = &options.key;
+        let field_type = &field.ty;
+        let option_inner = wrapped_type(field_type, "Option");
+        let value_type = option_inner.unwrap_or(field_type);
+
+        // A `via` field is checked against the type its value is parsed into
+        let (check, kind) = match wrapped_type(value_type, "Vec").or_else(|| array_element(value_type)) {
+            Some(element) => (quote! { check_list }, generate_check_kind(options.via.as_ref().unwrap_or(element))),
+            None => (quote! { check_value }, generate_check_kind(options.via.as_ref().unwrap_or(value_type))),
+        };
+        let kind = match options.relaxes_numbers() {
+            true => quote! { ::props_util::__private::check::Kind::Any },
+            false => kind,
+        };
+
+        let on_missing = match options.is_required(field) {
+            true => {
+                let message = format!("props_check: `{}` value is not configured which is required", key.value());
+                quote! { panic!("{}", #message) }
+            }
+            false => quote! {},
+        };
+
+        let message = format!("props_check: `{}` value does not parse into `{}`", key.value(), quote!(#field_type).to_string().replace(' ', ""));
+        let found = match &options.renamed_from {
+            Some(old) => quote! {
+                match #find(pairs, #key) {
+                    Some(val) => Some(val),
+                    None => #find(pairs, #old),
+                }
+            },
+            None => quote! { #find(pairs, #key) },
+        };
+        let (trim, inline_comments) = (options.trim, options.inline_comments);
+        let clean = quote! {
+            let found = match found {
+                Some(val) => Some(::props_util::__private::check::clean_value(val, #trim, #inline_comments)),
+                None => None,
+            };
+        };
+        // Like at runtime, an empty value is absent with `empty = "none"` and an error with `empty = "error"`
+        let (skip_empty, on_empty) = match options.empty {
+            EmptyPolicy::AsIs => (quote! {}, quote! {}),
+            EmptyPolicy::None => (
+                quote! {
+                    let found = match found {
+                        Some(val) if val.is_empty() => None,
+                        found => found,
+                    };
+                },
+                quote! {},
+            ),
+            EmptyPolicy::Error => {
+                let message = format!("props_check: `{}` is configured with an empty value", key.value());
+                (quote! {}, quote! { Some(val) if val.is_empty() => panic!("{}", #message), })
+            }
+        };
+        // Values the check can't follow are only checked for presence
+        let mut unchecked = vec![quote! { false }];
+        if options.inline_comments {
+            unchecked.push(quote! { ::props_util::__private::check::has_escaped_hash(val) });
+        }
+        if struct_options.templates {
+            unchecked.push(quote! { ::props_util::__private::check::has_template(val) });
+        }
+        checks.push(quote! {
+            {
+                let found = #found;
+                #clean
+                #skip_empty
+                match found {
+                    #on_empty
+                    Some(val) => if !( #( #unchecked )||* ) && !::props_util::__private::check::#check(val, #kind) {
+                        panic!("{}", #message)
+                    },
+                    None => { #on_missing }
+                }
+            }
+        });
+    }
+
+    Ok(quote! {
+        #[doc(hidden)]
+        pub const fn __props_check(pairs: &[(&str, &str)]) {
+            let _ = pairs;
+            #( #checks )*
+        }
+    })
+}
+
+fn generate_check_kind(ty: &syn::Type) -> proc_macro2::TokenStream {
+    let kind = quote! { ::props_util::__private::check::Kind };
+    let ident = match ty {
+        syn::Type::Path(tpath) if tpath.qself.is_none() => match tpath.path.get_ident() {
+            Some(ident) => ident,
+            None => return quote! { #kind::Any },
+        },
+        _ => return quote! { #kind::Any },
+    };
+
+    match ident.to_string().as_str() {
+        "bool" => quote! { #kind::Bool },
+        "char" => quote! { #kind::Char },
+        "f32" | "f64" => quote! { #kind::Float },
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => quote! { #kind::Uint(#ident::MAX as u128) },
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => quote! { #kind::Int(#ident::MIN as i128, #ident::MAX as i128) },
+        _ => quote! { #kind::Any },
+    }
+}
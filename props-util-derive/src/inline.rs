@@ -0,0 +1,58 @@
+//! Inline construction of derived types through `props!`.
+
+use quote::quote;
+use syn::{Error, LitStr, Token, braced, parse::Parse, parse::ParseStream, punctuated::Punctuated};
+
+pub(crate) struct PropsInput {
+    path: syn::Path,
+    pairs: Punctuated<Pair, Token![,]>,
+}
+
+struct Pair {
+    key: LitStr,
+    value: LitStr,
+}
+
+impl Parse for Pair {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let value = input.parse()?;
+        Ok(Self { key, value })
+    }
+}
+
+impl Parse for PropsInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let content;
+        braced!(content in input);
+        let pairs = content.parse_terminated(Pair::parse, Token![,])?;
+        Ok(Self { path, pairs })
+    }
+}
+
+pub(crate) fn generate_props(input: &PropsInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !cfg!(feature = "std") {
+        return Err(Error::new_spanned(&input.path, "`props!` requires the `std` feature of props-util"));
+    }
+
+    let mut keys = Vec::new();
+    for pair in &input.pairs {
+        if keys.iter().any(|key: &&LitStr| key.value() == pair.key.value()) {
+            return Err(Error::new_spanned(&pair.key, format!("duplicate key `{}`", pair.key.value())));
+        }
+        keys.push(&pair.key);
+    }
+
+    let path = &input.path;
+    let values = input.pairs.iter().map(|pair| &pair.value);
+    let pairs = quote! { [ #( (#keys, #values) ),* ] };
+
+    Ok(quote! {
+        {
+            const _: () = <#path>::__props_check(&#pairs);
+            <#path>::from_map(&std::collections::HashMap::<&str, &str>::from(#pairs))
+        }
+    })
+}
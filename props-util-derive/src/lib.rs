@@ -0,0 +1,2485 @@
+//! Derive macro implementation for [props-util](https://docs.rs/props-util).
+//!
+//! This crate is an implementation detail of `props-util` and is re-exported from there.
+//! Depend on `props-util` directly instead of using this crate.
+
+// Most of the code generation is only reachable with the `std` feature
+#![cfg_attr(not(feature = "std"), allow(dead_code))]
+
+extern crate proc_macro;
+
+mod check;
+mod inline;
+mod rename;
+// The syntax of properties files, shared with the runtime so that `props_check!` reads files exactly like it does
+#[allow(dead_code)]
+#[path = "../../src/syntax.rs"]
+mod syntax;
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DeriveInput, Error, Field, LitStr, parse::Parse, parse_macro_input, punctuated::Punctuated, token::Comma};
+
+/// Derive macro for automatically implementing properties parsing functionality.
+///
+/// This macro generates implementations for:
+/// - `from_file`: Load properties from a file
+/// - `from`: Create instance from a type that implements Into<HashMap<String, String>>, such as `&OtherConfig`
+/// - `default`: Create instance with default values
+///
+/// # Example
+///
+/// ```rust
+/// use props_util::Properties;
+/// use std::io::Result;
+///
+/// #[derive(Properties, Debug)]
+/// struct Config {
+///     #[prop(key = "server.host", default = "localhost")]
+///     host: String,
+///     #[prop(key = "server.port", default = "8080")]
+///     port: u16,
+/// }
+///
+/// fn main() -> Result<()> {
+///     let config = Config::default()?;
+///     println!("Host: {}", config.host);
+///     println!("Port: {}", config.port);
+///     Ok(())
+/// }
+/// ```
+#[proc_macro_derive(Properties, attributes(prop))]
+pub fn parse_prop_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match generate_derive(&input) {
+        Ok(derive_impl) => derive_impl.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Checks a properties file against a type deriving `Properties` at compile time.
+///
+/// The path is relative to the crate root, `CARGO_MANIFEST_DIR`. Compilation fails if the file can't be read, has a
+/// malformed line, lacks a key that is required by the type, or has a value that can't parse into the field type.
+/// Only primitive field types are checked for their values, everything else is only checked for presence.
+///
+/// ```rust
+/// use props_util::{Properties, props_check};
+///
+/// #[derive(Properties)]
+/// struct Config {
+///     name: String,
+///     option_vec1: Vec<u32>,
+/// }
+///
+/// props_check!(Config, "../examples/test.properties");
+/// ```
+#[proc_macro]
+pub fn props_check(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as check::PropsCheckInput);
+
+    match check::generate_props_check(&input) {
+        Ok(check_impl) => check_impl.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Creates an instance of a type deriving `Properties` from inline `key => value` pairs.
+///
+/// The pairs are checked at compile time the same way as with `props_check!`. The expression evaluates to the
+/// `std::io::Result` returned by `from_map`, since values of non primitive types can only be parsed at runtime.
+///
+/// ```rust
+/// use props_util::{Properties, props};
+///
+/// #[derive(Properties)]
+/// struct Config {
+///     #[prop(key = "server.host", default = "localhost")]
+///     host: String,
+///     #[prop(key = "server.port", default = "8080")]
+///     port: u16,
+/// }
+///
+/// let config = props!(Config { "server.port" => "9090" }).unwrap();
+/// assert_eq!(config.port, 9090);
+/// assert_eq!(config.host, "localhost");
+/// ```
+#[proc_macro]
+pub fn props(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as inline::PropsInput);
+
+    match inline::generate_props(&input) {
+        Ok(props_impl) => props_impl.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[cfg(feature = "std")]
+fn generate_derive(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name = &input.ident;
+    let options = parse_struct_options(input)?;
+    if options.templates && !cfg!(feature = "templates") {
+        return Err(Error::new_spanned(struct_name, "`templates` requires the `templates` feature of props-util"));
+    }
+    if options.templates && options.lazy {
+        return Err(Error::new_spanned(struct_name, "`templates` can't be combined with `lazy`, rendering a template can fail before any field is read"));
+    }
+    let prop_impl = generate_prop_fns(input, &options)?;
+    let check_fn = check::generate_check_fn(&options, &extract_named_fields(input)?)?;
+    let key_fns = generate_key_fns(&extract_named_fields(input)?)?;
+    let global_fns = match options.global {
+        true => generate_global_fns(struct_name),
+        false => proc_macro2::TokenStream::new(),
+    };
+
+    let lazy_impl = match options.lazy {
+        true => generate_lazy_struct(input, &options, &extract_named_fields(input)?)?,
+        false => proc_macro2::TokenStream::new(),
+    };
+
+    // A blanket `TryFrom<&T>` would overlap with the one in `core`, so the source types are listed explicitly
+    let try_from_impl = options.try_from.iter().map(|source| {
+        quote! {
+            impl ::core::convert::TryFrom<&#source> for #struct_name {
+                type Error = std::io::Error;
+
+                fn try_from(other: &#source) -> std::io::Result<Self> {
+                    Self::convert_from(other)
+                }
+            }
+        }
+    });
+
+    let serde_impl = generate_serde_impl(struct_name, &options)?;
+    let display_impl = generate_display_impl(struct_name, &options)?;
+    let debug_impl = generate_debug_impl(struct_name, &options, &extract_named_fields(input)?)?;
+
+    let patch_impl = match options.patch {
+        true => generate_patch_struct(input, &options, &extract_named_fields(input)?)?,
+        false => proc_macro2::TokenStream::new(),
+    };
+
+    // Converting into a map needs `Display` on every field type, so it can be switched off with `#[prop(no_hashmap)]`.
+    // Only references convert, so the conversion never consumes the value and doesn't clash with user written impls.
+    let hashmap_impl = match options.no_hashmap {
+        true => proc_macro2::TokenStream::new(),
+        false => quote! {
+            impl std::convert::From<&#struct_name> for std::collections::HashMap<String, String> {
+                fn from(value: &#struct_name) -> Self {
+                    value.to_hash_map()
+                }
+            }
+        },
+    };
+
+    Ok(quote! {
+        impl #struct_name {
+            #prop_impl
+
+            #check_fn
+
+            #key_fns
+
+            #global_fns
+        }
+
+        #lazy_impl
+
+        #patch_impl
+
+        #( #try_from_impl )*
+
+        #serde_impl
+
+        #display_impl
+
+        #debug_impl
+
+        impl ::props_util::Properties for #struct_name {
+            #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+            fn from_file(path: &str) -> std::io::Result<Self> {
+                Self::from_file(path)
+            }
+
+            #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+            fn validate_file(path: &str) -> std::io::Result<::props_util::ValidationReport> {
+                Self::validate_file(path)
+            }
+        }
+
+        #hashmap_impl
+    })
+}
+
+/// Without the `std` feature only `from_str` is generated, since every other constructor reads files or the environment.
+#[cfg(not(feature = "std"))]
+fn generate_derive(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name = &input.ident;
+    let options = parse_struct_options(input)?;
+    if options.lazy {
+        return Err(Error::new_spanned(struct_name, "`lazy` requires the `std` feature of props-util"));
+    }
+    if options.global {
+        return Err(Error::new_spanned(struct_name, "`global` requires the `std` feature of props-util"));
+    }
+    if options.patch {
+        return Err(Error::new_spanned(struct_name, "`patch` requires the `std` feature of props-util"));
+    }
+    if !options.try_from.is_empty() {
+        return Err(Error::new_spanned(struct_name, "`try_from` requires the `std` feature of props-util"));
+    }
+    if options.serde {
+        return Err(Error::new_spanned(struct_name, "`serde` requires the `serde` feature of props-util"));
+    }
+    if options.display {
+        return Err(Error::new_spanned(struct_name, "`display` requires the `std` feature of props-util"));
+    }
+    if options.before_parse.is_some() || options.after_parse.is_some() {
+        return Err(Error::new_spanned(struct_name, "`before_parse` and `after_parse` require the `std` feature of props-util"));
+    }
+    if options.case_insensitive {
+        return Err(Error::new_spanned(struct_name, "`case_insensitive` requires the `std` feature of props-util"));
+    }
+    if options.file.is_some() {
+        return Err(Error::new_spanned(struct_name, "`file` requires the `std` feature of props-util"));
+    }
+    if options.templates {
+        return Err(Error::new_spanned(struct_name, "`templates` requires the `templates` feature of props-util"));
+    }
+
+    let fields = extract_named_fields(input)?;
+    let check_fn = check::generate_check_fn(&options, &fields)?;
+    let key_fns = generate_key_fns(&fields)?;
+    let debug_impl = generate_debug_impl(struct_name, &options, &fields)?;
+    let init_arr = generate_init_token_streams(fields)?;
+    let embed_fn = generate_embed_fn(&options, quote! { ::core::result::Result<Self, ::props_util::parse::Error> });
+
+    Ok(quote! {
+        impl #struct_name {
+            /// Parses properties from a string.
+            pub fn from_str(content : &str) -> ::core::result::Result<Self, ::props_util::parse::Error> {
+                let propmap = ::props_util::parse::parse_str_untrimmed(content)?;
+                Ok(Self { #( #init_arr ),* })
+            }
+
+            /// Parses properties from a string like `from_str`, failing with `LimitExceeded` if it goes over `limits`.
+            pub fn from_str_limited(content : &str, limits : &::props_util::parse::Limits) -> ::core::result::Result<Self, ::props_util::parse::Error> {
+                limits.check(content)?;
+                Self::from_str(content)
+            }
+
+            #embed_fn
+
+            #check_fn
+
+            #key_fns
+        }
+
+        #debug_impl
+    })
+}
+
+/// Options configured through `#[prop(...)]` on the struct itself.
+#[derive(Default)]
+struct StructOptions {
+    lazy: bool,
+    no_hashmap: bool,
+    global: bool,
+    patch: bool,
+    serde: bool,
+    display: bool,
+    debug: bool,
+    case_insensitive: bool,
+    inline_comments: bool,
+    decimal_comma: bool,
+    underscores: bool,
+    radix_prefixes: bool,
+    templates: bool,
+    rename_all: Option<rename::RenameRule>,
+    env: bool,
+    env_prefix: Option<LitStr>,
+    order: Option<LitStr>,
+    try_from: Vec<syn::Type>,
+    before_parse: Option<syn::ExprPath>,
+    after_parse: Option<syn::ExprPath>,
+    embed: Option<LitStr>,
+    file: Option<LitStr>,
+    file_env: Option<LitStr>,
+}
+
+fn parse_struct_options(input: &DeriveInput) -> syn::Result<StructOptions> {
+    let mut options = StructOptions::default();
+
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("prop")) {
+        attr.parse_nested_meta(|meta| {
+            match () {
+                _ if meta.path.is_ident("lazy") => options.lazy = true,
+                _ if meta.path.is_ident("no_hashmap") => options.no_hashmap = true,
+                _ if meta.path.is_ident("global") => options.global = true,
+                _ if meta.path.is_ident("patch") => options.patch = true,
+                _ if meta.path.is_ident("serde") => options.serde = true,
+                _ if meta.path.is_ident("display") => options.display = true,
+                _ if meta.path.is_ident("debug") => options.debug = true,
+                _ if meta.path.is_ident("case_insensitive") => options.case_insensitive = true,
+                _ if meta.path.is_ident("inline_comments") => options.inline_comments = true,
+                _ if meta.path.is_ident("decimal_comma") => options.decimal_comma = true,
+                _ if meta.path.is_ident("underscores") => options.underscores = true,
+                _ if meta.path.is_ident("radix_prefixes") => options.radix_prefixes = true,
+                _ if meta.path.is_ident("templates") => options.templates = true,
+                _ if meta.path.is_ident("try_from") => {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    options.try_from.extend(content.parse_terminated(syn::Type::parse, syn::Token![,])?);
+                }
+                _ if meta.path.is_ident("before_parse") => match options.before_parse {
+                    Some(_) => return Err(meta.error("duplicate `before_parse` parameter")),
+                    None => options.before_parse = Some(meta.value()?.parse::<LitStr>()?.parse()?),
+                },
+                _ if meta.path.is_ident("after_parse") => match options.after_parse {
+                    Some(_) => return Err(meta.error("duplicate `after_parse` parameter")),
+                    None => options.after_parse = Some(meta.value()?.parse::<LitStr>()?.parse()?),
+                },
+                _ if meta.path.is_ident("rename_all") => match options.rename_all {
+                    Some(_) => return Err(meta.error("duplicate `rename_all` parameter")),
+                    None => options.rename_all = Some(rename::RenameRule::parse(&meta.value()?.parse()?)?),
+                },
+                _ if meta.path.is_ident("env") => options.env = true,
+                _ if meta.path.is_ident("order") => match options.order {
+                    Some(_) => return Err(meta.error("duplicate `order` parameter")),
+                    None => {
+                        let order: LitStr = meta.value()?.parse()?;
+                        parse_order(&order)?;
+                        options.order = Some(order);
+                    }
+                },
+                _ if meta.path.is_ident("env_prefix") => match options.env_prefix {
+                    Some(_) => return Err(meta.error("duplicate `env_prefix` parameter")),
+                    None => options.env_prefix = Some(meta.value()?.parse()?),
+                },
+                _ if meta.path.is_ident("file") => match options.file {
+                    Some(_) => return Err(meta.error("duplicate `file` parameter")),
+                    None => options.file = Some(meta.value()?.parse()?),
+                },
+                _ if meta.path.is_ident("file_env") => match options.file_env {
+                    Some(_) => return Err(meta.error("duplicate `file_env` parameter")),
+                    None => options.file_env = Some(meta.value()?.parse()?),
+                },
+                _ if meta.path.is_ident("embed") => match options.embed {
+                    Some(_) => return Err(meta.error("duplicate `embed` parameter")),
+                    None => options.embed = Some(meta.value()?.parse()?),
+                },
+                _ => return Err(meta.error(format!("unrecognized parameter '{}' in struct #[prop] attribute", meta.path.get_ident().map(|i| i.to_string()).unwrap_or_else(|| "<?>".into())))),
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(options)
+}
+
+fn extract_named_fields(input: &DeriveInput) -> syn::Result<Punctuated<Field, Comma>> {
+    let fields = match &input.data {
+        syn::Data::Struct(data_struct) => match &data_struct.fields {
+            syn::Fields::Named(fields_named) => &fields_named.named,
+            _ => return Err(Error::new_spanned(&input.ident, "Only named structs are allowd")),
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "Only structs can be used on Properties")),
+    };
+
+    // Applying `rename_all` and deriving env var names here gives every generator the same keys and names
+    let options = parse_struct_options(input)?;
+    let mut fields = fields.to_owned();
+    if let Some(rule) = options.rename_all {
+        fields.iter_mut().for_each(|field| rename::rename_field(field, rule));
+    }
+    let prefix = options.env_prefix.map(|prefix| prefix.value()).unwrap_or_default();
+    for field in fields.iter_mut() {
+        rename::derive_env(field, options.env, &prefix)?;
+        if let Some(order) = &options.order {
+            rename::inherit_order(field, order);
+        }
+        if options.inline_comments {
+            rename::inherit_flag(field, "inline_comments");
+        }
+        if options.decimal_comma {
+            rename::inherit_flag(field, "decimal_comma");
+        }
+        if options.underscores {
+            rename::inherit_flag(field, "underscores");
+        }
+        if options.radix_prefixes {
+            rename::inherit_flag(field, "radix_prefixes");
+        }
+    }
+    Ok(fields)
+}
+
+fn generate_field_init_quote(field_type: &syn::Type, raw_value_str: proc_macro2::TokenStream, options: &FieldOptions, is_option: bool) -> proc_macro2::TokenStream {
+    let FieldOptions { key, map_with, .. } = options;
+    // Pregenerated token streams to generate values. Errors are `props_util::parse::Error`, which `?` and `into` convert to the
+    // error type of the generated constructor.
+    let number_syntax = options.number_syntax();
+    let vec_parsing = match (&options.via, options.from_string) {
+        (Some(via), _) => quote! { ::props_util::parse::parse_vec_via::<#via, _>(#key, &val)? },
+        (None, true) => quote! { ::props_util::parse::from_string_vec(&val) },
+        (None, false) if number_syntax.is_some() => quote! { ::props_util::parse::parse_vec_number::<_>(#key, &val, #number_syntax)? },
+        (None, false) => quote! { ::props_util::parse::parse_vec::<_>(#key, &val)? },
+    };
+    let vec_parsing = match options.dedup {
+        true => quote! { ::props_util::parse::dedup(#vec_parsing) },
+        false => vec_parsing,
+    };
+    let vec_parsing = match options.sorted {
+        true => quote! { ::props_util::parse::sorted(#vec_parsing) },
+        false => vec_parsing,
+    };
+    let vec_parsing = match options.min_len {
+        Some(min_len) => quote! { ::props_util::parse::check_min_len(#key, #vec_parsing, #min_len)? },
+        None => vec_parsing,
+    };
+    let parsing = match () {
+        _ if options.flags && is_set_type(field_type) => quote! { ::props_util::parse::parse_flag_set::<_, _>(#key, &val)? },
+        _ if options.flags => quote! { ::props_util::parse::parse_bitflags(#key, &val)? },
+        _ if is_map_type(field_type) => {
+            let (entry_sep, kv_sep) = options.map_separators();
+            quote! { ::props_util::parse::parse_map::<_, _, _>(#key, &val, #entry_sep, #kv_sep)? }
+        }
+        _ if wrapped_type(field_type, "RangeInclusive").is_some() => quote! { ::props_util::parse::parse_range_inclusive(#key, &val)? },
+        _ if wrapped_type(field_type, "Range").is_some() => quote! { ::props_util::parse::parse_range(#key, &val)? },
+        _ if array_element(field_type).is_some() => quote! { ::props_util::parse::parse_array(#key, &val)? },
+        _ if options.from_string => quote! { ::props_util::parse::from_string(&val) },
+        _ if options.via.is_some() => {
+            let via = &options.via;
+            quote! { ::props_util::parse::parse_via::<#via, _>(#key, &val)? }
+        }
+        _ if number_syntax.is_some() => quote! { ::props_util::parse::parse_number(#key, &val, #number_syntax)? },
+        _ => quote! { ::props_util::parse::parse_value(#key, &val)? },
+    };
+
+    // `map_with` transforms the parsed value, which is the whole `Vec` for list fields
+    let (vec_parsing, parsing) = match map_with {
+        Some(map_with) => (quote! { #map_with(#vec_parsing) }, quote! { #map_with(#parsing) }),
+        None => (vec_parsing, parsing),
+    };
+    let error = quote! { Err(::props_util::parse::Error::missing_key(#key).into()) };
+    let raw_value_str = match options.empty {
+        EmptyPolicy::AsIs => raw_value_str,
+        EmptyPolicy::None => quote! { (#raw_value_str).filter(|val| !val.is_empty()) },
+        EmptyPolicy::Error => quote! {
+            match #raw_value_str {
+                Some(val) if val.is_empty() => return Err(::props_util::parse::Error::empty_value(#key).into()),
+                val => val,
+            }
+        },
+    };
+
+    let init = match field_type {
+        syn::Type::Path(tpath) if tpath.path.segments.last().is_some_and(|segment| segment.ident == "Vec") => match is_option {
+            false => quote! {
+                match #raw_value_str {
+                    Some(val) => #vec_parsing,
+                    None => return #error
+                }
+            },
+            true => quote! {
+                match #raw_value_str {
+                    Some(val) => Some(#vec_parsing),
+                    None => None
+                }
+            },
+        },
+        _ => match is_option {
+            false => quote! {
+                match #raw_value_str {
+                    Some(val) => #parsing,
+                    None => return #error
+                }
+            },
+            true => quote! {
+                match #raw_value_str {
+                    Some(val) => Some(#parsing),
+                    None => None
+                }
+            },
+        },
+    };
+
+    // The closure catches every error of the field, missing or invalid, to attach the `help` to it
+    match &options.help {
+        Some(help) => quote! {
+            match (|| ::core::result::Result::<_, ::props_util::parse::Error>::Ok(#init))() {
+                Ok(val) => val,
+                Err(e) => return Err(e.with_help(#help).into()),
+            }
+        },
+        None => init,
+    }
+}
+
+fn generate_init_token_streams(fields: Punctuated<Field, Comma>) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let mut init_arr: Vec<proc_macro2::TokenStream> = Vec::new();
+
+    for field in &fields {
+        let field_name = field.ident.as_ref().to_owned().unwrap();
+        let value = generate_field_value(field, &fields)?;
+        init_arr.push(quote! { #field_name : #value });
+    }
+
+    Ok(init_arr)
+}
+
+/// Generates the expression resolving a single field from `propmap`, env and defaults. `fields` are all fields of the
+/// struct, which a `rest` field needs to know the keys consumed by the others.
+fn generate_field_value(field: &Field, fields: &Punctuated<Field, Comma>) -> syn::Result<proc_macro2::TokenStream> {
+    let options = parse_field_options(field)?;
+    if options.rest {
+        return generate_rest_value(field, fields);
+    }
+    let lookup = options.file_value();
+    if let Some(env_key) = options.env.as_ref().filter(|_| !cfg!(feature = "std")) {
+        return Err(Error::new_spanned(env_key, "`env` requires the `std` feature of props-util"));
+    }
+
+    // Values are looked up as `Cow<str>` borrowed from the propmap, so nothing is allocated until a field parses into an owned type.
+    // The sources are tried in the configured order, the first one that has a value wins.
+    // With `empty = "none"` an empty value doesn't count, so the next source is tried
+    let non_empty = match options.empty {
+        EmptyPolicy::None => quote! { .filter(|val| !val.is_empty()) },
+        _ => quote! {},
+    };
+    let sources = options.order.iter().filter_map(|source| match source {
+        Source::File => Some(quote! { #lookup #non_empty }),
+        Source::Env => options.env.as_ref().map(|env_key| quote! { ::props_util::__private::env_var(#env_key).map(::props_util::__private::Cow::<str>::Owned) #non_empty }),
+        Source::Default => options.default.as_ref().map(|default| quote! { Some(::props_util::__private::Cow::Borrowed(#default)) }),
+    });
+    let mut sources = sources.collect::<Vec<_>>().into_iter();
+    let first = sources.next();
+    let val_token_stream = quote! { #first #( .or_else(|| #sources) )* };
+
+    let key = &options.key;
+    let field_type = &field.ty;
+
+    // With the `testing` feature, overrides of the current thread win over everything else
+    let val_token_stream = match cfg!(feature = "testing") {
+        true => quote! { ::props_util::__private::key_override(#key).map(|val| Some(::props_util::__private::Cow::<str>::Owned(val))).unwrap_or(#val_token_stream) },
+        false => val_token_stream,
+    };
+
+    // `check_shape` already rejected an `Option` without the type of its value
+    let value = match wrapped_type(field_type, "Option") {
+        Some(inner) => generate_field_init_quote(inner, val_token_stream, &options, true),
+        None => generate_field_init_quote(field_type, val_token_stream, &options, false),
+    };
+
+    Ok(value)
+}
+
+/// Generates the value of a `#[prop(rest)]` field, which collects every key that no other field consumes.
+fn generate_rest_value(field: &Field, fields: &Punctuated<Field, Comma>) -> syn::Result<proc_macro2::TokenStream> {
+    if !cfg!(feature = "std") {
+        return Err(Error::new_spanned(field, "`rest` requires the `std` feature of props-util"));
+    }
+    if !matches!(&field.ty, syn::Type::Path(tpath) if tpath.path.segments.last().is_some_and(|segment| segment.ident == "HashMap")) {
+        return Err(Error::new_spanned(&field.ty, "`rest` field must be a `HashMap<String, String>`"));
+    }
+
+    let mut consumed = Vec::new();
+    for other in fields {
+        let options = parse_field_options(other)?;
+        match options.rest {
+            true if other.ident != field.ident => return Err(Error::new_spanned(other, "only one field can be `rest`")),
+            true => {}
+            false => consumed.extend([Some(options.key), options.renamed_from].into_iter().flatten()),
+        }
+    }
+
+    Ok(quote! {
+        {
+            const CONSUMED: &[&str] = &[ #( #consumed ),* ];
+            propmap
+                .iter()
+                .filter(|(key, _)| !CONSUMED.contains(&::core::borrow::Borrow::<str>::borrow(*key)))
+                .map(|(key, val)| (::core::borrow::Borrow::<str>::borrow(key).to_string(), ::core::convert::AsRef::<str>::as_ref(val).trim().to_string()))
+                .collect()
+        }
+    })
+}
+
+fn generate_field_hm_token_stream(options: &FieldOptions, field_type: &syn::Type, field_name: &proc_macro2::Ident, is_option: bool, with_field_name: bool) -> proc_macro2::TokenStream {
+    let field_name_str = field_name.to_string();
+    let key = &options.key;
+
+    let value = match field_type {
+        _ if options.flags && is_set_type(field_type) => quote! { ::props_util::parse::format_flag_set(val) },
+        _ if options.flags => quote! { ::props_util::parse::format_bitflags(val) },
+        syn::Type::Path(tpath) if tpath.path.segments.last().is_some_and(|segment| segment.ident == "Vec") => quote! { ::props_util::parse::format_vec(val) },
+        syn::Type::Array(_) => quote! { ::props_util::parse::format_vec(val) },
+        _ if is_map_type(field_type) => {
+            let (entry_sep, kv_sep) = options.map_separators();
+            quote! { ::props_util::parse::format_map(val, #entry_sep, #kv_sep) }
+        }
+        _ if wrapped_type(field_type, "RangeInclusive").is_some() => quote! { ::props_util::parse::format_range_inclusive(val) },
+        _ if wrapped_type(field_type, "Range").is_some() => quote! { format!("{}..{}", val.start, val.end) },
+        _ => quote! { val.to_string() },
+    };
+
+    // When convert to a hashmap, we insert #filed_name and #key. This will be very helpful
+    // when using the resultant Hashmap to construct some other type which may or may not configure key in the props. That type can look up
+    // either #key or #field_name whichever it wants to construct its values. If both are the same, a single insert is enough.
+    let insert = match key.value() == field_name_str || !with_field_name {
+        true => quote! { hm.insert(#key.to_string(), value); },
+        false => quote! {
+            hm.insert(#field_name_str.to_string(), value.clone());
+            hm.insert(#key.to_string(), value);
+        },
+    };
+
+    match is_option {
+        false => quote! {
+            {
+                let val = &self.#field_name;
+                let value = #value;
+                #insert
+            }
+        },
+        true => quote! {
+            if let Some(val) = &self.#field_name {
+                let value = #value;
+                #insert
+            }
+        },
+    }
+}
+
+/// Returns the insert statements for every field along with the number of entries the resulting map can hold.
+///
+/// With `with_field_name`, values whose key differs from the field name are inserted under the field name as well.
+fn generate_hashmap_token_streams(fields: Punctuated<Field, Comma>, with_field_name: bool) -> syn::Result<(Vec<proc_macro2::TokenStream>, usize)> {
+    let mut init_arr: Vec<proc_macro2::TokenStream> = Vec::new();
+    let mut capacity = 0;
+
+    for field in fields {
+        let options = parse_field_options(&field).map_err(|e| Error::new_spanned(field.clone(), format!("Error parsing prop {e}")))?;
+        let field_name = field.ident.as_ref().to_owned().unwrap();
+        let field_type = &field.ty;
+
+        // Leftover keys are passed through verbatim
+        if options.rest {
+            init_arr.push(quote! { hm.extend(self.#field_name.iter().map(|(key, val)| (key.clone(), val.clone()))); });
+            continue;
+        }
+
+        capacity += if *field_name == options.key.value() || !with_field_name { 1 } else { 2 };
+
+        let quote = match field_type {
+            syn::Type::Path(tpath) if tpath.path.segments.last().is_some_and(|segment| segment.ident == "Option") => match tpath.path.segments.last().unwrap().to_owned().arguments {
+                syn::PathArguments::AngleBracketed(arguments) if arguments.args.first().is_some() => match arguments.args.first().unwrap() {
+                    syn::GenericArgument::Type(ftype) => generate_field_hm_token_stream(&options, ftype, field_name, true, with_field_name),
+                    _ => return Err(Error::new_spanned(field, "Optional {field_name} is not configured properly")),
+                },
+                _ => return Err(Error::new_spanned(field, "Optional {field_name} not configured properly")),
+            },
+            _ => generate_field_hm_token_stream(&options, field_type, field_name, false, with_field_name),
+        };
+
+        init_arr.push(quote);
+    }
+
+    Ok((init_arr, capacity))
+}
+
+fn generate_prop_fns(input: &DeriveInput, options: &StructOptions) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = extract_named_fields(input)?;
+    let init_arr = generate_init_token_streams(fields.clone())?;
+    let validations = generate_validations(&fields)?;
+    let overrides = generate_overrides(&fields)?;
+    let mut renamed = Vec::new();
+    let mut untrimmed = Vec::new();
+    for field in &fields {
+        let options = parse_field_options(field)?;
+        if !options.trim {
+            untrimmed.extend([Some(options.key.clone()), options.renamed_from.clone()].into_iter().flatten());
+        }
+        if let Some(old) = options.renamed_from {
+            let new = options.key;
+            renamed.push(quote! { (#old, #new) });
+        }
+    }
+    let load_event = generate_load_event(input, options, &fields)?;
+    let provenance = generate_provenance(&fields)?;
+    let normalize = generate_key_normalization(options, &fields)?;
+    let templates = generate_templates(options, &fields, quote! { return Err(e.into()) })?;
+    let prepare = quote! { #normalize #templates };
+    let override_templates = generate_templates(
+        options,
+        &fields,
+        quote! {
+            {
+                let mut report = ::props_util::ValidationReport::default();
+                report.push(e);
+                return Err(report);
+            }
+        },
+    )?;
+    let read_file = match &options.before_parse {
+        Some(before_parse) => quote! {
+            let mut propmap = ::props_util::__private::read_file(path)?;
+            ::props_util::__private::trim_values(&mut propmap, Self::__UNTRIMMED);
+            #before_parse(&mut propmap);
+            #prepare
+        },
+        None => quote! {
+            let content = ::props_util::__private::read_content(path, &::props_util::parse::Limits::new())?;
+            let propmap = ::props_util::__private::parse_content(&content, path)?;
+            #prepare
+        },
+    };
+    let construct = |source: proc_macro2::TokenStream| generate_construct(&init_arr, options, &prepare, &load_event, source);
+    let from_path = construct(quote! { path });
+    let from_paths = construct(quote! { paths.join(", ") });
+    let from_pattern = construct(quote! { pattern });
+    let from_app = construct(quote! { app });
+    let embedded = match &options.embed {
+        Some(path) => quote! { Some(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", #path))) },
+        None => quote! { None },
+    };
+    let mut standard_keys = Vec::new();
+    for field in &fields {
+        let options = parse_field_options(field)?;
+        if !options.rest {
+            standard_keys.push(options.key);
+        }
+    }
+    let from_str = construct(quote! { "<str>" });
+    let from_map = construct(quote! { "<map>" });
+    let from_pairs = construct(quote! { "<pairs>" });
+    let from_type = construct(quote! { std::any::type_name::<T>() });
+    let from_default = construct(quote! { "<default>" });
+    let mmap_impl = generate_mmap_fn(&from_path);
+    let embed_fn = generate_embed_fn(options, quote! { std::io::Result<Self> });
+    let load_fn = generate_load_fn(options)?;
+
+    let mut schema = Vec::new();
+    for field in &fields {
+        let options = parse_field_options(field)?;
+        if !options.rest {
+            let ty = &field.ty;
+            let type_name = quote!(#ty).to_string().replace(' ', "").replace(',', ", ");
+            let required = options.is_required(field);
+            let [default, env, renamed_from] = [&options.default, &options.env, &options.renamed_from].map(|value| match value {
+                Some(value) => quote! { Some(#value) },
+                None => quote! { None },
+            });
+            let via = match &options.via {
+                Some(via) => {
+                    let via = quote!(#via).to_string().replace(' ', "");
+                    quote! { Some(#via) }
+                }
+                None => quote! { None },
+            };
+            let empty = match options.empty {
+                EmptyPolicy::AsIs => "as_is",
+                EmptyPolicy::None => "none",
+                EmptyPolicy::Error => "error",
+            };
+            let FieldOptions {
+                secret,
+                trim,
+                inline_comments,
+                decimal_comma,
+                underscores,
+                radix_prefixes,
+                from_string,
+                key,
+                ..
+            } = options;
+            schema.push(quote! {
+                ::props_util::__private::FieldSchema {
+                    key: #key, type_name: #type_name, required: #required, default: #default, env: #env, secret: #secret, renamed_from: #renamed_from,
+                    trim: #trim, inline_comments: #inline_comments, empty: #empty, decimal_comma: #decimal_comma, underscores: #underscores,
+                    radix_prefixes: #radix_prefixes, via: #via, from_string: #from_string,
+                }
+            });
+        }
+    }
+    let struct_name = input.ident.to_string();
+
+    let hashmap_fns = match options.no_hashmap {
+        true => proc_macro2::TokenStream::new(),
+        false => {
+            let (ht_arr, ht_capacity) = generate_hashmap_token_streams(fields.clone(), true)?;
+            let (key_arr, _) = generate_hashmap_token_streams(fields.clone(), false)?;
+            let snapshot_fn = cfg!(feature = "testing").then(|| {
+                quote! {
+                    /// Renders the effective configuration as a stable string for snapshot tests: one `key = value  # origin` line
+                    /// per key, sorted by key, with `secret` values shown as `***`. Pass `Provenance::default()` to leave the
+                    /// origins out.
+                    pub fn snapshot(&self, provenance : &::props_util::Provenance) -> String {
+                        ::props_util::testing::snapshot(&self.__redacted_values(), provenance)
+                    }
+                }
+            });
+            let mut secrets = Vec::new();
+            for field in &fields {
+                let options = parse_field_options(field)?;
+                if options.secret {
+                    secrets.push(options.key.clone());
+                }
+            }
+            quote! {
+                /// Consumes this instance and returns its properties as a map.
+                #[deprecated(note = "use `to_hash_map` or `HashMap::from(&value)`, which don't consume the value")]
+                pub fn into_hash_map(self) -> std::collections::HashMap<String, String> {
+                    self.to_hash_map()
+                }
+
+                /// Returns the properties of this instance as a map without consuming it.
+                ///
+                /// Every value is stored under its `key`. If the key differs from the field name, the value is stored under the field name as well,
+                /// so the map can be used to construct other types through `from`.
+                pub fn to_hash_map(&self) -> std::collections::HashMap<String, String> {
+                    use std::collections::HashMap;
+                    let mut hm = HashMap::<String, String>::with_capacity(#ht_capacity);
+                    #( #ht_arr )*
+                    hm
+                }
+
+                /// Returns the keys whose values differ between `self` and `other`, sorted by key.
+                ///
+                /// Values are compared in their string form, as written by `to_hash_map`. A key that is only set on one side, like an
+                /// `Option` field that is `None` on the other, has `None` as its old or new value.
+                pub fn diff(&self, other: &Self) -> Vec<::props_util::KeyChange> {
+                    ::props_util::__private::diff(self.__key_values(), other.__key_values())
+                }
+
+                /// Returns the changes like `diff`, with the values of `secret` fields shown as `***`, for logs and audit
+                /// trails. A changed secret is still listed, since the values are compared before they are redacted.
+                pub fn redacted_diff(&self, other: &Self) -> Vec<::props_util::KeyChange> {
+                    const SECRETS: &[&str] = &[ #( #secrets ),* ];
+                    let mut changes = self.diff(other);
+                    for change in changes.iter_mut().filter(|change| SECRETS.contains(&change.key.as_str())) {
+                        change.old = change.old.as_ref().map(|_| "***".to_string());
+                        change.new = change.new.as_ref().map(|_| "***".to_string());
+                    }
+                    changes
+                }
+
+                /// Returns a hash of the keys and values of this instance, as written by `to_hash_map`.
+                ///
+                /// The hash is stable across processes and Rust versions, so it can be stored to tell later whether a reloaded
+                /// file actually changed anything.
+                pub fn fingerprint(&self) -> u64 {
+                    ::props_util::__private::fingerprint(&self.__key_values())
+                }
+
+                /// Returns whether any key has a different value in `other`. A file that was touched but still holds the same
+                /// content loads into an instance that hasn't changed.
+                pub fn has_changed(&self, other: &Self) -> bool {
+                    self.__key_values() != other.__key_values()
+                }
+
+                fn __key_values(&self) -> std::collections::BTreeMap<String, String> {
+                    let mut hm = std::collections::BTreeMap::<String, String>::new();
+                    #( #key_arr )*
+                    hm
+                }
+
+                #[doc(hidden)]
+                pub fn __redacted_values(&self) -> std::collections::BTreeMap<String, String> {
+                    const SECRETS: &[&str] = &[ #( #secrets ),* ];
+                    let mut values = self.__key_values();
+                    for (key, value) in values.iter_mut() {
+                        if SECRETS.contains(&key.as_str()) {
+                            *value = "***".to_string();
+                        }
+                    }
+                    values
+                }
+
+                /// Describes where the value of every field came from, one line per field in declaration order, as in
+                /// `server.port = 9090 (from env SERVER_PORT, overriding config.properties:12)`. Values of `secret` fields are
+                /// shown as `***`.
+                ///
+                /// `provenance` is the report returned along with this instance by a loader like `from_file_with_provenance`.
+                pub fn explain(&self, provenance : &::props_util::Provenance) -> String {
+                    ::props_util::__private::explain(&self.__redacted_values(), provenance)
+                }
+
+                /// Returns a JSON object describing every field, for attaching to a support ticket instead of the raw files.
+                ///
+                /// Each field lists its key, type, whether it is required, its `default` and `env`, the effective value and the
+                /// origins from `provenance`. Values and defaults of `secret` fields are shown as `***`.
+                pub fn diagnostic_dump(&self, provenance : &::props_util::Provenance) -> String {
+                    ::props_util::__private::diagnostic_dump(#struct_name, Self::__SCHEMA, &self.__redacted_values(), provenance)
+                }
+
+                #snapshot_fn
+
+                /// Returns the properties of this instance as [`Props`](::props_util::Props), with the same entries as `to_hash_map`.
+                ///
+                /// Useful to enumerate the keys, for example with `iter_prefix`.
+                pub fn to_props(&self) -> ::props_util::Props {
+                    ::props_util::Props::from(self.to_hash_map())
+                }
+            }
+        }
+    };
+
+    let new_impl = quote! {
+
+        /// Loads properties from a file into an instance of this struct.
+        ///
+        /// # Example
+        ///
+        /// ```rust,no_run
+        /// use props_util::Properties;
+        /// use std::io::Result;
+        ///
+        /// #[derive(Properties, Debug)]
+        /// struct Config {
+        ///     #[prop(key = "server.host", default = "localhost")]
+        ///     host: String,
+        ///
+        ///     #[prop(key = "server.port", default = "8080")]
+        ///     port: u16,
+        ///
+        ///     #[prop(key = "debug.enabled", default = "false")]
+        ///     debug: bool,
+        /// }
+        ///
+        /// fn main() -> Result<()> {
+        ///
+        ///     let config = Config::from_file("config.properties")?;
+        ///     println!("Server: {}:{}", config.host, config.port);
+        ///     println!("Debug mode: {}", config.debug);
+        ///     Ok(())
+        /// }
+        /// ```
+        ///
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_file(path : &str) -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || path.to_string(), || {
+                let content = ::props_util::__private::read_content(path, &::props_util::parse::Limits::new())?;
+                let propmap = ::props_util::__private::parse_content(&content, path)?;
+                Ok(#from_path)
+            })
+        }
+
+        /// Loads properties from a file like `from_file`, failing with `LimitExceeded` as soon as the file goes over
+        /// `limits`. Use this for files that come from users.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_file_limited(path : &str, limits : &::props_util::parse::Limits) -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || path.to_string(), || {
+                let content = ::props_util::__private::read_content(path, limits)?;
+                let propmap = ::props_util::__private::parse_content(&content, path)?;
+                Ok(#from_path)
+            })
+        }
+
+        /// Loads one instance per namespace under `prefix` from the properties file at `path`, like `from_namespaced`.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_file_namespaced(path : &str, prefix : &str) -> std::io::Result<std::collections::HashMap<String, Self>> {
+            Self::from_namespaced(::props_util::__private::read_file(path)?, prefix)
+        }
+
+        /// Loads the properties file at `path` for the active `profile`, like `from_with_profile`.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_file_with_profile(path : &str, profile : &str) -> std::io::Result<Self> {
+            Self::from_with_profile(::props_util::__private::read_file(path)?, profile)
+        }
+
+        /// Loads the layers most services want, each overriding the ones before: the `embed` file if there is one,
+        /// `/etc/app/app.properties`, the file of `load_user`, the environment variables prefixed with the upper case `app`
+        /// and `--key=value` arguments of the process.
+        ///
+        /// For `app` set to `myapp`, the key `server.host` is read from `MYAPP_SERVER_HOST`. Missing files are skipped.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn standard(app : &str) -> std::io::Result<Self> {
+            Self::standard_with_args(app, std::env::args().skip(1))
+        }
+
+        /// Loads the layers of `standard`, taking the `--key=value` arguments from `args` instead of the process.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn standard_with_args<I: IntoIterator<Item = String>>(app : &str, args : I) -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || app.to_string(), || {
+                let propmap = ::props_util::__private::read_standard(app, #embedded, &[ #( #standard_keys ),* ], args)?;
+                Ok(#from_app)
+            })
+        }
+
+        /// Loads `app/app.properties` from the per-user configuration directory of the platform.
+        ///
+        /// `$XDG_CONFIG_HOME` is searched first, then `~/Library/Application Support` on macOS, `%APPDATA%` on Windows and
+        /// `~/.config` elsewhere. Fails with `NotFound` if none of them has the file.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn load_user(app : &str) -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || app.to_string(), || {
+                let propmap = ::props_util::__private::read_user(app)?;
+                Ok(#from_app)
+            })
+        }
+
+        /// Loads the properties file at `path` and, if it exists, the sibling `.local.` file that overrides it.
+        ///
+        /// For `config.properties` the local file is `config.local.properties`, which is typically kept out of version
+        /// control to hold machine specific settings.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_file_with_local(path : &str) -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || path.to_string(), || {
+                let propmap = ::props_util::__private::read_with_local(path)?;
+                Ok(#from_path)
+            })
+        }
+
+        /// Loads properties like `from_file`, but falls back to `default` if the file does not exist.
+        ///
+        /// Every other error, such as a malformed line or a file that can't be read, is still returned.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_file_or_default(path : &str) -> std::io::Result<Self> {
+            match Self::from_file(path) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+                result => result,
+            }
+        }
+
+        /// Loads the `defaults` embedded into the binary, usually with `include_str!`, overridden by the properties
+        /// file at `path` if it exists.
+        ///
+        /// The embedded file holds a value for every required key, so they always resolve, and the file on disk only
+        /// needs the keys that differ. A missing file loads the defaults alone, while every other error, such as a
+        /// malformed line, is still returned.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn layered(defaults : &str, path : &str) -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || path.to_string(), || {
+                let propmap = ::props_util::__private::read_layered(defaults, path)?;
+                Ok(#from_path)
+            })
+        }
+
+        /// Checks that every field of the properties file at `path` is present and parses, without constructing an instance.
+        ///
+        /// Values are resolved like in `from_file`, including `env` and defaults. Reading the file and malformed lines are
+        /// reported as errors, while problems with individual fields are all collected in the returned report.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn validate_file(path : &str) -> std::io::Result<::props_util::ValidationReport> {
+            #read_file
+            let mut report = ::props_util::ValidationReport::default();
+            #( #validations )*
+            Ok(report)
+        }
+
+        /// Re-parses the fields whose keys are in `propmap` and assigns them, leaving every other field untouched.
+        ///
+        /// The update is all or nothing: if any value fails to parse, nothing is assigned and the returned report lists
+        /// every failing key. Keys that don't belong to a field are ignored.
+        pub fn apply_overrides<K, V, S>(&mut self, propmap : &std::collections::HashMap<K, V, S>) -> ::core::result::Result<(), ::props_util::ValidationReport>
+        where
+            K: std::borrow::Borrow<str> + std::hash::Hash + Eq,
+            V: AsRef<str>,
+            S: std::hash::BuildHasher,
+        {
+            #normalize
+            #override_templates
+            let mut report = ::props_util::ValidationReport::default();
+            #( #overrides )*
+            Ok(())
+        }
+
+        #[doc(hidden)]
+        pub const __RENAMED: &'static [(&'static str, &'static str)] = &[ #( #renamed ),* ];
+
+        #[doc(hidden)]
+        pub const __UNTRIMMED: &'static [&'static str] = &[ #( #untrimmed ),* ];
+
+        #[doc(hidden)]
+        pub const __SCHEMA: &'static [::props_util::__private::FieldSchema] = &[ #( #schema ),* ];
+
+        /// Returns the key, type, `default` and `env` of every field as a JSON object, with the defaults of `secret` fields
+        /// shown as `***`. Saved to a file, this is the schema `props-util validate` checks properties files against.
+        pub fn schema_json() -> String {
+            ::props_util::__private::schema_json(#struct_name, Self::__SCHEMA)
+        }
+
+        /// Loads properties like `from_file`, also returning the `renamed_from` keys that were read under their old name.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_file_with_report(path : &str) -> std::io::Result<(Self, ::props_util::MigrationReport)> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || path.to_string(), || {
+                let propmap = ::props_util::__private::read_file(path)?;
+                let report = ::props_util::__private::migration_report(Self::__RENAMED, &[path], std::slice::from_ref(&propmap));
+                Ok((#from_path, report))
+            })
+        }
+
+        /// Loads properties like `from_files`, also returning the `renamed_from` keys that were read under their old name
+        /// along with the file each of them came from.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_files_with_report(paths : &[&str]) -> std::io::Result<(Self, ::props_util::MigrationReport)> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || paths.join(", "), || {
+                let maps = ::props_util::__private::read_each(paths)?;
+                let report = ::props_util::__private::migration_report(Self::__RENAMED, paths, &maps);
+                let propmap = maps.into_iter().flatten().collect::<std::collections::HashMap<String, String>>();
+                Ok((#from_paths, report))
+            })
+        }
+
+        /// Loads properties like `from_file`, also returning where the value of every field came from, such as the line of
+        /// the file, an `env` variable or the `default`.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_file_with_provenance(path : &str) -> std::io::Result<(Self, ::props_util::Provenance)> {
+            Self::from_files_with_provenance(&[path])
+        }
+
+        /// Loads properties like `from_files`, also returning where the value of every field came from. A key set in
+        /// several files lists the files it was overridden in.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_files_with_provenance(paths : &[&str]) -> std::io::Result<(Self, ::props_util::Provenance)> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || paths.join(", "), || {
+                let (propmap, origins) = ::props_util::__private::read_with_origins(paths)?;
+                Ok((#from_paths, Self::__provenance(&origins)))
+            })
+        }
+
+        /// Loads the layers of `standard_with_args`, also returning where the value of every field came from, including the
+        /// arguments and environment variables of the layering.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn standard_with_provenance<I: IntoIterator<Item = String>>(app : &str, args : I) -> std::io::Result<(Self, ::props_util::Provenance)> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || app.to_string(), || {
+                let (propmap, origins) = ::props_util::__private::read_standard_with_origins(app, #embedded, &[ #( #standard_keys ),* ], args)?;
+                Ok((#from_app, Self::__provenance(&origins)))
+            })
+        }
+
+        #provenance
+
+        /// Returns the `(old, new)` pairs of `renamed_from` keys that `propmap` only contains under their old name.
+        ///
+        /// These are the keys a load of `propmap` reads through their previous name, which the configuration should be
+        /// updated to no longer use.
+        pub fn renamed_keys_in<K, V, S>(propmap : &std::collections::HashMap<K, V, S>) -> Vec<(&'static str, &'static str)>
+        where
+            K: std::borrow::Borrow<str> + std::hash::Hash + Eq,
+            S: std::hash::BuildHasher,
+        {
+            Self::__RENAMED.iter().filter(|(old, new)| !propmap.contains_key(*new) && propmap.contains_key(*old)).copied().collect()
+        }
+
+        /// Returns the keys of `required_keys` that the properties file at `path` doesn't contain.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn missing_keys(path : &str) -> std::io::Result<Vec<String>> {
+            #read_file
+            Ok(Self::required_keys().iter().filter(|key| !propmap.contains_key(**key)).map(|key| key.to_string()).collect())
+        }
+
+        /// Loads the first of `paths` that exists, returning the instance along with the path that was used.
+        ///
+        /// The paths are tried in order and a leading `~/` is expanded to the home directory, which makes this a
+        /// search path like `["/etc/app.properties", "~/.config/app.properties", "./app.properties"]`. Missing files
+        /// are skipped, but any other error, such as a malformed line, is returned. Fails with `NotFound` if none of
+        /// the files exist.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_first_of<'a>(paths : &[&'a str]) -> std::io::Result<(Self, &'a str)> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || paths.join(", "), || {
+                let (propmap, path) = ::props_util::__private::read_first(paths)?;
+                Ok((#from_path, path))
+            })
+        }
+
+        /// Parses properties from a string, using the same format as `from_file`.
+        pub fn from_str(content : &str) -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || "<str>".to_string(), || {
+                let propmap = ::props_util::parse::parse_str_untrimmed(content)?;
+                Ok(#from_str)
+            })
+        }
+
+        /// Parses properties from a string like `from_str`, failing with `LimitExceeded` if it goes over `limits`.
+        pub fn from_str_limited(content : &str, limits : &::props_util::parse::Limits) -> std::io::Result<Self> {
+            limits.check(content)?;
+            Self::from_str(content)
+        }
+
+        /// Creates an instance from a borrowed map of properties, without taking ownership of it.
+        pub fn from_map<K, V, S>(propmap : &std::collections::HashMap<K, V, S>) -> std::io::Result<Self>
+        where
+            K: std::borrow::Borrow<str> + std::hash::Hash + Eq,
+            V: AsRef<str>,
+            S: std::hash::BuildHasher,
+        {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || "<map>".to_string(), || {
+                Ok(#from_map)
+            })
+        }
+
+        /// Creates an instance from `(key, value)` pairs. Later pairs override earlier pairs with the same key.
+        ///
+        /// Useful in tests, where it saves building a `HashMap<String, String>` by hand.
+        pub fn from_pairs<I, K, V>(pairs : I) -> std::io::Result<Self>
+        where
+            I: IntoIterator<Item = (K, V)>,
+            K: Into<String>,
+            V: Into<String>,
+        {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || "<pairs>".to_string(), || {
+                let propmap = pairs.into_iter().map(|(key, value)| (key.into(), value.into())).collect::<std::collections::HashMap<String, String>>();
+                Ok(#from_pairs)
+            })
+        }
+
+        /// Loads and merges several properties files, in order. Keys in later files override the same keys in earlier files.
+        ///
+        /// With the `parallel` feature of `props-util`, the files are read and parsed concurrently before being merged.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_files(paths : &[&str]) -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || paths.join(", "), || {
+                let propmap = ::props_util::__private::merge_files(paths)?;
+                Ok(#from_paths)
+            })
+        }
+
+        /// Loads and merges every file matching `pattern`, like `from_files` with the matches sorted by path.
+        ///
+        /// Only the file name may contain wildcards, `*` matching any number of characters and `?` a single one, as in
+        /// `conf.d/*.properties`. If nothing matches, the instance is built from defaults alone.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_glob(pattern : &str) -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || pattern.to_string(), || {
+                let propmap = ::props_util::__private::merge_glob(pattern)?;
+                Ok(#from_pattern)
+            })
+        }
+
+        /// Loads and merges every `*.properties` file in the directory `path` in lexical order, so later files override
+        /// earlier ones like in a Debian style `conf.d` directory. Errors name the file they occurred in.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_dir_merged(path : &str) -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || path.to_string(), || {
+                let propmap = ::props_util::__private::merge_dir(path)?;
+                Ok(#from_path)
+            })
+        }
+
+        #mmap_impl
+
+        #embed_fn
+
+        #load_fn
+
+        #hashmap_fns
+
+        /// Convert from another type that implements `Properties` into this type.
+        ///
+        /// Pass the source by reference (`&other`), which converts through `to_hash_map` and leaves the source value untouched.
+        /// The conversion will succeed only if the source type's keys match this type's keys. All the required keys must be present in the source type.
+        ///
+        ///
+        /// # Example
+        ///
+        /// ```rust,no_run
+        /// use props_util::Properties;
+        /// use std::io::Result;
+        ///
+        /// #[derive(Properties, Debug)]
+        /// struct ServerConfig {
+        ///     #[prop(key = "host", default = "localhost")]
+        ///     host: String,
+        ///     #[prop(key = "port", default = "8080")]
+        ///     port: u16,
+        /// }
+        ///
+        /// #[derive(Properties, Debug)]
+        /// struct ClientConfig {
+        ///     #[prop(key = "host", default = "localhost")]  // Note: using same key as ServerConfig
+        ///     server_host: String,
+        ///     #[prop(key = "port", default = "8080")]      // Note: using same key as ServerConfig
+        ///     server_port: u16,
+        /// }
+        ///
+        /// fn main() -> Result<()> {
+        ///     let server_config = ServerConfig::default()?;
+        ///     let client_config = ClientConfig::from(&server_config)?;
+        ///     println!("Server host: {}", client_config.server_host);
+        ///     println!("Server port: {}", client_config.server_port);
+        ///     Ok(())
+        /// }
+        /// ```
+        pub fn from<T>(other: T) -> std::io::Result<Self>
+        where
+            T: Into<std::collections::HashMap<String, String>>
+        {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || "<map>".to_string(), || {
+                let propmap: std::collections::HashMap<String, String> = other.into();
+                Ok(#from_map)
+            })
+        }
+
+        /// Converts from a reference to another type that converts into a map, such as any type deriving `Properties`.
+        ///
+        /// Like `from`, but the source is left untouched.
+        pub fn convert_from<T>(other: &T) -> std::io::Result<Self>
+        where
+            for<'a> std::collections::HashMap<String, String>: From<&'a T>,
+        {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || std::any::type_name::<T>().to_string(), || {
+                let propmap = std::collections::HashMap::<String, String>::from(other);
+                Ok(#from_type)
+            })
+        }
+
+        /// Loads one instance per namespace from a map whose keys are grouped under `prefix`, keyed by the name of
+        /// the namespace.
+        ///
+        /// With the prefix `tenant.`, the keys `tenant.acme.port` and `tenant.globex.port` load the instances `acme`
+        /// and `globex`, each reading its key `port`. The namespaces are discovered from the keys, and each one is
+        /// loaded like `from`, with its own defaults. Errors name the full key, such as `tenant.acme.port`.
+        pub fn from_namespaced<T>(other: T, prefix: &str) -> std::io::Result<std::collections::HashMap<String, Self>>
+        where
+            T: Into<std::collections::HashMap<String, String>>
+        {
+            ::props_util::__private::load_namespaced(other.into(), prefix, Self::from)
+        }
+
+        /// Loads from a map like `from`, resolving the keys qualified with a profile, like `cache.size@prod`, for
+        /// the active `profile`.
+        ///
+        /// A key qualified with `profile` wins over the unqualified key, which is the fallback for every other profile,
+        /// and keys qualified with other profiles are ignored.
+        pub fn from_with_profile<T>(other: T, profile: &str) -> std::io::Result<Self>
+        where
+            T: Into<std::collections::HashMap<String, String>>
+        {
+            Self::from(::props_util::__private::resolve_profile(other.into(), profile))
+        }
+
+        pub fn default() -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || "<default>".to_string(), || {
+                use std::collections::HashMap;
+                let mut propmap = HashMap::<String, String>::new();
+                Ok(#from_default)
+            })
+        }
+    };
+
+    Ok(new_impl)
+}
+
+/// Generates the expression constructing `Self` from `propmap`, running the `before_parse` and `after_parse` hooks.
+/// With the `tracing` feature, `load_event` is emitted with `source` naming where the properties came from.
+fn generate_construct(init_arr: &[proc_macro2::TokenStream], options: &StructOptions, normalize: &proc_macro2::TokenStream, load_event: &proc_macro2::TokenStream, source: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if load_event.is_empty() && normalize.is_empty() && options.before_parse.is_none() && options.after_parse.is_none() {
+        return quote! { Self { #( #init_arr ),* } };
+    }
+
+    // The hook gets an owned copy, since constructors borrow maps of any key and value type
+    let before_parse = options.before_parse.as_ref().map(|before_parse| {
+        quote! {
+            let mut propmap = propmap
+                .iter()
+                .map(|(key, val)| (::core::borrow::Borrow::<str>::borrow(key).to_string(), ::core::convert::AsRef::<str>::as_ref(val).to_string()))
+                .collect::<std::collections::HashMap<String, String>>();
+            ::props_util::__private::trim_values(&mut propmap, Self::__UNTRIMMED);
+            #before_parse(&mut propmap);
+        }
+    });
+    let value = match &options.after_parse {
+        Some(after_parse) => quote! {
+            let mut value = Self { #( #init_arr ),* };
+            #after_parse(&mut value)?;
+        },
+        None => quote! { let value = Self { #( #init_arr ),* }; },
+    };
+    let source = match load_event.is_empty() {
+        true => quote! {},
+        false => quote! { let source = || #source; },
+    };
+
+    quote! {
+        {
+            #before_parse
+            #normalize
+            #value
+            #source
+            #load_event
+            value
+        }
+    }
+}
+
+/// Generates the statement replacing `propmap` by a copy whose keys are spelled like the field keys for
+/// `#[prop(case_insensitive)]`, or nothing without it.
+fn generate_key_normalization(options: &StructOptions, fields: &Punctuated<Field, Comma>) -> syn::Result<proc_macro2::TokenStream> {
+    if !options.case_insensitive {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+
+    let mut known = Vec::new();
+    for field in fields {
+        let options = parse_field_options(field)?;
+        if !options.rest {
+            known.extend([Some(options.key), options.renamed_from].into_iter().flatten());
+        }
+    }
+
+    Ok(quote! {
+        let propmap = ::props_util::__private::canonical_keys(propmap.iter(), &[ #( #known ),* ]);
+    })
+}
+
+/// Generates the statement replacing `propmap` by a copy with the templates in the values of the fields rendered for
+/// `#[prop(templates)]`, or nothing without it. `on_error` turns the error of a broken template into what the
+/// surrounding function returns.
+fn generate_templates(options: &StructOptions, fields: &Punctuated<Field, Comma>, on_error: proc_macro2::TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    if !options.templates {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    let mut keys = Vec::new();
+    for field in fields {
+        let options = parse_field_options(field)?;
+        if !options.rest {
+            keys.extend([Some(options.key), options.renamed_from].into_iter().flatten());
+        }
+    }
+    Ok(quote! {
+        let propmap = match ::props_util::__private::render_templates(propmap.iter(), &[ #( #keys ),* ]) {
+            Ok(propmap) => propmap,
+            Err(e) => #on_error,
+        };
+    })
+}
+
+/// Generates an event that logs the source of a loaded `value`, the number of keys and which fields used their
+/// default or came from env. Values of `secret` fields are redacted.
+#[cfg(feature = "tracing")]
+fn generate_load_event(input: &DeriveInput, options: &StructOptions, fields: &Punctuated<Field, Comma>) -> syn::Result<proc_macro2::TokenStream> {
+    let tracing = quote! { ::props_util::__private::tracing };
+
+    let mut checks = Vec::new();
+    for field in fields {
+        let field_options = parse_field_options(field)?;
+        if field_options.rest {
+            continue;
+        }
+
+        let key = &field_options.key;
+        let lookup = field_options.lookup();
+        let overridden = match cfg!(feature = "testing") {
+            true => quote! { ::props_util::__private::key_override(#key).is_some() },
+            false => quote! { false },
+        };
+        // Follows the resolution order of `generate_field_value`
+        let arms = field_options.order.iter().filter_map(|source| match source {
+            Source::File => Some(quote! { _ if #lookup.is_some() => {} }),
+            Source::Env => field_options.env.as_ref().map(|env| quote! { _ if ::props_util::__private::env_var(#env).is_some() => env.push(#key), }),
+            Source::Default => field_options.default.as_ref().map(|_| quote! { _ => defaults.push(#key), }),
+        });
+
+        checks.push(quote! {
+            match () {
+                _ if #overridden => {}
+                #( #arms )*
+                _ => {}
+            }
+        });
+    }
+
+    let message = format!("loaded `{}`", input.ident);
+    let event = match options.no_hashmap {
+        true => quote! { #tracing::info!(target: "props_util", source = %source(), keys = propmap.len(), ?defaults, ?env, #message) },
+        false => quote! { #tracing::info!(target: "props_util", source = %source(), keys = propmap.len(), ?defaults, ?env, values = ?value.__redacted_values(), #message) },
+    };
+
+    Ok(quote! {
+        if #tracing::enabled!(target: "props_util", #tracing::Level::INFO) {
+            let mut defaults = Vec::<&str>::new();
+            let mut env = Vec::<&str>::new();
+            #( #checks )*
+            #event;
+        }
+    })
+}
+
+#[cfg(not(feature = "tracing"))]
+fn generate_load_event(_: &DeriveInput, _: &StructOptions, _: &Punctuated<Field, Comma>) -> syn::Result<proc_macro2::TokenStream> {
+    Ok(proc_macro2::TokenStream::new())
+}
+
+/// Generates `__provenance`, which resolves the origin of every field from the `origins` of the properties it was
+/// loaded from, following the same order as `generate_field_value`.
+fn generate_provenance(fields: &Punctuated<Field, Comma>) -> syn::Result<proc_macro2::TokenStream> {
+    let mut entries = Vec::new();
+    for field in fields {
+        let field_options = parse_field_options(field)?;
+        if field_options.rest {
+            continue;
+        }
+
+        let key = &field_options.key;
+        let file = match &field_options.renamed_from {
+            Some(old) => quote! { origins.get(#key).or_else(|| origins.get(#old)) },
+            None => quote! { origins.get(#key) },
+        };
+        let overridden = cfg!(feature = "testing").then(|| {
+            quote! {
+                if ::props_util::__private::key_override(#key).is_some() {
+                    field.push(::props_util::Origin::Override);
+                }
+            }
+        });
+        let sources = field_options.order.iter().filter_map(|source| match source {
+            Source::File => Some(quote! { field.extend(#file.into_iter().flat_map(|origins| origins.iter().rev()).cloned()); }),
+            Source::Env => field_options.env.as_ref().map(|env| {
+                quote! {
+                    if ::props_util::__private::env_var(#env).is_some() {
+                        field.push(::props_util::Origin::Env(#env.to_string()));
+                    }
+                }
+            }),
+            Source::Default => field_options.default.as_ref().map(|_| quote! { field.push(::props_util::Origin::Default); }),
+        });
+
+        entries.push(quote! {
+            let mut field = Vec::new();
+            #overridden
+            #( #sources )*
+            provenance.__push(#key, field);
+        });
+    }
+
+    if entries.is_empty() {
+        return Ok(quote! {
+            #[doc(hidden)]
+            pub fn __provenance(_ : &::props_util::__private::Origins) -> ::props_util::Provenance {
+                ::props_util::Provenance::default()
+            }
+        });
+    }
+
+    Ok(quote! {
+        #[doc(hidden)]
+        pub fn __provenance(origins : &::props_util::__private::Origins) -> ::props_util::Provenance {
+            let mut provenance = ::props_util::Provenance::default();
+            #( #entries )*
+            provenance
+        }
+    })
+}
+
+/// Generates a statement per field that resolves its value and pushes any error to `report`.
+fn generate_validations(fields: &Punctuated<Field, Comma>) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let mut validations = Vec::new();
+    for field in fields {
+        if parse_field_options(field)?.rest {
+            continue;
+        }
+
+        let field_type = &field.ty;
+        let value = generate_field_value(field, fields)?;
+        validations.push(quote! {
+            let result = (|| -> ::core::result::Result<#field_type, ::props_util::parse::Error> { Ok(#value) })();
+            if let Err(e) = result {
+                report.push(e);
+            }
+        });
+    }
+    Ok(validations)
+}
+
+/// Generates the body of `apply_overrides`, which first parses every provided key into a local and only assigns the
+/// locals once all of them parsed.
+fn generate_overrides(fields: &Punctuated<Field, Comma>) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let mut parsed = Vec::new();
+    let mut assigned = Vec::new();
+
+    for field in fields {
+        let options = parse_field_options(field)?;
+        if options.rest {
+            continue;
+        }
+
+        let field_name = field.ident.as_ref().unwrap();
+        let field_type = &field.ty;
+        let local = format_ident!("__override_{}", field_name);
+        let raw_value = options.clean_value(quote! { val });
+        let raw_value = quote! { Some(#raw_value) };
+        let value = match wrapped_type(field_type, "Option") {
+            Some(inner) => generate_field_init_quote(inner, raw_value, &options, true),
+            None => generate_field_init_quote(field_type, raw_value, &options, false),
+        };
+        let lookup = options.lookup();
+
+        parsed.push(quote! {
+            let #local = match #lookup {
+                Some(val) => match (|| -> ::core::result::Result<#field_type, ::props_util::parse::Error> { Ok(#value) })() {
+                    Ok(value) => Some(value),
+                    Err(e) => {
+                        report.push(e);
+                        None
+                    }
+                },
+                None => None,
+            };
+        });
+        assigned.push(quote! {
+            if let Some(value) = #local {
+                self.#field_name = value;
+            }
+        });
+    }
+
+    parsed.push(quote! {
+        if !report.is_valid() {
+            return Err(report);
+        }
+    });
+    parsed.extend(assigned);
+    Ok(parsed)
+}
+
+/// Generates `load` for `#[prop(file = "...")]`, reading the configured path or the one in `file_env` if it is set.
+fn generate_load_fn(options: &StructOptions) -> syn::Result<proc_macro2::TokenStream> {
+    let Some(file) = &options.file else {
+        return match &options.file_env {
+            Some(file_env) => Err(Error::new_spanned(file_env, "`file_env` requires `file`")),
+            None => Ok(proc_macro2::TokenStream::new()),
+        };
+    };
+
+    let (doc, path) = match &options.file_env {
+        Some(file_env) => (
+            format!("Loads `{}`, or the file named by the environment variable `{}` if it is set.", file.value(), file_env.value()),
+            quote! { ::props_util::__private::env_var(#file_env).unwrap_or_else(|| #file.to_string()) },
+        ),
+        None => (format!("Loads `{}`.", file.value()), quote! { #file.to_string() }),
+    };
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn load() -> std::io::Result<Self> {
+            Self::from_file(&#path)
+        }
+    })
+}
+
+/// Generates `from_embedded` for `#[prop(embed = "...")]`. The path is relative to the crate root of the deriving crate.
+fn generate_embed_fn(options: &StructOptions, result_type: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let Some(path) = &options.embed else {
+        return proc_macro2::TokenStream::new();
+    };
+
+    let doc = format!("Parses `{}`, which was embedded into the binary at compile time.", path.value());
+    quote! {
+        #[doc = #doc]
+        pub fn from_embedded() -> #result_type {
+            Self::from_str(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", #path)))
+        }
+    }
+}
+
+/// Generates `global` and `init_global` for `#[prop(global)]`, backed by a `OnceLock` holding the first instance.
+fn generate_global_fns(struct_name: &syn::Ident) -> proc_macro2::TokenStream {
+    let not_initialized = format!("`{struct_name}::global()` called before `{struct_name}::init_global`");
+    let already_initialized = format!("global `{struct_name}` is already initialized");
+    let replace_fn = generate_replace_global_fn(struct_name, &not_initialized);
+
+    quote! {
+        // In an `Arc` so that `global_arc` of `global-replace` can share the first instance
+        fn __global_cell() -> &'static std::sync::OnceLock<std::sync::Arc<#struct_name>> {
+            static GLOBAL: std::sync::OnceLock<std::sync::Arc<#struct_name>> = std::sync::OnceLock::new();
+            &GLOBAL
+        }
+
+        /// Loads the properties file at `path` and stores it as the global instance returned by `global`.
+        ///
+        /// Fails with `AlreadyExists` if the global instance was initialized before, in which case the file is still
+        /// read but discarded.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn init_global(path : &str) -> std::io::Result<&'static Self> {
+            Self::set_global(Self::from_file(path)?)
+        }
+
+        /// Initializes the global instance from the properties file at `path` unless that already happened.
+        ///
+        /// Returns whether this call initialized it. The file is only read if the global instance isn't set yet.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn try_init_global(path : &str) -> std::io::Result<bool> {
+            if Self::__global_cell().get().is_some() {
+                return Ok(false);
+            }
+            Ok(Self::__global_cell().set(std::sync::Arc::new(Self::from_file(path)?)).is_ok())
+        }
+
+        /// Stores `value` as the global instance returned by `global`. Fails with `AlreadyExists` if it was initialized before.
+        pub fn set_global(value : Self) -> std::io::Result<&'static Self> {
+            let cell = Self::__global_cell();
+            cell.set(std::sync::Arc::new(value)).map_err(|_| std::io::Error::new(std::io::ErrorKind::AlreadyExists, #already_initialized))?;
+            Ok(Self::global())
+        }
+
+        /// Returns the global instance.
+        ///
+        /// # Panics
+        ///
+        /// Panics if neither `init_global` nor `set_global` has been called yet.
+        pub fn global() -> &'static Self {
+            Self::__global_cell().get().expect(#not_initialized)
+        }
+
+        #replace_fn
+    }
+}
+
+#[cfg(feature = "global-replace")]
+fn generate_replace_global_fn(struct_name: &syn::Ident, not_initialized: &str) -> proc_macro2::TokenStream {
+    quote! {
+        fn __replaced_global() -> &'static std::sync::RwLock<Option<std::sync::Arc<#struct_name>>> {
+            static REPLACED: std::sync::RwLock<Option<std::sync::Arc<#struct_name>>> = std::sync::RwLock::new(None);
+            &REPLACED
+        }
+
+        /// Returns the current global instance, the one passed to the last `replace_global` if it was ever called.
+        ///
+        /// # Panics
+        ///
+        /// Panics if neither `init_global` nor `set_global` has been called yet.
+        pub fn global_arc() -> std::sync::Arc<Self> {
+            let replaced = Self::__replaced_global().read().unwrap_or_else(|e| e.into_inner()).clone();
+            replaced.unwrap_or_else(|| std::sync::Arc::clone(Self::__global_cell().get().expect(#not_initialized)))
+        }
+
+        /// Replaces the global instance returned by `global_arc` and returns the previous one, or `None` if this call
+        /// initialized it.
+        ///
+        /// `global` keeps returning the instance set at initialization, since its references are `'static`. Readers
+        /// holding an earlier `Arc` keep seeing that instance, which is freed once the last of them is dropped.
+        pub fn replace_global(value : Self) -> Option<std::sync::Arc<Self>> {
+            let cell = Self::__global_cell();
+            let value = match cell.set(std::sync::Arc::new(value)) {
+                Ok(()) => return None,
+                Err(value) => value,
+            };
+
+            let mut replaced = Self::__replaced_global().write().unwrap_or_else(|e| e.into_inner());
+            Some(replaced.replace(value).unwrap_or_else(|| std::sync::Arc::clone(cell.get().unwrap())))
+        }
+    }
+}
+
+#[cfg(not(feature = "global-replace"))]
+fn generate_replace_global_fn(_: &syn::Ident, _: &str) -> proc_macro2::TokenStream {
+    proc_macro2::TokenStream::new()
+}
+
+/// Generates `Serialize` and `Deserialize` for `#[prop(serde)]`. Both go through a flat map from key to string value,
+/// so the serialized form matches the properties a value is loaded from.
+fn generate_serde_impl(struct_name: &syn::Ident, options: &StructOptions) -> syn::Result<proc_macro2::TokenStream> {
+    if !options.serde {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    if !cfg!(feature = "serde") {
+        return Err(Error::new_spanned(struct_name, "`serde` requires the `serde` feature of props-util"));
+    }
+    if options.no_hashmap {
+        return Err(Error::new_spanned(struct_name, "`serde` can't be combined with `no_hashmap`, serializing needs `Display` on every field type"));
+    }
+
+    let serde = quote! { ::props_util::__private::serde };
+    Ok(quote! {
+        impl #serde::Serialize for #struct_name {
+            fn serialize<S: #serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {
+                serializer.collect_map(self.__key_values())
+            }
+        }
+
+        impl<'de> #serde::Deserialize<'de> for #struct_name {
+            fn deserialize<D: #serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {
+                let propmap = <std::collections::HashMap<String, String> as #serde::Deserialize>::deserialize(deserializer)?;
+                Self::from_map(&propmap).map_err(<D::Error as #serde::de::Error>::custom)
+            }
+        }
+    })
+}
+
+/// Generates `Display` for `#[prop(display)]`, writing one `key=value` line per key in sorted order. Values of
+/// `secret` fields are replaced with `***`.
+fn generate_display_impl(struct_name: &syn::Ident, options: &StructOptions) -> syn::Result<proc_macro2::TokenStream> {
+    if !options.display {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    if options.no_hashmap {
+        return Err(Error::new_spanned(struct_name, "`display` can't be combined with `no_hashmap`, printing needs `Display` on every field type"));
+    }
+
+    Ok(quote! {
+        impl ::core::fmt::Display for #struct_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                for (key, value) in self.__redacted_values() {
+                    writeln!(f, "{key}={value}")?;
+                }
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Generates `Debug` for `#[prop(debug)]`, which prints like `#[derive(Debug)]` but shows `secret` fields as `***`.
+fn generate_debug_impl(struct_name: &syn::Ident, options: &StructOptions, fields: &Punctuated<Field, Comma>) -> syn::Result<proc_macro2::TokenStream> {
+    if !options.debug {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+
+    let mut entries = Vec::new();
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_name_str = field_name.to_string();
+        entries.push(match parse_field_options(field)?.secret {
+            true => quote! { .field(#field_name_str, &format_args!("***")) },
+            false => quote! { .field(#field_name_str, &self.#field_name) },
+        });
+    }
+
+    let struct_name_str = struct_name.to_string();
+    Ok(quote! {
+        impl ::core::fmt::Debug for #struct_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(#struct_name_str) #( #entries )* .finish()
+            }
+        }
+    })
+}
+
+/// Generates `<Name>Patch`, holding an `Option` of every field that is only `Some` for keys present in the properties,
+/// along with `apply` on the struct itself.
+fn generate_patch_struct(input: &DeriveInput, options: &StructOptions, fields: &Punctuated<Field, Comma>) -> syn::Result<proc_macro2::TokenStream> {
+    let normalize = generate_key_normalization(options, fields)?;
+    let templates = generate_templates(options, fields, quote! { return Err(e.into()) })?;
+    let normalize = quote! { #normalize #templates };
+    let struct_name = &input.ident;
+    let vis = &input.vis;
+    let patch_name = format_ident!("{}Patch", struct_name);
+
+    let mut patch_fields = Vec::new();
+    let mut inits = Vec::new();
+    let mut applies = Vec::new();
+
+    for field in fields {
+        let options = parse_field_options(field)?;
+        if options.rest {
+            continue;
+        }
+
+        let field_name = field.ident.as_ref().unwrap();
+        let field_type = &field.ty;
+        let field_vis = &field.vis;
+        let raw_value = options.file_value();
+        let value = match wrapped_type(field_type, "Option") {
+            Some(inner) => {
+                let value = generate_field_init_quote(inner, raw_value, &options, true);
+                quote! { (#value).map(Some) }
+            }
+            None => generate_field_init_quote(field_type, raw_value, &options, true),
+        };
+
+        patch_fields.push(quote! { #field_vis #field_name: Option<#field_type> });
+        inits.push(quote! { #field_name: #value });
+        applies.push(quote! {
+            if let Some(value) = patch.#field_name {
+                self.#field_name = value;
+            }
+        });
+    }
+
+    let doc = format!("Partial [`{struct_name}`] where every field is an `Option`, set only for the keys present in the properties. Apply it with [`{struct_name}::apply`].");
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[derive(Default)]
+        #vis struct #patch_name {
+            #( #patch_fields ),*
+        }
+
+        #[allow(dead_code)]
+        impl #patch_name {
+            /// Reads the fields whose keys are present in the properties file at `path`.
+            #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+            pub fn from_file(path: &str) -> std::io::Result<Self> {
+                let content = ::props_util::__private::read_content(path, &::props_util::parse::Limits::new())?;
+                let propmap = ::props_util::__private::parse_content(&content, path)?;
+                #normalize
+                Ok(Self { #( #inits ),* })
+            }
+
+            /// Reads the fields whose keys are present in the properties text.
+            pub fn from_str(content: &str) -> std::io::Result<Self> {
+                let propmap = ::props_util::parse::parse_str_untrimmed(content)?;
+                #normalize
+                Ok(Self { #( #inits ),* })
+            }
+
+            /// Reads the fields whose keys are present in `propmap`.
+            pub fn from_map<K, V, S>(propmap : &std::collections::HashMap<K, V, S>) -> std::io::Result<Self>
+            where
+                K: std::borrow::Borrow<str> + std::hash::Hash + Eq,
+                V: AsRef<str>,
+                S: std::hash::BuildHasher,
+            {
+                #normalize
+                Ok(Self { #( #inits ),* })
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #struct_name {
+            /// Overwrites every field that is set in `patch`.
+            pub fn apply(&mut self, patch: #patch_name) {
+                #( #applies )*
+            }
+        }
+    })
+}
+
+/// Generates `<Name>Lazy`, which indexes the properties once and parses every field on first access.
+fn generate_lazy_struct(input: &DeriveInput, options: &StructOptions, fields: &Punctuated<Field, Comma>) -> syn::Result<proc_macro2::TokenStream> {
+    let normalize = generate_key_normalization(options, fields)?;
+    let struct_name = &input.ident;
+    let vis = &input.vis;
+    let lazy_name = format_ident!("{}Lazy", struct_name);
+
+    let mut cells = Vec::new();
+    let mut cell_inits = Vec::new();
+    let mut accessors = Vec::new();
+
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_type = &field.ty;
+        let field_vis = &field.vis;
+        let key = parse_field_options(field)?.key;
+        let value = generate_field_value(field, fields)?;
+        let accessor_doc = format!("Returns `{}`, parsing it on first access.", key.value());
+
+        cells.push(quote! { #field_name: std::sync::OnceLock<#field_type> });
+        cell_inits.push(quote! { #field_name: std::sync::OnceLock::new() });
+        accessors.push(quote! {
+            #[doc = #accessor_doc]
+            #field_vis fn #field_name(&self) -> std::io::Result<&#field_type> {
+                if let Some(val) = self.#field_name.get() {
+                    return Ok(val);
+                }
+
+                let propmap = &self.propmap;
+                let val: #field_type = #value;
+                Ok(self.#field_name.get_or_init(|| val))
+            }
+        });
+    }
+
+    let doc = format!("Lazily parsed [`{struct_name}`]. The properties are read once and each field is parsed the first time its accessor is called.");
+
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #lazy_name {
+            propmap: std::collections::HashMap<String, String>,
+            #( #cells ),*
+        }
+
+        #[allow(dead_code)]
+        impl #lazy_name {
+            /// Reads the properties file without parsing any field. Malformed lines are still reported here.
+            #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+            pub fn from_file(path: &str) -> std::io::Result<Self> {
+                Ok(Self::from(::props_util::__private::read_file(path)?))
+            }
+
+            /// Wraps an existing map of properties without parsing any field.
+            pub fn from<T>(other: T) -> Self
+            where
+                T: Into<std::collections::HashMap<String, String>>
+            {
+                let propmap: std::collections::HashMap<String, String> = other.into();
+                #normalize
+                Self { propmap, #( #cell_inits ),* }
+            }
+
+            #( #accessors )*
+        }
+    })
+}
+
+#[cfg(feature = "mmap")]
+fn generate_mmap_fn(from_path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        /// Loads properties by memory-mapping the file instead of reading it into a `String`.
+        ///
+        /// This behaves exactly like `from_file`, but avoids copying the file contents, which helps with very large
+        /// generated properties files. The file must not be modified by another process while it is being parsed.
+        #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+        pub fn from_file_mmap(path : &str) -> std::io::Result<Self> {
+            ::props_util::__private::observe_load(std::any::type_name::<Self>(), || path.to_string(), || {
+                let file = std::fs::File::open(path).map_err(|e| std::io::Error::new(e.kind(), format!("Error opening file {}", path)))?;
+                // SAFETY: The mapping is read-only and only lives for the duration of this call. Concurrent modification
+                // of the file is documented as unsupported above.
+                let mmap = unsafe { ::props_util::__private::memmap2::Mmap::map(&file) }.map_err(|e| std::io::Error::new(e.kind(), format!("Error Reading File : {}", path)))?;
+                let content = std::str::from_utf8(&mmap).map_err(|_| ::props_util::parse::Error::invalid_utf8(None, path))?;
+
+                let propmap = ::props_util::__private::parse_content(content, path)?;
+                Ok(#from_path)
+            })
+        }
+    }
+}
+
+#[cfg(not(feature = "mmap"))]
+fn generate_mmap_fn(_: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    proc_macro2::TokenStream::new()
+}
+
+/// Returns `T` if `ty` is `<wrapper><T>`, for example the `T` of an `Option<T>`.
+fn wrapped_type<'a>(ty: &'a syn::Type, wrapper: &str) -> Option<&'a syn::Type> {
+    let syn::Type::Path(tpath) = ty else {
+        return None;
+    };
+
+    let segment = tpath.path.segments.last().filter(|segment| segment.ident == wrapper)?;
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(arguments) => match arguments.args.first() {
+            Some(syn::GenericArgument::Type(inner)) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Rejects an `Option` without the type of its value and the nestings of `Option` and `Vec` that a single value can't
+/// express, which would otherwise fail with a missing `FromStr` implementation somewhere in the generated code.
+fn check_shape(ty: &syn::Type) -> syn::Result<()> {
+    if let syn::Type::Path(tpath) = ty
+        && tpath.path.segments.last().is_some_and(|segment| segment.ident == "Option")
+        && wrapped_type(ty, "Option").is_none()
+    {
+        return Err(Error::new_spanned(ty, "`Option` needs the type of its value as its first argument, like `Option<u16>`"));
+    }
+    let (ty, optional) = match wrapped_type(ty, "Option") {
+        Some(inner) => (inner, true),
+        None => (ty, false),
+    };
+    if optional && wrapped_type(ty, "Option").is_some() {
+        return Err(Error::new_spanned(ty, "`Option<Option<T>>` isn't supported, a missing key is already `None`, use `Option<T>`"));
+    }
+    let Some(element) = wrapped_type(ty, "Vec").or_else(|| array_element(ty)) else {
+        return Ok(());
+    };
+    if wrapped_type(element, "Option").is_some() {
+        return Err(Error::new_spanned(element, "`Vec<Option<T>>` isn't supported, empty elements are skipped, use `Vec<T>`"));
+    }
+    if wrapped_type(element, "Vec").is_some() || array_element(element).is_some() {
+        return Err(Error::new_spanned(element, "nested lists like `Vec<Vec<T>>` aren't supported, a value is a single comma separated list"));
+    }
+    Ok(())
+}
+
+/// Returns the element type of an array type like `[u8; 3]`.
+fn array_element(ty: &syn::Type) -> Option<&syn::Type> {
+    match ty {
+        syn::Type::Array(array) => Some(&array.elem),
+        _ => None,
+    }
+}
+
+/// Returns whether `ty` is a `HashMap` or `BTreeMap`, which a non `rest` field parses from an inline map.
+fn is_map_type(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(tpath) if tpath.path.segments.last().is_some_and(|segment| segment.ident == "HashMap" || segment.ident == "BTreeMap"))
+}
+
+/// Returns whether `ty` is a `HashSet` or `BTreeSet`, which a `flags` field parses element by element.
+fn is_set_type(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(tpath) if tpath.path.segments.last().is_some_and(|segment| segment.ident == "HashSet" || segment.ident == "BTreeSet"))
+}
+
+/// Generates `required_keys` and `optional_keys`. The `rest` field is part of neither.
+fn generate_key_fns(fields: &Punctuated<Field, Comma>) -> syn::Result<proc_macro2::TokenStream> {
+    let mut required = Vec::new();
+    let mut optional = Vec::new();
+    for field in fields {
+        let options = parse_field_options(field)?;
+        match () {
+            _ if options.rest => {}
+            _ if options.is_required(field) => required.push(options.key),
+            _ => optional.push(options.key),
+        }
+    }
+
+    Ok(quote! {
+        /// Returns the keys that must be present, since their field is neither an `Option` nor has a `default` or `env`.
+        pub const fn required_keys() -> &'static [&'static str] {
+            &[ #( #required ),* ]
+        }
+
+        /// Returns the keys that may be left out, since their field is an `Option` or has a `default` or `env`.
+        pub const fn optional_keys() -> &'static [&'static str] {
+            &[ #( #optional ),* ]
+        }
+    })
+}
+
+/// Options configured through `#[prop(...)]` on a field.
+struct FieldOptions {
+    key: LitStr,
+    env: Option<LitStr>,
+    default: Option<LitStr>,
+    rest: bool,
+    renamed_from: Option<LitStr>,
+    secret: bool,
+    map_with: Option<syn::ExprPath>,
+    order: Vec<Source>,
+    min_len: Option<usize>,
+    dedup: bool,
+    sorted: bool,
+    entry_sep: Option<LitStr>,
+    kv_sep: Option<LitStr>,
+    flags: bool,
+    from_string: bool,
+    via: Option<syn::Type>,
+    empty: EmptyPolicy,
+    trim: bool,
+    inline_comments: bool,
+    decimal_comma: bool,
+    underscores: bool,
+    radix_prefixes: bool,
+    help: Option<LitStr>,
+}
+
+/// A place a field value can come from, tried in the order configured with `order`.
+#[derive(Clone, Copy, PartialEq)]
+enum Source {
+    Env,
+    File,
+    Default,
+}
+
+const DEFAULT_ORDER: [Source; 3] = [Source::Env, Source::File, Source::Default];
+
+/// What an empty value like `key=` means for a field, configured with `empty`.
+#[derive(Clone, Copy, PartialEq)]
+enum EmptyPolicy {
+    /// The empty string is parsed like any other value.
+    AsIs,
+    /// The value is treated as absent, so later sources and defaults apply.
+    None,
+    /// Loading fails.
+    Error,
+}
+
+fn parse_empty_policy(lit: &LitStr) -> syn::Result<EmptyPolicy> {
+    match lit.value().as_str() {
+        "as_is" => Ok(EmptyPolicy::AsIs),
+        "none" => Ok(EmptyPolicy::None),
+        "error" => Ok(EmptyPolicy::Error),
+        other => Err(Error::new_spanned(lit, format!("unknown `empty` policy \"{other}\", expected \"as_is\", \"none\" or \"error\""))),
+    }
+}
+
+/// Parses an `order` like `"file,env,default"`, which must name every source exactly once.
+fn parse_order(order: &LitStr) -> syn::Result<Vec<Source>> {
+    let mut sources = Vec::new();
+    for name in order.value().split(',').map(str::trim) {
+        let source = match name {
+            "env" => Source::Env,
+            "file" => Source::File,
+            "default" => Source::Default,
+            _ => return Err(Error::new_spanned(order, format!("unknown source `{name}` in `order`, expected `env`, `file` or `default`"))),
+        };
+        if sources.contains(&source) {
+            return Err(Error::new_spanned(order, format!("duplicate source `{name}` in `order`")));
+        }
+        sources.push(source);
+    }
+    if sources.len() != DEFAULT_ORDER.len() {
+        return Err(Error::new_spanned(order, "`order` must list each of `env`, `file` and `default`"));
+    }
+    Ok(sources)
+}
+
+impl FieldOptions {
+    /// Whether loading fails if the key is missing. With `env` the value may come from the environment instead, so
+    /// such fields don't count as required.
+    fn is_required(&self, field: &Field) -> bool {
+        !self.rest && wrapped_type(&field.ty, "Option").is_none() && self.default.is_none() && self.env.is_none()
+    }
+
+    /// Whether numbers are read in a syntax the compile time check of `props_check!` doesn't know, so it only checks
+    /// that the key is present.
+    fn relaxes_numbers(&self) -> bool {
+        self.number_syntax().is_some()
+    }
+
+    /// Returns the `NumberSyntax` of `decimal_comma`, `underscores` and `radix_prefixes`, `None` if the field uses none
+    /// of them.
+    fn number_syntax(&self) -> Option<proc_macro2::TokenStream> {
+        let (decimal_comma, underscores, radix_prefixes) = (self.decimal_comma, self.underscores, self.radix_prefixes);
+        match decimal_comma || underscores || radix_prefixes {
+            true => Some(quote! {
+                ::props_util::parse::NumberSyntax::new().decimal_comma(#decimal_comma).underscores(#underscores).radix_prefixes(#radix_prefixes)
+            }),
+            false => None,
+        }
+    }
+
+    /// Returns the separators of an inline map, `;` between entries and `=` between a key and its value by default.
+    fn map_separators(&self) -> (LitStr, LitStr) {
+        let span = self.key.span();
+        (self.entry_sep.clone().unwrap_or_else(|| LitStr::new(";", span)), self.kv_sep.clone().unwrap_or_else(|| LitStr::new("=", span)))
+    }
+
+    /// Looks the value up in `propmap` as a `Cow<str>`, trimming the whitespace around it unless the field has
+    /// `trim = false` and stripping the comment of `inline_comments`.
+    fn file_value(&self) -> proc_macro2::TokenStream {
+        let lookup = self.lookup();
+        let value = self.clean_value(quote! { val });
+        quote! { #lookup.map(|val| #value) }
+    }
+
+    /// Turns the raw value `val` into a `Cow<str>` the same way.
+    fn clean_value(&self, val: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let val = quote! { ::core::convert::AsRef::<str>::as_ref(#val) };
+        match (self.inline_comments, self.trim) {
+            (true, trim) => quote! { ::props_util::parse::strip_inline_comment(#val, #trim) },
+            (false, true) => quote! { ::props_util::__private::Cow::Borrowed(#val.trim()) },
+            (false, false) => quote! { ::props_util::__private::Cow::Borrowed(#val) },
+        }
+    }
+
+    /// Looks the value up in `propmap`, falling back to the `renamed_from` key if the current key is absent.
+    fn lookup(&self) -> proc_macro2::TokenStream {
+        let key = &self.key;
+        match &self.renamed_from {
+            Some(old) => quote! { propmap.get(#key).or_else(|| propmap.get(#old)) },
+            None => quote! { propmap.get(#key) },
+        }
+    }
+}
+
+fn parse_separator(lit: LitStr) -> syn::Result<LitStr> {
+    match lit.value().is_empty() {
+        true => Err(Error::new_spanned(lit, "separators can't be empty")),
+        false => Ok(lit),
+    }
+}
+
+fn parse_field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
+    let prop_attr = field.attrs.iter().find(|attr| attr.path().is_ident("prop"));
+    let prop_attr = match prop_attr {
+        Some(attr) => attr,
+        None => {
+            // If there is no "prop" attr, simply return the field name with None default
+            check_shape(&field.ty)?;
+            let ident = field.ident.to_owned().unwrap();
+            let key = LitStr::new(&ident.to_string(), ident.span());
+            return Ok(FieldOptions {
+                key,
+                env: None,
+                default: None,
+                rest: false,
+                renamed_from: None,
+                secret: false,
+                map_with: None,
+                order: DEFAULT_ORDER.to_vec(),
+                min_len: None,
+                dedup: false,
+                sorted: false,
+                entry_sep: None,
+                kv_sep: None,
+                flags: false,
+                from_string: false,
+                via: None,
+                empty: EmptyPolicy::AsIs,
+                trim: true,
+                inline_comments: false,
+                decimal_comma: false,
+                underscores: false,
+                radix_prefixes: false,
+                help: None,
+            });
+        }
+    };
+
+    let mut key: Option<LitStr> = None;
+    let mut default: Option<LitStr> = None;
+    let mut env: Option<LitStr> = None;
+    let mut rest = false;
+    let mut renamed_from: Option<LitStr> = None;
+    let mut secret = false;
+    let mut map_with: Option<syn::ExprPath> = None;
+    let mut order: Option<Vec<Source>> = None;
+    let mut min_len: Option<usize> = None;
+    let mut dedup = false;
+    let mut sorted = false;
+    let mut entry_sep: Option<LitStr> = None;
+    let mut kv_sep: Option<LitStr> = None;
+    let mut flags = false;
+    let mut from_string = false;
+    let mut via: Option<syn::Type> = None;
+    let mut empty: Option<EmptyPolicy> = None;
+    let mut trim: Option<bool> = None;
+    let mut inline_comments = false;
+    let mut decimal_comma = false;
+    let mut underscores = false;
+    let mut radix_prefixes = false;
+    let mut help: Option<LitStr> = None;
+
+    // parse the metadata to find `key` and `default` values
+    prop_attr.parse_nested_meta(|meta| {
+        match () {
+            _ if meta.path.is_ident("key") => match key {
+                Some(_) => return Err(meta.error("duplicate 'key' parameter")),
+                None => key = Some(meta.value()?.parse()?),
+            },
+            _ if meta.path.is_ident("default") => match default {
+                Some(_) => return Err(meta.error("duplicate 'default' parameter")),
+                None => default = Some(meta.value()?.parse()?),
+            },
+            _ if meta.path.is_ident("env") => match env {
+                Some(_) => return Err(meta.error("duplicate `env` parameter")),
+                None => env = Some(meta.value()?.parse()?),
+            },
+            _ if meta.path.is_ident("rest") => rest = true,
+            _ if meta.path.is_ident("secret") => secret = true,
+            _ if meta.path.is_ident("dedup") => dedup = true,
+            _ if meta.path.is_ident("sorted") => sorted = true,
+            _ if meta.path.is_ident("flags") => flags = true,
+            _ if meta.path.is_ident("from_string") => from_string = true,
+            _ if meta.path.is_ident("via") => match via {
+                Some(_) => return Err(meta.error("duplicate `via` parameter")),
+                None => via = Some(meta.value()?.parse::<LitStr>()?.parse()?),
+            },
+            _ if meta.path.is_ident("inline_comments") => inline_comments = true,
+            _ if meta.path.is_ident("decimal_comma") => decimal_comma = true,
+            _ if meta.path.is_ident("underscores") => underscores = true,
+            _ if meta.path.is_ident("radix_prefixes") => radix_prefixes = true,
+            _ if meta.path.is_ident("order") => match order {
+                Some(_) => return Err(meta.error("duplicate `order` parameter")),
+                None => order = Some(parse_order(&meta.value()?.parse()?)?),
+            },
+            _ if meta.path.is_ident("map_with") => match map_with {
+                Some(_) => return Err(meta.error("duplicate `map_with` parameter")),
+                None => map_with = Some(meta.value()?.parse::<LitStr>()?.parse()?),
+            },
+            _ if meta.path.is_ident("min_len") => match min_len {
+                Some(_) => return Err(meta.error("duplicate `min_len` parameter")),
+                None => min_len = Some(meta.value()?.parse::<syn::LitInt>()?.base10_parse()?),
+            },
+            _ if meta.path.is_ident("trim") => match trim {
+                Some(_) => return Err(meta.error("duplicate `trim` parameter")),
+                None => trim = Some(meta.value()?.parse::<syn::LitBool>()?.value),
+            },
+            _ if meta.path.is_ident("empty") => match empty {
+                Some(_) => return Err(meta.error("duplicate `empty` parameter")),
+                None => empty = Some(parse_empty_policy(&meta.value()?.parse()?)?),
+            },
+            _ if meta.path.is_ident("entry_sep") => match entry_sep {
+                Some(_) => return Err(meta.error("duplicate `entry_sep` parameter")),
+                None => entry_sep = Some(parse_separator(meta.value()?.parse()?)?),
+            },
+            _ if meta.path.is_ident("kv_sep") => match kv_sep {
+                Some(_) => return Err(meta.error("duplicate `kv_sep` parameter")),
+                None => kv_sep = Some(parse_separator(meta.value()?.parse()?)?),
+            },
+            _ if meta.path.is_ident("help") => match help {
+                Some(_) => return Err(meta.error("duplicate `help` parameter")),
+                None => help = Some(meta.value()?.parse()?),
+            },
+            _ if meta.path.is_ident("renamed_from") => match renamed_from {
+                Some(_) => return Err(meta.error("duplicate `renamed_from` parameter")),
+                None => renamed_from = Some(meta.value()?.parse()?),
+            },
+            _ => return Err(meta.error(format!("unrecognized parameter '{}' in #[prop] attribute", meta.path.get_ident().map(|i| i.to_string()).unwrap_or_else(|| "<?>".into())))),
+        }
+        Ok(())
+    })?;
+
+    if rest
+        && (key.is_some()
+            || default.is_some()
+            || env.is_some()
+            || renamed_from.is_some()
+            || secret
+            || map_with.is_some()
+            || order.is_some()
+            || min_len.is_some()
+            || dedup
+            || sorted
+            || entry_sep.is_some()
+            || kv_sep.is_some()
+            || flags
+            || from_string
+            || via.is_some()
+            || empty.is_some()
+            || inline_comments
+            || decimal_comma
+            || underscores
+            || radix_prefixes
+            || help.is_some())
+    {
+        return Err(Error::new_spanned(prop_attr, "`rest` can't be combined with other parameters"));
+    }
+    if !rest {
+        check_shape(&field.ty)?;
+    }
+
+    let ty = wrapped_type(&field.ty, "Option").unwrap_or(&field.ty);
+    if wrapped_type(ty, "Vec").is_none() {
+        let list_params = [("min_len", min_len.is_some()), ("dedup", dedup), ("sorted", sorted)];
+        if let Some((param, _)) = list_params.iter().find(|(_, set)| *set) {
+            return Err(Error::new_spanned(&field.ty, format!("`{param}` only applies to `Vec` fields")));
+        }
+    }
+    if !is_map_type(ty) && (entry_sep.is_some() || kv_sep.is_some()) {
+        let param = if entry_sep.is_some() { "entry_sep" } else { "kv_sep" };
+        return Err(Error::new_spanned(&field.ty, format!("`{param}` only applies to `HashMap` and `BTreeMap` fields")));
+    }
+    let is_range = wrapped_type(ty, "RangeInclusive").is_some() || wrapped_type(ty, "Range").is_some();
+    let conversion = [("from_string", from_string), ("via", via.is_some())].into_iter().find(|(_, set)| *set);
+    if let (Some((param, _)), true) = (conversion, flags || is_map_type(ty) || is_range || array_element(ty).is_some()) {
+        return Err(Error::new_spanned(&field.ty, format!("`{param}` only applies to plain and `Vec` fields, not to `flags`, maps, ranges or arrays")));
+    }
+    if let (true, Some(via)) = (from_string, &via) {
+        return Err(Error::new_spanned(via, "`via` can't be combined with `from_string`"));
+    }
+    if flags && !is_set_type(ty) && !cfg!(feature = "bitflags") {
+        return Err(Error::new_spanned(&field.ty, "`flags` on a type other than `HashSet` or `BTreeSet` requires the `bitflags` feature of props-util"));
+    }
+
+    // if there is no key, simple use the ident field name
+    let key_str = match key {
+        Some(key) => key,
+        None => match field.ident.to_owned() {
+            Some(key) => LitStr::new(&key.to_string(), key.span()),
+            None => return Err(syn::Error::new_spanned(prop_attr, "Missing 'key' parameter in #[prop] attribute")),
+        },
+    };
+
+    Ok(FieldOptions {
+        key: key_str,
+        env,
+        default,
+        rest,
+        renamed_from,
+        secret,
+        map_with,
+        order: order.unwrap_or_else(|| DEFAULT_ORDER.to_vec()),
+        min_len,
+        dedup,
+        sorted,
+        entry_sep,
+        kv_sep,
+        flags,
+        from_string,
+        via,
+        empty: empty.unwrap_or(EmptyPolicy::AsIs),
+        trim: trim.unwrap_or(true),
+        inline_comments,
+        decimal_comma,
+        underscores,
+        radix_prefixes,
+        help,
+    })
+}
@@ -0,0 +1,179 @@
+//! Field parameters derived from struct level options: keys from `rename_all`, env var names from keys, the
+//! resolution `order` and flags like `inline_comments`.
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::quote;
+use syn::{Error, Field, LitStr};
+
+/// How field names are turned into keys when a field has no explicit `key`.
+#[derive(Clone, Copy)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Camel,
+    Pascal,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+    Dot,
+}
+
+impl RenameRule {
+    pub(crate) fn parse(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "camelCase" => Self::Camel,
+            "PascalCase" => Self::Pascal,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            "dot.case" => Self::Dot,
+            other => {
+                let message =
+                    format!("unknown `rename_all` rule \"{other}\", expected one of \"lowercase\", \"UPPERCASE\", \"camelCase\", \"PascalCase\", \"snake_case\", \"SCREAMING_SNAKE_CASE\", \"kebab-case\", \"SCREAMING-KEBAB-CASE\" or \"dot.case\"");
+                return Err(Error::new_spanned(lit, message));
+            }
+        })
+    }
+
+    /// Renames a snake case field name.
+    pub(crate) fn apply(self, name: &str) -> String {
+        let words = name.split('_').filter(|word| !word.is_empty());
+        match self {
+            Self::Lower => name.to_lowercase(),
+            Self::Upper => name.to_uppercase(),
+            Self::Camel => words.enumerate().map(|(i, word)| if i == 0 { word.to_lowercase() } else { capitalize(word) }).collect(),
+            Self::Pascal => words.map(capitalize).collect(),
+            Self::Snake => name.to_string(),
+            Self::ScreamingSnake => name.to_uppercase(),
+            Self::Kebab => words.collect::<Vec<_>>().join("-"),
+            Self::ScreamingKebab => words.collect::<Vec<_>>().join("-").to_uppercase(),
+            Self::Dot => words.collect::<Vec<_>>().join("."),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+/// Gives `field` the key `rule` derives from its name, unless it has an explicit `key` or is the `rest` field.
+pub(crate) fn rename_field(field: &mut Field, rule: RenameRule) {
+    let Some(ident) = &field.ident else {
+        return;
+    };
+    let key = LitStr::new(&rule.apply(&ident.to_string()), ident.span());
+
+    match field.attrs.iter_mut().find(|attr| attr.path().is_ident("prop")) {
+        Some(attr) => {
+            // Leave broken attributes alone, parsing the field reports them
+            let syn::Meta::List(list) = &mut attr.meta else {
+                return;
+            };
+            if has_param(&list.tokens, "key") || has_param(&list.tokens, "rest") {
+                return;
+            }
+            let tokens = &list.tokens;
+            list.tokens = match tokens.is_empty() {
+                true => quote! { key = #key },
+                false => quote! { #tokens, key = #key },
+            };
+        }
+        None => field.attrs.push(syn::parse_quote! { #[prop(key = #key)] }),
+    }
+}
+
+/// Gives `field` an `env` named after its key if it has a bare `env` flag, or if `all` is set and it has no `env` yet.
+///
+/// The name is the key in upper case with `.` and `-` replaced by `_`, after `prefix`: `server.host` reads `SERVER_HOST`.
+pub(crate) fn derive_env(field: &mut Field, all: bool, prefix: &str) -> syn::Result<()> {
+    let params = match field.attrs.iter().find(|attr| attr.path().is_ident("prop")).map(|attr| &attr.meta) {
+        Some(syn::Meta::List(list)) => split_params(&list.tokens),
+        _ => Vec::new(),
+    };
+    let is_flag = |param: &Vec<TokenTree>| matches!(param.as_slice(), [TokenTree::Ident(ident)] if ident == "env");
+    let flag = params.iter().any(is_flag);
+    let explicit = params.iter().any(|param| !is_flag(param) && param_is(param, "env"));
+    if params.iter().any(|param| param_is(param, "rest")) || explicit || !(flag || all) {
+        return Ok(());
+    }
+
+    // Drop the flag so the remaining parameters parse, then look up the key they configure
+    let params = params.into_iter().filter(|param| !is_flag(param)).collect::<Vec<_>>();
+    set_params(field, &params);
+    let key = crate::parse_field_options(field)?.key;
+
+    let name = format!("{prefix}{}", key.value().to_uppercase().replace(['.', '-'], "_"));
+    let env = LitStr::new(&name, key.span());
+    let mut params = params;
+    params.push(quote! { env = #env }.into_iter().collect());
+    set_params(field, &params);
+    Ok(())
+}
+
+/// Gives `field` the struct level `order` unless it configures its own or is the `rest` field.
+pub(crate) fn inherit_order(field: &mut Field, order: &LitStr) {
+    let mut params = match field.attrs.iter().find(|attr| attr.path().is_ident("prop")).map(|attr| &attr.meta) {
+        Some(syn::Meta::List(list)) => split_params(&list.tokens),
+        Some(_) => return,
+        None => Vec::new(),
+    };
+    if params.iter().any(|param| param_is(param, "order") || param_is(param, "rest")) {
+        return;
+    }
+    params.push(quote! { order = #order }.into_iter().collect());
+    set_params(field, &params);
+}
+
+/// Sets the flag `name` on `field` unless it already has it or is the `rest` field.
+pub(crate) fn inherit_flag(field: &mut Field, name: &str) {
+    let mut params = match field.attrs.iter().find(|attr| attr.path().is_ident("prop")).map(|attr| &attr.meta) {
+        Some(syn::Meta::List(list)) => split_params(&list.tokens),
+        Some(_) => return,
+        None => Vec::new(),
+    };
+    if params.iter().any(|param| param_is(param, name) || param_is(param, "rest")) {
+        return;
+    }
+    params.push(vec![TokenTree::Ident(proc_macro2::Ident::new(name, proc_macro2::Span::call_site()))]);
+    set_params(field, &params);
+}
+
+/// Splits the parameters of an attribute at the top level commas.
+fn split_params(tokens: &TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut params = vec![Vec::new()];
+    for token in tokens.clone() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => params.push(Vec::new()),
+            token => params.last_mut().unwrap().push(token),
+        }
+    }
+    params.retain(|param| !param.is_empty());
+    params
+}
+
+/// Whether `param` configures `name`, either as a flag or with a value.
+fn param_is(param: &[TokenTree], name: &str) -> bool {
+    matches!(param.first(), Some(TokenTree::Ident(ident)) if ident == name)
+}
+
+/// Replaces the parameters of the `#[prop]` attribute of `field`, adding the attribute if there is none.
+fn set_params(field: &mut Field, params: &[Vec<TokenTree>]) {
+    let params = params.iter().map(|param| param.iter().cloned().collect::<TokenStream>());
+    let tokens = quote! { #( #params ),* };
+    match field.attrs.iter_mut().find(|attr| attr.path().is_ident("prop")) {
+        Some(attr) => attr.meta = syn::parse_quote! { prop(#tokens) },
+        None => field.attrs.push(syn::parse_quote! { #[prop(#tokens)] }),
+    }
+}
+
+fn has_param(tokens: &TokenStream, name: &str) -> bool {
+    split_params(tokens).iter().any(|param| param_is(param, name))
+}
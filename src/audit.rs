@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::KeyChange;
+
+/// A trail of the most recent reloads, for admin endpoints that show the configuration history.
+///
+/// Every reload is recorded with its time, its source and either the keys it changed or the error it failed with. The
+/// log keeps the last `capacity` entries in memory and can also append every entry to a file, one line each.
+///
+/// The changes are stored as they are passed in, so they should come from a generated `redacted_diff`, which shows
+/// the values of `secret` fields as `***`.
+///
+/// ```rust
+/// use props_util::{AuditLog, Properties};
+///
+/// #[derive(Properties)]
+/// struct Config {
+///     #[prop(key = "db.password", secret)]
+///     password: String,
+///     #[prop(key = "server.port")]
+///     port: u16,
+/// }
+///
+/// let log = AuditLog::new(100);
+/// let old = Config::from_str("db.password = hunter2\nserver.port = 80")?;
+/// let new = Config::from_str("db.password = swordfish\nserver.port = 81")?;
+/// log.record_reload("config.properties", old.redacted_diff(&new))?;
+///
+/// let entry = &log.entries()[0];
+/// assert!(entry.is_success());
+/// assert!(entry.to_string().ends_with("config.properties reloaded: db.password: *** -> ***, server.port: 80 -> 81"));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct AuditLog {
+    entries: Mutex<VecDeque<AuditEntry>>,
+    capacity: usize,
+    file: Option<PathBuf>,
+}
+
+/// A reload recorded by an [`AuditLog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// When the reload was recorded.
+    pub time: SystemTime,
+    /// Where the properties were reloaded from, such as the path of the file.
+    pub source: String,
+    /// The keys the reload changed, empty if it failed or changed nothing.
+    pub changes: Vec<KeyChange>,
+    /// The error the reload failed with, `None` if it succeeded.
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    /// Returns whether the reload succeeded.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Formats as a single line: the seconds since the Unix epoch, the source and either the changes or the error, as in
+/// `1760000000 config.properties reloaded: server.port: 80 -> 81`.
+impl fmt::Display for AuditEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.time.duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+        write!(f, "{secs} {} ", self.source)?;
+        match &self.error {
+            Some(error) => write!(f, "failed: {}", error.replace('\n', " ")),
+            None if self.changes.is_empty() => write!(f, "reloaded without changes"),
+            None => {
+                let changes = self.changes.iter().map(KeyChange::to_string).collect::<Vec<_>>();
+                write!(f, "reloaded: {}", changes.join(", "))
+            }
+        }
+    }
+}
+
+impl AuditLog {
+    /// Creates a log that keeps the last `capacity` entries in memory.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            file: None,
+        }
+    }
+
+    /// Also appends every entry to the file at `path`, creating it if needed. Entries that already are in the file
+    /// are not read back.
+    pub fn with_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Records a successful reload from `source` that made `changes`.
+    pub fn record_reload(&self, source: &str, changes: Vec<KeyChange>) -> io::Result<()> {
+        self.record(AuditEntry {
+            time: SystemTime::now(),
+            source: source.to_string(),
+            changes,
+            error: None,
+        })
+    }
+
+    /// Records a reload from `source` that failed with `error`.
+    pub fn record_failure(&self, source: &str, error: &io::Error) -> io::Result<()> {
+        self.record(AuditEntry {
+            time: SystemTime::now(),
+            source: source.to_string(),
+            changes: Vec::new(),
+            error: Some(error.to_string()),
+        })
+    }
+
+    /// Returns the entries in memory, oldest first.
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+
+    /// Adds `entry`, which is kept in memory even if appending it to the file fails.
+    fn record(&self, entry: AuditEntry) -> io::Result<()> {
+        let line = self.file.as_ref().map(|path| (path, entry.to_string()));
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        if self.capacity > 0 {
+            entries.push_back(entry);
+        }
+        drop(entries);
+
+        match line {
+            Some((path, line)) => {
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| io::Error::new(e.kind(), format!("Error opening file {}", path.display())))?;
+                writeln!(file, "{line}")
+            }
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Checks properties files in CI without compiling the application that reads them.
+//!
+//! `props-util validate --schema schema.json file.properties` checks a file against the `schema_json` of a derived
+//! type: every required key is present and values of the built in types parse, read with the options of their field
+//! such as `trim`, `inline_comments`, `empty` and the number syntax. `props-util diff a.properties b.properties` lists
+//! the keys that differ between two files.
+//!
+//! Both exit with 0 on success, 1 if the file is invalid or the files differ and 2 if they can't be read.
+
+use std::borrow::Cow;
+use std::process::ExitCode;
+
+use props_util::Props;
+use props_util::parse::{self, Error, NumberSyntax};
+
+const USAGE: &str = "usage: props-util validate --schema <schema.json> <file.properties>
+       props-util diff <a.properties> <b.properties>";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let result = match args.as_slice() {
+        ["validate", "--schema", schema, file] | ["validate", file, "--schema", schema] => validate(schema, file),
+        ["diff", a, b] => diff(a, b),
+        ["-h" | "--help" | "help"] => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Prints every problem of `file` and returns whether there were none. Keys the schema doesn't know are reported as
+/// warnings, since the application ignores them.
+fn validate(schema: &str, file: &str) -> Result<bool, String> {
+    let content = std::fs::read_to_string(schema).map_err(|e| format!("Error reading schema {schema} : {e}"))?;
+    let json = serde_json::from_str::<serde_json::Value>(&content).map_err(|e| format!("Invalid schema {schema} : {e}"))?;
+    let fields = json["fields"].as_array().ok_or_else(|| format!("Invalid schema {schema} : no `fields` array"))?;
+    // Read like the generated `from_file` does, with the whitespace around the values kept for `trim = false` fields
+    let props = props_util::__private::read_file(file).map_err(|e| e.to_string())?;
+
+    let mut problems = Vec::new();
+    let mut known = Vec::new();
+    for field in fields {
+        let (Some(key), Some(type_name)) = (field["key"].as_str(), field["type"].as_str()) else {
+            return Err(format!("Invalid schema {schema} : a field has no `key` or `type`"));
+        };
+        let renamed_from = field["renamed_from"].as_str();
+        known.extend([Some(key), renamed_from].into_iter().flatten());
+        let reading = Reading::of(field);
+        let value = props.get(key).or_else(|| renamed_from.and_then(|old| props.get(old))).map(|value| reading.clean(value));
+        match value.filter(|value| !value.is_empty() || reading.empty != "none") {
+            Some(value) if value.is_empty() && reading.empty == "error" => problems.push(Error::empty_value(key)),
+            Some(value) => problems.extend(check_value(key, &value, type_name, &reading).err()),
+            None if field["required"].as_bool().unwrap_or(false) => problems.push(Error::missing_key(key)),
+            None => {}
+        }
+    }
+
+    let mut unknown = props.keys().map(String::as_str).filter(|key| !known.contains(key)).collect::<Vec<_>>();
+    unknown.sort();
+    for key in unknown {
+        eprintln!("warning: `{key}` is not a key of the schema");
+    }
+    for problem in &problems {
+        println!("{problem}");
+    }
+    Ok(problems.is_empty())
+}
+
+/// The options of a field in the schema that change how the application reads its value. Schemas written before the
+/// options were added read like fields without them.
+struct Reading<'a> {
+    trim: bool,
+    inline_comments: bool,
+    empty: &'a str,
+    syntax: NumberSyntax,
+    via: Option<&'a str>,
+    from_string: bool,
+}
+
+impl<'a> Reading<'a> {
+    fn of(field: &'a serde_json::Value) -> Self {
+        let flag = |name: &str| field[name].as_bool().unwrap_or(false);
+        Self {
+            trim: field["trim"].as_bool().unwrap_or(true),
+            inline_comments: flag("inline_comments"),
+            empty: field["empty"].as_str().unwrap_or("as_is"),
+            syntax: NumberSyntax::new().decimal_comma(flag("decimal_comma")).underscores(flag("underscores")).radix_prefixes(flag("radix_prefixes")),
+            via: field["via"].as_str(),
+            from_string: flag("from_string"),
+        }
+    }
+
+    /// Trims the raw value unless the field has `trim = false` and strips the comment of `inline_comments`.
+    fn clean<'v>(&self, value: &'v str) -> Cow<'v, str> {
+        match (self.inline_comments, self.trim) {
+            (true, trim) => parse::strip_inline_comment(value, trim),
+            (false, true) => Cow::Borrowed(value.trim()),
+            (false, false) => Cow::Borrowed(value),
+        }
+    }
+}
+
+/// Parses `value` as the written out `type_name` of the schema if it is a built in type, alone, in an `Option`, a `Vec`
+/// or an array, or as the built in type of `via`. Values of other types can't be checked without the application and
+/// pass, as do `from_string` values, which always convert.
+fn check_value(key: &str, value: &str, type_name: &str, reading: &Reading) -> Result<(), Error> {
+    if reading.from_string {
+        return Ok(());
+    }
+    let type_name = type_name.strip_prefix("Option<").and_then(|inner| inner.strip_suffix('>')).unwrap_or(type_name);
+    let list = type_name.strip_prefix("Vec<").and_then(|inner| inner.strip_suffix('>'));
+    let array = type_name.strip_prefix('[').and_then(|inner| inner.strip_suffix(']')).and_then(|inner| inner.split_once(';'));
+    // Like the generated code, arrays don't read the number syntax and ignore `via`
+    let (element, len, syntax) = match (list, array) {
+        (Some(element), _) => (reading.via.unwrap_or(element), None, reading.syntax),
+        (None, Some((element, len))) => (element, len.trim().parse::<usize>().ok(), NumberSyntax::new()),
+        (None, None) => return check_element(key, value, reading.via.unwrap_or(type_name), reading.syntax),
+    };
+
+    let elements = parse::parse_vec::<String>(key, value)?;
+    for element_value in &elements {
+        check_element(key, element_value, element.trim(), syntax)?;
+    }
+    match len {
+        Some(len) if len != elements.len() => Err(Error::wrong_len(key, elements.len(), len)),
+        _ => Ok(()),
+    }
+}
+
+fn check_element(key: &str, value: &str, type_name: &str, syntax: NumberSyntax) -> Result<(), Error> {
+    fn parses<T: std::str::FromStr>(key: &str, value: &str, syntax: NumberSyntax) -> Result<(), Error> {
+        parse::parse_number::<T>(key, value, syntax).map(drop)
+    }
+
+    match type_name {
+        "bool" => parses::<bool>(key, value, syntax),
+        "char" => parses::<char>(key, value, syntax),
+        "f32" => parses::<f32>(key, value, syntax),
+        "f64" => parses::<f64>(key, value, syntax),
+        "u8" => parses::<u8>(key, value, syntax),
+        "u16" => parses::<u16>(key, value, syntax),
+        "u32" => parses::<u32>(key, value, syntax),
+        "u64" => parses::<u64>(key, value, syntax),
+        "u128" => parses::<u128>(key, value, syntax),
+        "usize" => parses::<usize>(key, value, syntax),
+        "i8" => parses::<i8>(key, value, syntax),
+        "i16" => parses::<i16>(key, value, syntax),
+        "i32" => parses::<i32>(key, value, syntax),
+        "i64" => parses::<i64>(key, value, syntax),
+        "i128" => parses::<i128>(key, value, syntax),
+        "isize" => parses::<isize>(key, value, syntax),
+        _ => Ok(()),
+    }
+}
+
+/// Prints the keys that differ between the files and returns whether there were none.
+fn diff(a: &str, b: &str) -> Result<bool, String> {
+    let [a, b] = [a, b].map(Props::from_file);
+    let changes = a.map_err(|e| e.to_string())?.diff(&b.map_err(|e| e.to_string())?);
+    for change in &changes {
+        println!("{change}");
+    }
+    Ok(changes.is_empty())
+}
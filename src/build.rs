@@ -0,0 +1,43 @@
+//! Checks for build scripts, which fail the build when the properties files shipped with a crate no longer match the
+//! type that reads them.
+//!
+//! The type has to be available to the build script, so it lives in a crate listed under `[build-dependencies]`,
+//! such as a small crate holding only the configuration types.
+//!
+//! ```rust,no_run
+//! # use props_util::Properties;
+//! # #[derive(Properties)]
+//! # struct Config {
+//! #     #[prop(key = "server.port")]
+//! #     port: u16,
+//! # }
+//! // build.rs
+//! fn main() {
+//!     props_util::build::validate::<Config>("config/defaults.properties");
+//! }
+//! ```
+
+use std::io::{Error, ErrorKind, Result};
+
+use crate::Properties;
+
+/// Fails the build unless every field of `T` is present in the file at `path`, or has a `default`, `env` or is an
+/// `Option`, and parses. The build script is rerun whenever the file changes.
+///
+/// Relative paths are resolved from the crate root, where cargo runs build scripts.
+pub fn validate<T: Properties>(path: &str) {
+    if let Err(e) = try_validate::<T>(path) {
+        panic!("{e}");
+    }
+}
+
+/// Checks the file at `path` like [`validate`], returning the problems as an error of kind `InvalidData` instead of
+/// failing the build.
+pub fn try_validate<T: Properties>(path: &str) -> Result<()> {
+    println!("cargo:rerun-if-changed={path}");
+    let report = T::validate_file(path)?;
+    match report.is_valid() {
+        true => Ok(()),
+        false => Err(Error::new(ErrorKind::InvalidData, format!("'{path}' doesn't match `{}`:\n{report}", std::any::type_name::<T>()))),
+    }
+}
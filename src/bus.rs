@@ -0,0 +1,95 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use crate::KeyChange;
+
+type Callback = Arc<dyn Fn(&[KeyChange]) + Send + Sync>;
+
+/// Notifies the components that registered interest in some keys when those keys change, so a subsystem doesn't have
+/// to diff the whole configuration on every reload.
+///
+/// Subscribers register a pattern: a key like `db.url`, a prefix ending with `*` like `db.*`, or `*` for every key.
+/// [`ChangeBus::publish`] calls each subscriber once with the changes matching its pattern, and not at all if none
+/// match.
+///
+/// ```rust
+/// use props_util::{ChangeBus, Properties};
+///
+/// #[derive(Properties)]
+/// struct Config {
+///     #[prop(key = "db.url")]
+///     db_url: String,
+///     #[prop(key = "server.port")]
+///     port: u16,
+/// }
+///
+/// let bus = ChangeBus::new();
+/// bus.subscribe("db.*", |changes| println!("reconnecting, {} keys changed", changes.len()));
+///
+/// let old = Config::from_str("db.url = postgres://a\nserver.port = 80")?;
+/// let new = Config::from_str("db.url = postgres://b\nserver.port = 80")?;
+/// bus.publish(&old.diff(&new));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Default)]
+pub struct ChangeBus {
+    subscribers: RwLock<Vec<(Subscription, String, Callback)>>,
+    next_id: AtomicU64,
+}
+
+/// Identifies a subscription of a [`ChangeBus`], for [`ChangeBus::unsubscribe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subscription(u64);
+
+impl ChangeBus {
+    /// Creates a bus without subscribers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `callback` to be called with the changes of the keys matching `pattern`.
+    pub fn subscribe(&self, pattern: &str, callback: impl Fn(&[KeyChange]) + Send + Sync + 'static) -> Subscription {
+        let id = Subscription(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.subscribers.write().unwrap_or_else(|e| e.into_inner()).push((id, pattern.to_string(), Arc::new(callback)));
+        id
+    }
+
+    /// Removes a subscription, returning whether it was still registered.
+    pub fn unsubscribe(&self, subscription: Subscription) -> bool {
+        let mut subscribers = self.subscribers.write().unwrap_or_else(|e| e.into_inner());
+        let len = subscribers.len();
+        subscribers.retain(|(id, ..)| *id != subscription);
+        subscribers.len() != len
+    }
+
+    /// Calls every subscriber whose pattern matches one of `changes` with the matching changes, in the order of
+    /// subscription and on the calling thread.
+    ///
+    /// The subscribers are called without holding a lock, so they may subscribe and unsubscribe themselves.
+    pub fn publish(&self, changes: &[KeyChange]) {
+        if changes.is_empty() {
+            return;
+        }
+        let notified = self
+            .subscribers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter_map(|(_, pattern, callback)| {
+                let matching = changes.iter().filter(|change| matches(pattern, &change.key)).cloned().collect::<Vec<_>>();
+                (!matching.is_empty()).then(|| (callback.clone(), matching))
+            })
+            .collect::<Vec<_>>();
+        for (callback, matching) in notified {
+            callback(&matching);
+        }
+    }
+}
+
+/// Whether `key` matches a subscription pattern: the key itself, a prefix followed by `*`, or `*`.
+fn matches(pattern: &str, key: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => key.starts_with(prefix),
+        None => pattern == key,
+    }
+}
@@ -0,0 +1,98 @@
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::Properties;
+
+/// Loads a properties file and keeps the parsed value around until the file changes on disk.
+///
+/// Every call to [`CachedLoader::load`] reads the file metadata. If the modification time and size are the same
+/// as the last successful load, the previously parsed value is returned as a cheap `Arc` clone. Otherwise the file is
+/// parsed again and the cache is replaced.
+///
+/// Failed loads never replace the cached value. [`CachedLoader::load_or_last_good`] keeps serving it when a reload
+/// fails, so a live configuration never ends up broken by a bad edit.
+pub struct CachedLoader<T> {
+    path: PathBuf,
+    state: Mutex<State<T>>,
+}
+
+struct State<T> {
+    cached: Option<Cached<T>>,
+    last_error: Option<Arc<Error>>,
+}
+
+struct Cached<T> {
+    modified: SystemTime,
+    len: u64,
+    value: Arc<T>,
+}
+
+impl<T: Properties> CachedLoader<T> {
+    /// Creates a loader for the file at `path`. Nothing is read until [`CachedLoader::load`] is called.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self { path: path.as_ref().to_path_buf(), state: Mutex::new(State { cached: None, last_error: None }) }
+    }
+
+    /// Returns the path this loader reads from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the parsed properties, re-reading the file only if it changed since the last load.
+    pub fn load(&self) -> Result<Arc<T>> {
+        self.load_into(&mut self.lock())
+    }
+
+    /// Returns the parsed properties like [`CachedLoader::load`], but keeps returning the last value that loaded if
+    /// the file can no longer be read or parsed. The error is kept for [`CachedLoader::last_error`] until a load
+    /// succeeds again, and load observers see it as well. Fails only if no load has succeeded yet.
+    ///
+    /// A broken file is parsed again on every call until it is fixed.
+    pub fn load_or_last_good(&self) -> Result<Arc<T>> {
+        let mut state = self.lock();
+        match self.load_into(&mut state) {
+            Ok(value) => Ok(value),
+            Err(e) => match state.cached.as_ref().map(|entry| entry.value.clone()) {
+                Some(value) => {
+                    state.last_error = Some(Arc::new(e));
+                    Ok(value)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Returns the error of the last [`CachedLoader::load_or_last_good`] that fell back to the previous value, `None`
+    /// once a load succeeds again.
+    pub fn last_error(&self) -> Option<Arc<Error>> {
+        self.lock().last_error.clone()
+    }
+
+    /// Drops the cached value so the next [`CachedLoader::load`] parses the file again.
+    pub fn invalidate(&self) {
+        self.lock().cached = None;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn load_into(&self, state: &mut State<T>) -> Result<Arc<T>> {
+        let metadata = std::fs::metadata(&self.path).map_err(|e| Error::new(e.kind(), format!("Error opening file {}", self.path.display())))?;
+        let modified = metadata.modified()?;
+        let len = metadata.len();
+
+        if let Some(entry) = state.cached.as_ref().filter(|entry| entry.modified == modified && entry.len == len) {
+            let value = entry.value.clone();
+            state.last_error = None;
+            return Ok(value);
+        }
+
+        let path = self.path.to_str().ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Path is not valid UTF-8 : {}", self.path.display())))?;
+        let value = Arc::new(T::from_file(path)?);
+        *state = State { cached: Some(Cached { modified, len, value: value.clone() }), last_error: None };
+        Ok(value)
+    }
+}
@@ -0,0 +1,222 @@
+//! `const fn` validation used by `props_check!`. Everything here runs at compile time, so it only works on bytes and
+//! can only check the value shapes of primitive types. Values are split and cleaned by the rules of `syntax`, which
+//! the derive reads the file with and the runtime loads it with.
+
+use crate::syntax::{bytes_eq, comment_start, spelling_wins, split_element, trim, utf8_len};
+
+/// Shape a value must have to parse into the field type.
+#[derive(Clone, Copy)]
+pub enum Kind {
+    /// Anything goes, the type can't be checked at compile time.
+    Any,
+    Bool,
+    Char,
+    Float,
+    /// Unsigned integer up to the given maximum.
+    Uint(u128),
+    /// Signed integer within the given range.
+    Int(i128, i128),
+}
+
+/// Returns the last value configured for `key`, mirroring how later lines override earlier ones.
+pub const fn find<'a>(pairs: &[(&str, &'a str)], key: &str) -> Option<&'a str> {
+    let mut i = pairs.len();
+    while i > 0 {
+        i -= 1;
+        if bytes_eq(pairs[i].0.as_bytes(), key.as_bytes()) {
+            return Some(pairs[i].1);
+        }
+    }
+    None
+}
+
+/// Cleans the raw `value` like the generated code does before parsing: strips its inline comment with
+/// `inline_comments` and trims it unless the field has `trim = false`. Escaped `\#` are kept, see
+/// [`has_escaped_hash`].
+pub const fn clean_value(value: &str, trim_value: bool, inline_comments: bool) -> &str {
+    let value = match inline_comments {
+        true => value.split_at(comment_start(value).0).0,
+        false => value,
+    };
+    match trim_value {
+        true => trim(value),
+        false => value,
+    }
+}
+
+/// Returns whether `value` has a `\#`, which stands for a `#` with `inline_comments`. The check can't unescape values
+/// without allocating, so these are not checked.
+pub const fn has_escaped_hash(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let mut i = 1;
+    while i < bytes.len() {
+        if bytes[i - 1] == b'\\' && bytes[i] == b'#' {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Returns the value configured for `key` regardless of its ASCII case, for `#[prop(case_insensitive)]`.
+///
+/// Picks the same value as loading does: the last one of a key spelled exactly like `key`, or else the last one of
+/// the differently cased spelling that sorts first.
+pub const fn find_ignore_case<'a>(pairs: &[(&str, &'a str)], key: &str) -> Option<&'a str> {
+    let mut found: Option<(&str, &'a str)> = None;
+    let mut i = 0;
+    while i < pairs.len() {
+        let (spelling, value) = pairs[i];
+        if spelling.as_bytes().eq_ignore_ascii_case(key.as_bytes()) {
+            // A later line of the same spelling overrides the earlier one
+            found = match found {
+                Some((best, _)) if !bytes_eq(best.as_bytes(), spelling.as_bytes()) && !spelling_wins(key, best, spelling) => found,
+                _ => Some((spelling, value)),
+            };
+        }
+        i += 1;
+    }
+    match found {
+        Some((_, value)) => Some(value),
+        None => None,
+    }
+}
+
+/// Returns whether `value` has a `${`, which `#[prop(templates)]` renders before parsing, so the check can't know the
+/// value.
+pub const fn has_template(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let mut i = 1;
+    while i < bytes.len() {
+        if bytes[i - 1] == b'$' && bytes[i] == b'{' {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Checks that `value` parses as `kind`.
+pub const fn check_value(value: &str, kind: Kind) -> bool {
+    check_bytes(value.as_bytes(), kind)
+}
+
+/// Checks that every non-empty comma separated element of `value` parses as `kind`.
+///
+/// Elements are split like `parse_vec` does. Quoted elements with a backslash escape are not checked, since
+/// unescaping them needs an allocation.
+pub const fn check_list(value: &str, kind: Kind) -> bool {
+    let mut rest = Some(value);
+    while let Some(list) = rest {
+        let (element, quoted, after) = split_element(list);
+        let escaped = quoted && contains(element.as_bytes(), b'\\');
+        if !escaped && (quoted || !element.is_empty()) && !check_bytes(element.as_bytes(), kind) {
+            return false;
+        }
+        rest = after;
+    }
+    true
+}
+
+const fn contains(bytes: &[u8], byte: u8) -> bool {
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == byte {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn check_bytes(value: &[u8], kind: Kind) -> bool {
+    match kind {
+        Kind::Any => true,
+        Kind::Bool => bytes_eq(value, b"true") || bytes_eq(value, b"false"),
+        Kind::Char => !value.is_empty() && utf8_len(value[0]) == value.len(),
+        Kind::Float => is_float(value),
+        Kind::Uint(max) => match value {
+            [b'+', digits @ ..] => parse_digits(digits, max).is_some(),
+            _ => parse_digits(value, max).is_some(),
+        },
+        Kind::Int(min, max) => match value {
+            [b'-', digits @ ..] => parse_digits(digits, min.unsigned_abs()).is_some(),
+            [b'+', digits @ ..] => parse_digits(digits, max as u128).is_some(),
+            _ => parse_digits(value, max as u128).is_some(),
+        },
+    }
+}
+
+/// Parses ASCII digits, failing on anything else or on values above `max`.
+const fn parse_digits(digits: &[u8], max: u128) -> Option<u128> {
+    if digits.is_empty() {
+        return None;
+    }
+
+    let mut value: u128 = 0;
+    let mut i = 0;
+    while i < digits.len() {
+        if !digits[i].is_ascii_digit() {
+            return None;
+        }
+        value = match value.checked_mul(10) {
+            Some(value) => match value.checked_add((digits[i] - b'0') as u128) {
+                Some(value) => value,
+                None => return None,
+            },
+            None => return None,
+        };
+        i += 1;
+    }
+
+    match value <= max {
+        true => Some(value),
+        false => None,
+    }
+}
+
+/// Follows the grammar accepted by `f64::from_str`: an optional sign, then `inf`, `infinity`, `nan` or a decimal
+/// number with an optional exponent.
+const fn is_float(value: &[u8]) -> bool {
+    let value = match value {
+        [b'+' | b'-', rest @ ..] => rest,
+        _ => value,
+    };
+
+    if value.eq_ignore_ascii_case(b"inf") || value.eq_ignore_ascii_case(b"infinity") || value.eq_ignore_ascii_case(b"nan") {
+        return true;
+    }
+
+    let mut i = 0;
+    let mut digits = 0;
+    while i < value.len() && value[i].is_ascii_digit() {
+        i += 1;
+        digits += 1;
+    }
+    if i < value.len() && value[i] == b'.' {
+        i += 1;
+        while i < value.len() && value[i].is_ascii_digit() {
+            i += 1;
+            digits += 1;
+        }
+    }
+    if digits == 0 {
+        return false;
+    }
+
+    if i < value.len() && (value[i] == b'e' || value[i] == b'E') {
+        i += 1;
+        if i < value.len() && (value[i] == b'+' || value[i] == b'-') {
+            i += 1;
+        }
+        let exponent_start = i;
+        while i < value.len() && value[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i == exponent_start {
+            return false;
+        }
+    }
+
+    i == value.len()
+}
@@ -0,0 +1,84 @@
+use std::io;
+
+use config::{ConfigError, Map, Source, Value, ValueKind};
+
+use crate::files::read_file;
+use crate::parse;
+
+/// A [`config::Source`] for properties, so they can be layered with other sources through config-rs.
+///
+/// Keys are passed on as is, so config-rs reads a dotted key like `server.port` as the nested path `server` → `port`.
+/// All values are strings, which config-rs converts when deserializing.
+///
+/// ```rust,no_run
+/// use props_util::PropertiesSource;
+///
+/// let settings = config::Config::builder()
+///     .add_source(PropertiesSource::file("defaults.properties"))
+///     .add_source(PropertiesSource::file("local.properties").required(false))
+///     .build()?;
+/// let port: u16 = settings.get("server.port")?;
+/// # Ok::<(), config::ConfigError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct PropertiesSource {
+    input: Input,
+    required: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Input {
+    File(String),
+    Content(String),
+}
+
+impl PropertiesSource {
+    /// Reads the properties file at `path` when the configuration is built.
+    pub fn file(path: impl Into<String>) -> Self {
+        Self {
+            input: Input::File(path.into()),
+            required: true,
+        }
+    }
+
+    /// Parses properties text when the configuration is built.
+    pub fn from_content(content: impl Into<String>) -> Self {
+        Self {
+            input: Input::Content(content.into()),
+            required: true,
+        }
+    }
+
+    /// Sets whether a missing file is an error. Defaults to `true`, like [`config::File`].
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    fn read(&self) -> io::Result<Vec<(String, String)>> {
+        match &self.input {
+            Input::File(path) => match read_file(path) {
+                Ok(propmap) => Ok(propmap.into_iter().map(|(key, value)| (key, value.trim().to_string())).collect()),
+                Err(e) if e.kind() == io::ErrorKind::NotFound && !self.required => Ok(Vec::new()),
+                Err(e) => Err(e),
+            },
+            Input::Content(content) => Ok(parse::parse_str(content)?.into_iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()),
+        }
+    }
+}
+
+impl Source for PropertiesSource {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        let origin = match &self.input {
+            Input::File(path) => Some(path),
+            Input::Content(_) => None,
+        };
+
+        let pairs = self.read().map_err(|e| ConfigError::Foreign(Box::new(e)))?;
+        Ok(pairs.into_iter().map(|(key, value)| (key, Value::new(origin, ValueKind::String(value)))).collect())
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::Provenance;
+
+/// What the generated code knows about a field, without its value.
+#[doc(hidden)]
+pub struct FieldSchema {
+    pub key: &'static str,
+    pub type_name: &'static str,
+    pub required: bool,
+    pub default: Option<&'static str>,
+    pub env: Option<&'static str>,
+    pub secret: bool,
+    pub renamed_from: Option<&'static str>,
+    pub trim: bool,
+    pub inline_comments: bool,
+    /// The `empty` policy: `as_is`, `none` or `error`.
+    pub empty: &'static str,
+    pub decimal_comma: bool,
+    pub underscores: bool,
+    pub radix_prefixes: bool,
+    /// The written out type of `via`.
+    pub via: Option<&'static str>,
+    pub from_string: bool,
+}
+
+/// Writes the schema, effective value and origin of every field of `type_name` as a JSON object, one field per line.
+///
+/// `values` must already be redacted. The defaults of `secret` fields are redacted too, since they're compiled in.
+pub fn diagnostic_dump(type_name: &str, schema: &[FieldSchema], values: &BTreeMap<String, String>, provenance: &Provenance) -> String {
+    let mut dump = format!("{{\n  \"type\": {},\n  \"fields\": [", json_string(type_name));
+    for (i, field) in schema.iter().enumerate() {
+        let redact = |value: &str| match field.secret {
+            true => "***".to_string(),
+            false => value.to_string(),
+        };
+        let optional = |value: Option<String>| value.map_or_else(|| "null".to_string(), |value| json_string(&value));
+        let overridden = provenance.overridden(field.key).iter().map(|origin| json_string(&origin.to_string())).collect::<Vec<_>>();
+
+        let _ = write!(
+            dump,
+            "{}\n    {{\"key\": {}, \"type\": {}, \"required\": {}, \"default\": {}, \"env\": {}, \"secret\": {}, \"value\": {}, \"origin\": {}, \"overridden\": [{}]}}",
+            if i == 0 { "" } else { "," },
+            json_string(field.key),
+            json_string(field.type_name),
+            field.required,
+            optional(field.default.map(redact)),
+            optional(field.env.map(str::to_string)),
+            field.secret,
+            optional(values.get(field.key).cloned()),
+            optional(provenance.origin(field.key).map(ToString::to_string)),
+            overridden.join(", "),
+        );
+    }
+    dump.push_str("\n  ]\n}");
+    dump
+}
+
+/// Writes the schema of every field of `type_name` as a JSON object, one field per line, in the shape of
+/// `diagnostic_dump` without the values and origins, followed by the options that change how the value is read. The
+/// `props-util validate` command reads this.
+pub fn schema_json(type_name: &str, schema: &[FieldSchema]) -> String {
+    let mut json = format!("{{\n  \"type\": {},\n  \"fields\": [", json_string(type_name));
+    for (i, field) in schema.iter().enumerate() {
+        let optional = |value: Option<&str>| value.map_or_else(|| "null".to_string(), json_string);
+        let _ = write!(
+            json,
+            "{}\n    {{\"key\": {}, \"type\": {}, \"required\": {}, \"default\": {}, \"env\": {}, \"secret\": {}, \"renamed_from\": {}, \"trim\": {}, \
+             \"inline_comments\": {}, \"empty\": {}, \"decimal_comma\": {}, \"underscores\": {}, \"radix_prefixes\": {}, \"via\": {}, \"from_string\": {}}}",
+            if i == 0 { "" } else { "," },
+            json_string(field.key),
+            json_string(field.type_name),
+            field.required,
+            optional(if field.secret { field.default.map(|_| "***") } else { field.default }),
+            optional(field.env),
+            field.secret,
+            optional(field.renamed_from),
+            field.trim,
+            field.inline_comments,
+            json_string(field.empty),
+            field.decimal_comma,
+            field.underscores,
+            field.radix_prefixes,
+            optional(field.via),
+            field.from_string,
+        );
+    }
+    json.push_str("\n  ]\n}");
+    json
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
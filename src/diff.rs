@@ -0,0 +1,59 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A key whose value differs between two instances, as returned by a generated `diff`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChange {
+    /// The key, as configured with `#[prop(key = "...")]`.
+    pub key: String,
+    /// The value before the change, `None` if the key was not set.
+    pub old: Option<String>,
+    /// The value after the change, `None` if the key is no longer set.
+    pub new: Option<String>,
+}
+
+/// Formats as `key: old -> new`, writing `<unset>` for a missing value.
+impl fmt::Display for KeyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let old = self.old.as_deref().unwrap_or("<unset>");
+        let new = self.new.as_deref().unwrap_or("<unset>");
+        write!(f, "{}: {} -> {}", self.key, old, new)
+    }
+}
+
+/// Compares the string values of two instances, returning the changes sorted by key.
+pub fn diff(mut old: BTreeMap<String, String>, new: BTreeMap<String, String>) -> Vec<KeyChange> {
+    let mut changes = Vec::new();
+    for (key, new) in new {
+        match old.remove(&key) {
+            Some(old) if old == new => {}
+            old => changes.push(KeyChange { key, old, new: Some(new) }),
+        }
+    }
+    changes.extend(old.into_iter().map(|(key, old)| KeyChange { key, old: Some(old), new: None }));
+    changes.sort_by(|a, b| a.key.cmp(&b.key));
+    changes
+}
+
+/// Hashes the string values of an instance with 64 bit FNV-1a.
+///
+/// The hash only depends on the keys and values, so it is stable across processes and versions of Rust and can be
+/// persisted. Keys and values are prefixed with their length, so moving characters between them changes the hash.
+pub fn fingerprint(values: &BTreeMap<String, String>) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let mut hash = OFFSET;
+    let mut write = |bytes: &[u8]| {
+        for byte in (bytes.len() as u64).to_le_bytes().iter().chain(bytes) {
+            hash = (hash ^ u64::from(*byte)).wrapping_mul(PRIME);
+        }
+    };
+    for (key, value) in values {
+        write(key.as_bytes());
+        write(value.as_bytes());
+    }
+    hash
+}
@@ -0,0 +1,99 @@
+//! Renders load errors for people, with the offending line of the file, a caret under the bad value and a hint on how
+//! to fix it.
+//!
+//! ```rust
+//! use props_util::Properties;
+//!
+//! #[derive(Properties, Debug)]
+//! struct Config {
+//!     #[prop(key = "server.port")]
+//!     port: u16,
+//! }
+//!
+//! let content = "# server\nserver.port = 80800\n";
+//! let error = Config::from_str(content).unwrap_err();
+//! assert_eq!(
+//!     props_util::fancy::render_str(&error, "config.properties", content),
+//!     "error: Error Parsing `server.port` with value `80800` Error Parsing with value `80800`
+//!  --> config.properties:2:15
+//!   |
+//! 2 | server.port = 80800
+//!   |               ^^^^^
+//!   = help: expected a whole number from 0 to 65535
+//! "
+//! );
+//! ```
+
+use std::fmt::Write;
+use std::io;
+
+use crate::parse::{Error, ErrorKind, parse_line_untrimmed};
+
+/// Renders an error returned by a generated constructor that read the file at `path`, rereading the file for the
+/// offending line. Errors that don't come from parsing, such as a missing file, are rendered without a snippet.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn render(error: &io::Error, path: &str) -> String {
+    match parse_error(error) {
+        Some(error) => render_parse_error(error, path, &std::fs::read_to_string(path).unwrap_or_default()),
+        None => format!("error: {error}\n"),
+    }
+}
+
+/// Renders `error` with the line of `content` it is about, naming the content `name` in the location.
+///
+/// Invalid values are looked up by their key, so the snippet shows the line that set the value. Values that came
+/// from the environment or a default have no line and are rendered with the message and hint only.
+pub fn render_str(error: &io::Error, name: &str, content: &str) -> String {
+    match parse_error(error) {
+        Some(error) => render_parse_error(error, name, content),
+        None => format!("error: {error}\n"),
+    }
+}
+
+/// Returns the parse error the generated constructors wrap in an `io::Error` of kind `InvalidData`.
+fn parse_error(error: &io::Error) -> Option<&Error> {
+    error.get_ref().and_then(|e| e.downcast_ref::<Error>())
+}
+
+fn render_parse_error(error: &Error, name: &str, content: &str) -> String {
+    let mut text = format!("error: {error}\n");
+    let snippet = locate(error, content);
+    let width = snippet.map_or(1, |(line_num, ..)| line_num.to_string().len());
+    if let Some((line_num, line, start, len)) = snippet {
+        let column = line[..start].chars().count() + 1;
+        let _ = writeln!(text, "{:width$}--> {name}:{line_num}:{column}", "");
+        let _ = writeln!(text, "{:width$} |", "");
+        let _ = writeln!(text, "{line_num} | {line}");
+        let _ = writeln!(text, "{:width$} | {:column$}{}", "", "", "^".repeat(len.max(1)), column = column - 1);
+    }
+    if let Some(help) = error.help() {
+        let _ = writeln!(text, "{:width$} = help: {help}", "");
+    }
+    text
+}
+
+/// Finds the line of `content` the error is about, returning its number, its text and the byte position and
+/// character length of the part to underline.
+fn locate<'a>(error: &Error, content: &'a str) -> Option<(usize, &'a str, usize, usize)> {
+    let mut lines = content.lines().map(|line| line.strip_suffix('\r').unwrap_or(line));
+    match (error.kind(), error.line(), error.key()) {
+        (ErrorKind::MalformedLine, Some(line_num), _) => {
+            let line = lines.nth(line_num - 1)?;
+            let start = line.len() - line.trim_start().len();
+            Some((line_num, line, start, line.trim().chars().count()))
+        }
+        (ErrorKind::InvalidValue | ErrorKind::ConstraintViolated, _, Some(key)) => {
+            // The last line for the key set the value, like it does when loading
+            let (line_num, line) = lines.enumerate().filter(|(_, line)| matches!(parse_line_untrimmed(line), Ok(Some((k, _))) if k == key)).last()?;
+            let value_start = line.find('=')? + 1;
+            let value = line[value_start..].trim();
+            let value_start = value_start + line[value_start..].find(value).unwrap_or(0);
+            let (start, len) = match error.value().and_then(|element| Some((value.find(element)?, element))) {
+                Some((offset, element)) if !element.is_empty() => (value_start + offset, element.chars().count()),
+                _ => (value_start, value.chars().count()),
+            };
+            Some((line_num + 1, line, start, len))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+use std::io::Result;
+
+use crate::parse::{self, parse_line_untrimmed};
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+use crate::parse::Limits;
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+use crate::provenance::{Origin, Origins};
+
+/// Returns the value of the environment variable `key`, if it is set to valid unicode.
+///
+/// Always `None` on `wasm32-unknown-unknown`, which has no environment. With the `testing` feature, overrides set
+/// through `props_util::testing` take precedence.
+pub fn env_var(key: &str) -> Option<String> {
+    #[cfg(feature = "testing")]
+    if let Some(value) = crate::testing::env_override(key) {
+        return value;
+    }
+
+    match cfg!(all(target_family = "wasm", target_os = "unknown")) {
+        true => None,
+        false => std::env::var(key).ok(),
+    }
+}
+
+/// Reads the properties file at `path` into an owned map.
+///
+/// The file is streamed line by line through a single reused buffer instead of being read into memory as a whole,
+/// but every key and value is copied into a `String` of the map. Use `read_content` and `parse_content` to borrow them
+/// instead. Values keep the whitespace around them, which the generated code trims unless a field has `trim = false`.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn read_file(path: &str) -> Result<HashMap<String, String>> {
+    read_file_limited(path, &Limits::new())
+}
+
+/// Reads the properties file at `path` like `read_file`, failing as soon as it goes over `limits`.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn read_file_limited(path: &str, limits: &Limits) -> Result<HashMap<String, String>> {
+    let mut propmap = HashMap::<String, String>::new();
+    read_lines(path, limits, |key, value, _| drop(propmap.insert(key.to_string(), value.to_string())))?;
+    Ok(propmap)
+}
+
+/// Reads the properties file at `path` into a single `String`, checking it against `limits` line by line like
+/// `read_file_limited`. `parse_content` then borrows the keys and values from it, which is how the generated
+/// `from_file` avoids allocating for every key and value.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn read_content(path: &str, limits: &Limits) -> Result<String> {
+    let content = stream_lines(path, limits, true, |_, _, _| {})?;
+    String::from_utf8(content).map_err(|_| parse::Error::invalid_utf8(None, path).into())
+}
+
+/// Streams the properties file at `path`, calling `f` with every key, value and line number. The key and value borrow
+/// from the buffer of the line, so callers that keep them have to copy them.
+///
+/// A line is read only up to the point where it goes over the line length limit, so an endless line doesn't fill
+/// the memory.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+fn read_lines(path: &str, limits: &Limits, f: impl FnMut(&str, &str, usize)) -> Result<()> {
+    stream_lines(path, limits, false, f).map(drop)
+}
+
+/// Reads the file at `path` line by line for `read_lines` and `read_content`, returning every line read if `keep`.
+/// Otherwise the buffer is reused for every line.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+fn stream_lines(path: &str, limits: &Limits, keep: bool, mut f: impl FnMut(&str, &str, usize)) -> Result<Vec<u8>> {
+    use std::io::{BufRead, BufReader, Error, Read};
+
+    let file = std::fs::File::open(path).map_err(|e| Error::new(e.kind(), format!("Error opening file {}", path)))?;
+    if let Ok(metadata) = file.metadata() {
+        limits.check_size(Some(path), usize::try_from(metadata.len()).unwrap_or(usize::MAX))?;
+    }
+    let mut reader = BufReader::new(file);
+
+    let mut buf = Vec::new();
+    let mut line_num = 0;
+    let mut size = 0;
+    let mut keys = 0;
+    loop {
+        let start = match keep {
+            true => buf.len(),
+            false => {
+                buf.clear();
+                0
+            }
+        };
+        // Bytes rather than a `String`, since stopping at the length limit may split a character
+        match (&mut reader).take(limits.line_read_len()).read_until(b'\n', &mut buf).map_err(|e| Error::new(e.kind(), format!("Error Reading File : {}", path)))? {
+            0 => break,
+            len => {
+                line_num += 1;
+                size += len;
+            }
+        }
+        // The size is checked again while reading, since files like pipes don't know theirs up front
+        limits.check_size(Some(path), size)?;
+        let line = &buf[start..];
+        let content = line.strip_suffix(b"\n").unwrap_or(line);
+        limits.check_line(Some(path), line_num, content.strip_suffix(b"\r").unwrap_or(content).len())?;
+        let line = std::str::from_utf8(line).map_err(|_| parse::Error::invalid_utf8(Some(line_num), path))?;
+        if let Some((key, value)) = parse_line_untrimmed(line).map_err(|line| parse::Error::malformed_line(line_num, Some(path), line))? {
+            keys += 1;
+            limits.check_keys(Some(path), keys)?;
+            f(key, value, line_num);
+        }
+    }
+
+    Ok(buf)
+}
+
+/// Reads and merges `paths` like `merge_files`, also recording the file and line of every value.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn read_with_origins(paths: &[&str]) -> Result<(HashMap<String, String>, Origins)> {
+    let mut propmap = HashMap::<String, String>::new();
+    let mut origins = Origins::new();
+    for path in paths {
+        read_lines(path, &Limits::new(), |key, value, line| {
+            propmap.insert(key.to_string(), value.to_string());
+            push_origin(&mut origins, key, Origin::File { path: path.to_string(), line });
+        })?;
+    }
+    Ok((propmap, origins))
+}
+
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+fn push_origin(origins: &mut Origins, key: &str, origin: Origin) {
+    let key_origins = origins.entry(key.to_string()).or_default();
+    // A key repeated in the same file keeps its last line only, like the value does
+    match (key_origins.last_mut(), &origin) {
+        (Some(Origin::File { path: last, line }), Origin::File { path, line: new_line }) if last == path => *line = *new_line,
+        (Some(Origin::Embedded { line }), Origin::Embedded { line: new_line }) => *line = *new_line,
+        _ => key_origins.push(origin),
+    }
+}
+
+/// Trims the whitespace around every value of `propmap` except those of the `untrimmed` keys.
+pub fn trim_values(propmap: &mut HashMap<String, String>, untrimmed: &[&str]) {
+    for (key, value) in propmap.iter_mut() {
+        if !untrimmed.contains(&key.as_str()) && value.trim().len() != value.len() {
+            *value = value.trim().to_string();
+        }
+    }
+}
+
+/// Reads the first of `paths` that exists, returning its properties along with the path that was used.
+///
+/// A leading `~/` is expanded to the home directory. Missing files are skipped, any other error is returned right away.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn read_first<'a>(paths: &[&'a str]) -> Result<(HashMap<String, String>, &'a str)> {
+    for path in paths {
+        match read_file(&expand_home(path)) {
+            Ok(propmap) => return Ok((propmap, path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("None of the files exist : {}", paths.join(", "))))
+}
+
+/// Reads the properties file at `path` and, if it exists, its sibling `.local.` file on top, so that
+/// `config.properties` is overridden by `config.local.properties`.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn read_with_local(path: &str) -> Result<HashMap<String, String>> {
+    let mut propmap = read_file(path)?;
+    match read_file(&local_path(path)) {
+        Ok(local) => propmap.extend(local),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    Ok(propmap)
+}
+
+/// Inserts `.local` before the extension of the file name, or appends it if there is none.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+fn local_path(path: &str) -> String {
+    let path = std::path::Path::new(path);
+    let local = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => format!("{}.local.{}", stem.to_string_lossy(), extension.to_string_lossy()),
+        (Some(stem), None) => format!("{}.local", stem.to_string_lossy()),
+        (None, _) => ".local".to_string(),
+    };
+    path.with_file_name(local).to_string_lossy().into_owned()
+}
+
+/// Parses the `defaults` embedded into the binary and overrides them with the file at `path`, if it exists.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn read_layered(defaults: &str, path: &str) -> Result<HashMap<String, String>> {
+    let mut propmap = parse_content(defaults, "<embedded>")?.into_iter().map(|(key, value)| (key.to_string(), value.to_string())).collect::<HashMap<_, _>>();
+    match read_file(path) {
+        Ok(overrides) => propmap.extend(overrides),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    Ok(propmap)
+}
+
+/// Reads `app/app.properties` from the first per-user configuration directory that has it.
+///
+/// `$XDG_CONFIG_HOME` is tried first on every platform, then `~/Library/Application Support` on macOS, `%APPDATA%` on
+/// Windows and `~/.config` elsewhere.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn read_user(app: &str) -> Result<HashMap<String, String>> {
+    let paths = user_config_paths(app);
+    read_first(&paths.iter().map(String::as_str).collect::<Vec<_>>()).map(|(propmap, _)| propmap)
+}
+
+/// Merges the layers of `standard`, each overriding the ones before: `embedded`, `/etc/app/app.properties`, the
+/// per-user file of `read_user`, an environment variable per key in `keys` and `--key=value` arguments.
+///
+/// The variable for `server.host` of the app `myapp` is `MYAPP_SERVER_HOST`. Missing files are skipped and arguments
+/// of another form are ignored.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn read_standard(app: &str, embedded: Option<&str>, keys: &[&str], args: impl IntoIterator<Item = String>) -> Result<HashMap<String, String>> {
+    read_standard_with_origins(app, embedded, keys, args).map(|(propmap, _)| propmap)
+}
+
+/// Merges the layers of `read_standard`, also recording where every value came from.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn read_standard_with_origins(app: &str, embedded: Option<&str>, keys: &[&str], args: impl IntoIterator<Item = String>) -> Result<(HashMap<String, String>, Origins)> {
+    let mut propmap = HashMap::<String, String>::new();
+    let mut origins = Origins::new();
+    for (line_num, line) in embedded.unwrap_or_default().lines().enumerate() {
+        if let Some((key, value)) = parse_line_untrimmed(line).map_err(|line| parse::Error::malformed_line(line_num + 1, None, line))? {
+            propmap.insert(key.to_string(), value.to_string());
+            push_origin(&mut origins, key, Origin::Embedded { line: line_num + 1 });
+        }
+    }
+
+    let mut read_layer = |path: &str| {
+        read_lines(path, &Limits::new(), |key, value, line| {
+            propmap.insert(key.to_string(), value.to_string());
+            push_origin(&mut origins, key, Origin::File { path: path.to_string(), line });
+        })
+    };
+    match read_layer(&format!("/etc/{app}/app.properties")) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        result => result?,
+    }
+    // Only the first per-user file that exists is read, like `read_user` does
+    for path in user_config_paths(app) {
+        match read_layer(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            result => {
+                result?;
+                break;
+            }
+        }
+    }
+
+    let prefix = app.to_uppercase().replace(['.', '-'], "_");
+    for key in keys {
+        let name = format!("{prefix}_{}", key.to_uppercase().replace(['.', '-'], "_"));
+        if let Some(value) = env_var(&name) {
+            propmap.insert(key.to_string(), value);
+            push_origin(&mut origins, key, Origin::Env(name));
+        }
+    }
+
+    for arg in args {
+        if let Some((key, value)) = arg.strip_prefix("--").and_then(|arg| arg.split_once('=')) {
+            propmap.insert(key.to_string(), value.to_string());
+            push_origin(&mut origins, key, Origin::Arg(format!("--{key}")));
+        }
+    }
+    Ok((propmap, origins))
+}
+
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+fn user_config_paths(app: &str) -> Vec<String> {
+    let home = env_var("HOME").or_else(|| env_var("USERPROFILE")).map(std::path::PathBuf::from);
+    let platform = match () {
+        _ if cfg!(target_os = "macos") => home.map(|home| home.join("Library").join("Application Support")),
+        _ if cfg!(windows) => env_var("APPDATA").map(std::path::PathBuf::from),
+        _ => home.map(|home| home.join(".config")),
+    };
+
+    let dirs = [env_var("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()).map(std::path::PathBuf::from), platform];
+    dirs.into_iter().flatten().map(|dir| dir.join(app).join("app.properties").to_string_lossy().into_owned()).collect()
+}
+
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+fn expand_home(path: &str) -> std::borrow::Cow<'_, str> {
+    let home = match path.strip_prefix("~/") {
+        Some(rest) => env_var("HOME").or_else(|| env_var("USERPROFILE")).map(|home| (home, rest)),
+        None => None,
+    };
+
+    match home {
+        Some((home, rest)) => std::path::Path::new(&home).join(rest).to_string_lossy().into_owned().into(),
+        None => path.into(),
+    }
+}
+
+/// Parses the content of the file at `path` into a map of slices borrowed from `content`.
+pub fn parse_content<'a>(content: &'a str, path: &str) -> Result<HashMap<&'a str, &'a str>> {
+    let mut propmap = HashMap::<&str, &str>::new();
+    for (line_num, line) in content.lines().enumerate() {
+        if let Some((key, value)) = parse_line_untrimmed(line).map_err(|line| parse::Error::malformed_line(line_num + 1, Some(path), line))? {
+            propmap.insert(key, value);
+        }
+    }
+    Ok(propmap)
+}
+
+/// Reads every file and merges the results in the order of `paths`, so later files override earlier ones.
+///
+/// With the `parallel` feature the files are read on scoped threads, one chunk of paths per available core. The merge
+/// itself always happens in path order, so the result is the same either way. If several files fail, the error of the
+/// first failing path is returned.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn merge_files(paths: &[&str]) -> Result<HashMap<String, String>> {
+    let mut propmap = HashMap::new();
+    for map in read_all(paths) {
+        propmap.extend(map?);
+    }
+    Ok(propmap)
+}
+
+/// Merges the files matching `pattern` like `merge_files` does, in the order of their sorted paths.
+///
+/// Only the file name may contain wildcards: `*` matches any number of characters and `?` a single one. A pattern
+/// that matches no file results in an empty map.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn merge_glob(pattern: &str) -> Result<HashMap<String, String>> {
+    let paths = expand_glob(pattern)?;
+    merge_files(&paths.iter().map(String::as_str).collect::<Vec<_>>())
+}
+
+/// Merges every `*.properties` file in `dir` in lexical order of the file names, so later files override earlier ones.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn merge_dir(dir: &str) -> Result<HashMap<String, String>> {
+    merge_glob(&std::path::Path::new(dir).join("*.properties").to_string_lossy())
+}
+
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+fn expand_glob(pattern: &str) -> Result<Vec<String>> {
+    use std::io::{Error, ErrorKind};
+
+    let pattern = std::path::Path::new(pattern);
+    let dir = pattern.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+    let name = pattern.file_name().and_then(|name| name.to_str()).ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Invalid glob pattern : {}", pattern.display())))?;
+    if dir.to_string_lossy().contains(['*', '?']) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("Wildcards are only supported in the file name : {}", pattern.display())));
+    }
+
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| Error::new(e.kind(), format!("Error opening directory {}", dir.display())))? {
+        let entry = entry?;
+        let matches = entry.file_name().to_str().is_some_and(|file_name| wildcard_match(name, file_name));
+        if matches && entry.file_type()?.is_file() {
+            paths.push(pattern.with_file_name(entry.file_name()).to_string_lossy().into_owned());
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Matches `name` against `pattern`, where `*` matches any number of characters and `?` a single one.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (pattern.chars().collect::<Vec<_>>(), name.chars().collect::<Vec<_>>());
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name position it was tried at, to backtrack to on a mismatch
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Reads every file like `merge_files` does, but returns the properties of each file separately, in the order of `paths`.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn read_each(paths: &[&str]) -> Result<Vec<HashMap<String, String>>> {
+    read_all(paths).into_iter().collect()
+}
+
+#[cfg(all(not(feature = "parallel"), not(all(target_family = "wasm", target_os = "unknown"))))]
+fn read_all(paths: &[&str]) -> Vec<Result<HashMap<String, String>>> {
+    paths.iter().map(|path| read_file(path)).collect()
+}
+
+#[cfg(all(feature = "parallel", not(all(target_family = "wasm", target_os = "unknown"))))]
+fn read_all(paths: &[&str]) -> Vec<Result<HashMap<String, String>>> {
+    if paths.len() < 2 {
+        return paths.iter().map(|path| read_file(path)).collect();
+    }
+
+    let threads = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get).min(paths.len());
+    let chunk_size = paths.len().div_ceil(threads);
+
+    std::thread::scope(|scope| {
+        let handles = paths
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|path| read_file(path)).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        handles.into_iter().flat_map(|handle| handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e))).collect()
+    })
+}
@@ -0,0 +1,101 @@
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Result;
+
+use crate::parse::Error;
+
+/// Copies `propmap`, renaming keys that match one of `known` except for their ASCII case to the spelling in `known`.
+///
+/// A key spelled exactly as in `known` wins over differently cased ones. Among several differently cased keys, the one
+/// that sorts first wins, so the result doesn't depend on the iteration order of `propmap`. Unknown keys are kept as is.
+pub fn canonical_keys<'a, K, V>(propmap: impl IntoIterator<Item = (&'a K, &'a V)>, known: &[&str]) -> HashMap<String, String>
+where
+    K: Borrow<str> + ?Sized + 'a,
+    V: AsRef<str> + ?Sized + 'a,
+{
+    let mut canonical = HashMap::<String, String>::new();
+    // The original spelling of every renamed key, to decide which variant wins
+    let mut spellings = HashMap::<&str, String>::new();
+
+    for (key, value) in propmap {
+        let key = key.borrow();
+        let Some(known_key) = known.iter().copied().find(|known_key| known_key.eq_ignore_ascii_case(key)) else {
+            canonical.insert(key.to_string(), value.as_ref().to_string());
+            continue;
+        };
+
+        let wins = match spellings.get(known_key) {
+            None => true,
+            Some(previous) => crate::syntax::spelling_wins(known_key, previous, key),
+        };
+        if wins {
+            spellings.insert(known_key, key.to_string());
+            canonical.insert(known_key.to_string(), value.as_ref().to_string());
+        }
+    }
+    canonical
+}
+
+/// Resolves the keys qualified with a profile, like `cache.size@prod`, for the active `profile`.
+///
+/// A key qualified with the active profile replaces the unqualified key, wherever it is in the file, and keys of every
+/// other profile are dropped. The profile is the part after the last `@` of the key. Unqualified keys are the fallback
+/// for profiles that don't set their own value.
+///
+/// ```rust
+/// use props_util::runtime;
+///
+/// let props = runtime::parse_properties("cache.size@prod = 1024\ncache.size = 64\ncache.size@dev = 16\nname = app")?;
+/// let prod = runtime::resolve_profile(props.clone(), "prod");
+/// assert_eq!(prod["cache.size"], "1024");
+/// assert_eq!(prod.len(), 2);
+/// assert_eq!(runtime::resolve_profile(props, "test")["cache.size"], "64");
+/// # Ok::<(), props_util::runtime::Error>(())
+/// ```
+pub fn resolve_profile(propmap: HashMap<String, String>, profile: &str) -> HashMap<String, String> {
+    let mut resolved = HashMap::with_capacity(propmap.len());
+    let mut qualified = Vec::new();
+    for (key, value) in propmap {
+        match key.rsplit_once('@') {
+            Some((key, key_profile)) if key_profile == profile => qualified.push((key.to_string(), value)),
+            Some(_) => {}
+            None => {
+                resolved.insert(key, value);
+            }
+        }
+    }
+    resolved.extend(qualified);
+    resolved
+}
+
+/// Splits the keys of `propmap` that start with `prefix` by the segment after it, for loading one instance per
+/// namespace. `tenant.acme.db.url` with the prefix `tenant.` is `db.url` in the namespace `acme`.
+///
+/// Keys without a segment and a `.` after the prefix, such as `tenant.acme`, belong to no namespace and are skipped,
+/// like every key outside the prefix.
+pub fn namespaces(propmap: HashMap<String, String>, prefix: &str) -> BTreeMap<String, HashMap<String, String>> {
+    let mut namespaces = BTreeMap::<String, HashMap<String, String>>::new();
+    for (key, value) in propmap {
+        let Some((name, key)) = key.strip_prefix(prefix).and_then(|rest| rest.split_once('.')) else {
+            continue;
+        };
+        if !name.is_empty() {
+            namespaces.entry(name.to_string()).or_default().insert(key.to_string(), value);
+        }
+    }
+    namespaces
+}
+
+/// Loads every namespace of `propmap` under `prefix` with `load`, naming the full key in errors about a single key.
+pub fn load_namespaced<T>(propmap: HashMap<String, String>, prefix: &str, load: impl Fn(HashMap<String, String>) -> Result<T>) -> Result<HashMap<String, T>> {
+    namespaces(propmap, prefix)
+        .into_iter()
+        .map(|(name, propmap)| match load(propmap) {
+            Ok(value) => Ok((name, value)),
+            Err(e) => match e.get_ref().and_then(|e| e.downcast_ref::<Error>()) {
+                Some(error) => Err(error.clone().under(&format!("{prefix}{name}.")).into()),
+                None => Err(e),
+            },
+        })
+        .collect()
+}
@@ -55,6 +55,48 @@
 //!
 //! - `key`: The property key to look for in the properties file (optional). If not specified, the field name will be used as the key.
 //! - `default`: A default value to use if the property is not found in the file (optional)
+//! - `env`: The environment variable name to look for (optional). If the environment variable is set, its value will be used instead of the value from the properties file. Written as a bare `env`, the name is derived from the key by upper casing it and replacing `.` and `-` with `_`, so `server.host` reads `SERVER_HOST`.
+//! - `renamed_from`: A previous name of the key (optional). It is read if the current key is absent, so renamed keys keep working. `renamed_keys_in(&map)` lists the renamed keys a map still uses under their old name. `from_file_with_report(path)` and `from_files_with_report(paths)` also return a `MigrationReport` naming each old key that was read and the file it came from, to ask users to update their configuration.
+//! - `rest`: Marks a single `HashMap<String, String>` field that receives every key not consumed by the other fields, for passing unknown settings through verbatim. It can't be combined with other parameters.
+//! - `secret`: Marks a value that must not be printed, such as a password. `display` and `debug` show it as `***`.
+//! - `map_with = "path"`: A `fn(T) -> T` applied to every parsed value of the field, including its default, for normalizations like lowercasing a hostname. For an `Option` field it receives the value inside, for a `Vec` field the whole list.
+//! - `order`: Overrides the struct level `order` for a single field.
+//! - `empty`: What an empty value like `port =` means. `"as_is"`, the default, parses the empty string like any other value, `"none"` treats it as absent so `env`, the `default` or `None` apply instead, and `"error"` fails the load.
+//! - `trim = false`: Keeps the whitespace around the value, for padding or indent strings. The value is everything after the `=` up to the end of the line, so write it right after the `=`: `indent=    `.
+//! - `min_len = n`: The least number of elements a `Vec` field must have. A list with fewer elements, like an empty `seed_nodes =`, fails the load with a message naming the key.
+//! - `dedup`: Removes repeated elements from a `Vec` field after parsing, keeping the first occurrence of each. It applies before `min_len` and `map_with`.
+//! - `sorted`: Sorts the elements of a `Vec` field after parsing and `dedup`, so lists whose order doesn't matter compare and serialize the same however they were written.
+//! - `entry_sep` and `kv_sep`: The separators of an inline map field, `;` between entries and `=` between a key and its value by default. `entry_sep = ",", kv_sep = ":"` reads `a:1, b:2`.
+//! - `flags`: Reads a `|` separated set like `features = ssl|gzip|http2`. The field is a `HashSet` or `BTreeSet` of a `FromStr` type, such as an enum of the features, or, with the `bitflags` feature, a type generated by `bitflags!` whose flag names are matched regardless of case.
+//! - `from_string`: Converts the value with `From<String>` instead of parsing it with `FromStr`, for newtypes like `struct TenantId(String)` that only have the conversion. For a `Vec` field every element is converted.
+//! - `via = "Type"`: Parses the value as `Type` and converts it into the field type with `TryFrom`, or `From`, for wrapper IDs like `struct UserId(u64)` and enums backed by their discriminants. A failed conversion fails the load like a value that doesn't parse. For a `Vec` field every element is converted.
+//! - `help = "..."`: A hint on how to fix the value, such as `"expected one of: trace, debug, info"`, attached to the error when the key is missing or its value doesn't parse. Without it, `bool`, `char`, integer and float fields hint at the values they accept.
+//!
+//! The `#[prop]` attribute can also be placed on the struct itself:
+//!
+//! - `lazy`: Also generate a lazily parsed `<Name>Lazy` type (see [Lazy Parsing](#lazy-parsing))
+//! - `no_hashmap`: Skip generating `to_hash_map` and the conversions into `HashMap<String, String>`. Those require every field type to implement `Display`.
+//! - `embed`: Path of a properties file, relative to the crate root, that is embedded into the binary with `include_str!`. The generated `from_embedded()` parses it, so the defaults file always ships with the binary.
+//! - `file = "path"`: The default location of the properties file, read by the generated `load()` so call sites don't repeat the path.
+//! - `file_env = "NAME"`: An environment variable that, when set, names the file `load()` reads instead of `file`, such as `MYAPP_CONFIG`.
+//! - `global`: Generate `init_global(path)`, `set_global(value)` and `global()`, which store a single instance in a `OnceLock` so it can be initialized once in `main` and read anywhere. `try_init_global(path)` returns whether it initialized the instance. With the `global-replace` feature, `replace_global(value)` swaps the instance for tests or reloads and `global_arc()` returns the current one as an `Arc`, so replaced instances are freed once no reader holds them. `global()` keeps returning the instance set at initialization.
+//! - `patch`: Generate `<Name>Patch`, where every field is an `Option` that is only set for keys present in the properties, and `apply(patch)` to overwrite the fields a patch sets. This models layered partial overrides in typed form.
+//! - `try_from(Type, ...)`: Generate `impl TryFrom<&Type>` for each listed type, converting through `convert_from`.
+//! - `serde`: Implement `Serialize` and `Deserialize`, which go through a flat map from key to string value. Requires the `serde` feature and can't be combined with `no_hashmap`.
+//! - `display`: Implement `Display`, printing one `key=value` line per key in sorted order with the values of `secret` fields replaced by `***`. Useful to log the effective configuration at startup. Can't be combined with `no_hashmap`.
+//! - `debug`: Implement `Debug` like `#[derive(Debug)]` does, except that `secret` fields are shown as `***`. Unlike `display` it only needs `Debug` on the field types.
+//! - `before_parse = "path"`: A `fn(&mut HashMap<String, String>)` that can rewrite the raw properties before any field is parsed, for example to rename legacy keys. It also runs in `validate_file` and `missing_keys`.
+//! - `after_parse = "path"`: A `fn(&mut Self) -> std::io::Result<()>` that runs on every constructed instance, to derive fields or reject invalid combinations. An error fails the load.
+//! - `case_insensitive`: Match keys regardless of their ASCII case, so `SERVER.PORT` and `Server.Port` both satisfy a field keyed `server.port`. A key spelled exactly like the field key wins over other spellings.
+//! - `inline_comments`: Strip trailing comments from the values of the fields, so `port = 8080 # admin port` is read as `8080`. A `#` starts the comment at the beginning of the value or after whitespace, and `\#` stands for a literal `#`. Can also be set on a single field.
+//! - `decimal_comma`: Accept a decimal comma in float values, so `ratio = 3,14` is read like `ratio = 3.14`. A value is only read this way if it doesn't parse as is and has a single `,` and no `.`. In lists the elements with a decimal comma have to be quoted, as in `"1,5", "2,5"`. `props_check!` only checks that these fields are present. Can also be set on a single field.
+//! - `underscores`: Accept `_` between the digits of numbers, so `max_bytes = 1_000_000` is read as `1000000`. Values that parse as they are, such as strings, are kept with their underscores. `props_check!` only checks that these fields are present. Can also be set on a single field.
+//! - `radix_prefixes`: Accept integers in hexadecimal, octal and binary, such as `0x1F`, `0o755` and `0b1010`, for permission masks and register values. A `-` before the prefix makes the number negative, and the range of the field type is checked like for decimal values. `props_check!` only checks that these fields are present. Can also be set on a single field.
+//! - `templates`: Render `${...}` templates in values with other keys and environment variables, such as `greeting = Hello ${user.name|upper}`. Requires the `templates` feature, see [Templates in Values](#templates-in-values).
+//! - `rename_all = "rule"`: Derive the key of every field without an explicit `key` from its name. The rules are `lowercase`, `UPPERCASE`, `camelCase`, `PascalCase`, `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case`, `SCREAMING-KEBAB-CASE` and `dot.case`, so with `kebab-case` the field `max_connections` reads `max-connections`.
+//! - `env`: Look up a derived environment variable for every field that doesn't configure `env` itself, as if each had a bare `env`.
+//! - `env_prefix = "PREFIX_"`: Prepended to every derived environment variable name, so with `APP_` the key `server.host` reads `APP_SERVER_HOST`.
+//! - `order = "env,file,default"`: The order in which a value is looked up: the environment variable, the properties and the `default`. The first one that has a value wins. It must list all three, `"env,file,default"` is the default and `"file,env,default"` lets the file win over the environment.
 //!
 //! ### Field Types
 //!
@@ -63,10 +105,26 @@
 //! - `String`
 //! - Numeric types (`u8`, `u16`, `u32`, `u64`, `i8`, `i16`, `i32`, `i64`, `f32`, `f64`)
 //! - Boolean (`bool`)
-//! - `Vec<T>` where `T` implements `FromStr` (values are comma-separated in the properties file)
+//! - `Vec<T>` where `T` implements `FromStr` (values are comma-separated in the properties file, and an element in double quotes may contain commas: `names = "Smith, John", "Doe, Jane"`)
+//! - `[T; N]` where `T` implements `FromStr` (comma-separated like a `Vec`, such as an RGB triple `color = 255, 128, 0`; a value with a different number of elements fails to load)
+//! - `HashMap<K, V>` and `BTreeMap<K, V>` where `K` and `V` implement `FromStr` (an inline map like `labels = env=prod;team=core`)
+//! - `RangeInclusive<T>` and `Range<T>` where `T` implements `FromStr` and `PartialOrd` (bounds separated by `..=` and `..`, or for an inclusive range of integers by `-` as in `port_range = 9000-9100`; a range that starts after it ends fails to load)
 //! - `Option<T>` where `T` implements `FromStr` (optional fields that may or may not be present in the properties file)
 //! - Custom types that implement `FromStr`
 //!
+//! Shapes a single value can't express, `Option<Option<T>>`, `Vec<Option<T>>` and lists of lists like
+//! `Option<Vec<Vec<T>>>`, are rejected at compile time with an error on the field type, and so is an `Option` without
+//! the type of its value.
+//!
+//! ```compile_fail
+//! use props_util::Properties;
+//!
+//! #[derive(Properties)]
+//! struct Config {
+//!     port: Option,
+//! }
+//! ```
+//!
 //! ### Example of using Vec and Option types:
 //!
 //! ```rust
@@ -129,7 +187,7 @@
 //!
 //! fn main() -> Result<()> {
 //!     let server_config = ServerConfig::default()?;
-//!     let client_config = ClientConfig::from(server_config)?;
+//!     let client_config = ClientConfig::from(&server_config)?;
 //!     println!("Server host: {}", client_config.server_host);
 //!     println!("Server port: {}", client_config.server_port);
 //!     Ok(())
@@ -138,6 +196,13 @@
 //!
 //! > **Important**: When converting between types using `from`, the `key` attribute values must match between the source and target types. If no `key` is specified, the field names must match. This ensures that the configuration values are correctly mapped between the different types.
 //!
+//! Derived types only convert into a map by reference, so the conversion never consumes the source. The generated
+//! `to_hash_map(&self)` returns the same map for inspection.
+//!
+//! `convert_from(&other)` does the same for any source that converts into a map by reference. To take part in the
+//! standard conversion traits, list the source types with `#[prop(try_from(ServerConfig))]` on the target, which
+//! generates `impl TryFrom<&ServerConfig> for ClientConfig`.
+//!
 //! ### Error Handling
 //!
 //! The `from_file` method returns a `std::io::Result<T>`, which will contain:
@@ -173,6 +238,483 @@
 //! }
 //! ```
 //!
+//! Use `from_file_or_default` to fall back to the defaults when the file does not exist. Malformed files are still
+//! reported as errors.
+//!
+//! ### Caching Loaded Files
+//!
+//! When the same file is loaded repeatedly, [`CachedLoader`] keeps the last parsed value and only re-parses
+//! the file when its modification time or size changes:
+//!
+//! ```rust
+//! use props_util::{CachedLoader, Properties};
+//! use std::io::Result;
+//!
+//! #[derive(Properties, Debug)]
+//! struct Config {
+//!     #[prop(key = "server.port", default = "8080")]
+//!     port: u16,
+//! }
+//!
+//! fn main() -> Result<()> {
+//!     let temp_file = tempfile::NamedTempFile::new()?;
+//!     std::fs::write(&temp_file, "server.port=9090")?;
+//!
+//!     let loader = CachedLoader::<Config>::new(temp_file.path());
+//!     let first = loader.load()?;
+//!     let second = loader.load()?; // File is unchanged, so this is the same `Arc`
+//!     assert!(std::sync::Arc::ptr_eq(&first, &second));
+//!     Ok(())
+//! }
+//! ```
+//!
+//! For a configuration that is reloaded while the service runs, `load_or_last_good()` keeps returning the last value
+//! that loaded when an edit breaks the file, instead of failing. The error is available from `last_error()` until the
+//! file loads again, for a health endpoint or an alert, and load observers see it too.
+//!
+//! ### Memory-Mapped Loading
+//!
+//! `from_file` streams the file through a `BufReader` into a single `String` and looks the fields up in slices of it,
+//! so it only allocates for the values of fields that parse into owned types. The constructors that merge several
+//! files, record origins or hand the map to `before_parse` still copy every key and value into an owned map, since it
+//! outlives the buffer of each file.
+//!
+//! With the `mmap` feature enabled, the derive also generates `from_file_mmap`, which parses a memory-mapped file
+//! instead of reading it into a `String` first. It behaves exactly like `from_file` and is meant for very large
+//! generated properties files.
+//!
+//! ### Lazy Parsing
+//!
+//! Adding `#[prop(lazy)]` to a struct additionally generates a `<Name>Lazy` type. It reads the properties once and
+//! parses each field only when its accessor is called for the first time, which helps with large structs where a
+//! given run only reads a few fields:
+//!
+//! ```rust
+//! use props_util::Properties;
+//! use std::io::Result;
+//!
+//! #[derive(Properties, Debug)]
+//! #[prop(lazy)]
+//! struct Config {
+//!     #[prop(key = "server.port", default = "8080")]
+//!     port: u16,
+//!     #[prop(key = "server.workers", default = "4")]
+//!     workers: u32,
+//! }
+//!
+//! fn main() -> Result<()> {
+//!     let config = ConfigLazy::from_file("examples/test.properties")?;
+//!     println!("Port: {}", config.port()?); // Only `server.port` is parsed
+//!     Ok(())
+//! }
+//! ```
+//!
+//! ### Loading Several Files
+//!
+//! `from_files` reads a list of files and merges them in order, so keys in later files override the same keys in
+//! earlier files. This fits `conf.d` style layouts where each component ships its own fragment. With the `parallel`
+//! feature enabled, the files are read and parsed on multiple threads before being merged; the result is identical.
+//!
+//! ```rust,no_run
+//! # use props_util::Properties;
+//! # #[derive(Properties)]
+//! # struct Config {
+//! #     #[prop(key = "server.port", default = "8080")]
+//! #     port: u16,
+//! # }
+//! let config = Config::from_files(&["defaults.properties", "tenant-a.properties"])?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! `from_glob("conf.d/*.properties")` merges every file matching a pattern, in the order of their sorted paths. Only
+//! the file name may contain wildcards: `*` matches any number of characters and `?` a single one.
+//!
+//! `from_dir_merged("conf.d")` does the same for every `*.properties` file in a directory, in lexical order of the
+//! file names like a Debian style `conf.d` directory. Errors name the file they occurred in.
+//!
+//! To use only one file out of a search path instead, `from_first_of` tries each path in order and returns the first
+//! one that exists along with the path that was used. A leading `~/` is expanded to the home directory.
+//!
+//! ```rust,no_run
+//! # use props_util::Properties;
+//! # #[derive(Properties)]
+//! # struct Config {
+//! #     #[prop(key = "server.port", default = "8080")]
+//! #     port: u16,
+//! # }
+//! let (config, path) = Config::from_first_of(&["/etc/app.properties", "~/.config/app.properties", "./app.properties"])?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! CLI tools can look up a per-user file with `load_user("myapp")`, which reads `myapp/app.properties` from
+//! `$XDG_CONFIG_HOME`, then from `~/Library/Application Support` on macOS, `%APPDATA%` on Windows or `~/.config`
+//! elsewhere.
+//!
+//! `from_file_with_local` follows the `config.properties` plus `config.local.properties` convention: it loads the
+//! base file and, if it exists, the sibling `.local.` file on top. The local file is typically gitignored and holds
+//! settings for a single machine.
+//!
+//! ### `no_std` Support
+//!
+//! The line and value parsing lives in the [`parse`] module, which only needs `alloc`. Disabling the default `std`
+//! feature makes the crate `no_std`. The derive then only generates `from_str`, which parses properties text
+//! received from any source and returns a [`parse::Error`] on failure. `env` and `lazy` are not available in that
+//! configuration.
+//!
+//! With `std` enabled, `from_str` is generated as well and returns a `std::io::Result` like the other constructors.
+//!
+//! ### WebAssembly
+//!
+//! On `wasm32-unknown-unknown` there is no filesystem or environment. The constructors that read files (`from_file`,
+//! `from_files`, `from_file_mmap`) and [`CachedLoader`] are not available on that target, and `env` lookups always
+//! fall back to the properties. Use `from_str` for properties text, or `from_map` to build an instance from a borrowed
+//! `HashMap` without giving up ownership of it.
+//!
+//! ### Checking Files at Compile Time
+//!
+//! [`props_check!`] validates a properties file against a type at compile time. The path is relative to the crate root.
+//! The build fails if a required key is missing or a value of a primitive field (`bool`, `char`, integers and floats)
+//! does not parse into the field type. Fields with `env` are not required, since the value may come from the
+//! environment at runtime. Other field types are only checked for presence, and so are the values of `templates`
+//! types with a `${` template, which is rendered at runtime.
+//!
+//! ```rust
+//! use props_util::{Properties, props_check};
+//!
+//! #[derive(Properties)]
+//! struct Config {
+//!     name: String,
+//!     option_vec1: Vec<u32>,
+//! }
+//!
+//! props_check!(Config, "examples/test.properties");
+//! ```
+//!
+//! ### Inline Properties
+//!
+//! [`props!`] builds an instance from inline `key => value` pairs, which is handy for tests and examples that would
+//! otherwise need a temporary file. The pairs are checked at compile time like with `props_check!`, and the macro
+//! evaluates to the `std::io::Result` of `from_map`.
+//!
+//! ```rust
+//! use props_util::{Properties, props};
+//!
+//! #[derive(Properties, Debug)]
+//! struct Config {
+//!     #[prop(key = "server.host", default = "localhost")]
+//!     host: String,
+//!     #[prop(key = "server.port", default = "8080")]
+//!     port: u16,
+//! }
+//!
+//! let config = props!(Config { "server.port" => "9090" })?;
+//! assert_eq!(config.port, 9090);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! When the pairs are only known at runtime, `from_pairs` accepts any iterator of `(key, value)` pairs instead:
+//!
+//! ```rust
+//! # use props_util::Properties;
+//! # #[derive(Properties)]
+//! # struct Config {
+//! #     #[prop(key = "server.port", default = "8080")]
+//! #     port: u16,
+//! # }
+//! let port = 9090.to_string();
+//! let config = Config::from_pairs([("server.port", port)])?;
+//! assert_eq!(config.port, 9090);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! ### Overriding Values in Tests
+//!
+//! With the `testing` feature, the `testing` module overrides property keys and environment variables for the
+//! duration of a scope. Overrides only apply to the current thread and are reverted when the returned guard is
+//! dropped, so parallel tests no longer race on `std::env::set_var`.
+//!
+//! The feature also generates `snapshot(&provenance)`, which renders the effective configuration as a stable string
+//! for snapshot assertions: one line per key in key order, `secret` values shown as `***` and the origin of every value,
+//! with files shown by their name only. A test that compares it against a stored snapshot catches changes in which
+//! source wins as well as in the values themselves.
+//!
+//! ### Untyped Access
+//!
+//! [`Props`] reads properties without a struct, for ad-hoc access to a few keys. Values are parsed on access with
+//! `get`, `get_or`, `get_opt` and `get_vec`, following the same rules as the derived constructors. A `Props` can also
+//! be passed to the `from` of any derived type, and
+//! `subset("db.")` returns the keys under a prefix with the prefix stripped, which hands a component its own slice of
+//! a larger file. `keys` and `iter_prefix("feature.")` enumerate keys that aren't known in advance, such as
+//! feature flags. Derived types convert into `Props` through `to_props`, and `diff(&other)` lists the keys that differ
+//! between two `Props`. Code that only needs the raw pairs can call [`parse_properties`], which returns them as a
+//! `HashMap<String, String>` with the same parsing rules. The [`runtime`] module gathers this and the rest of the code
+//! the derive calls into, such as `read_properties(path)`, `parse_vec` and `parse_map`, for reading properties without
+//! a derived type. Its [`runtime::Writer`] goes the other way and writes key value pairs as `.properties` text escaped
+//! like Java's `Properties.store`, with a configurable separator, a comment header and optional `\uXXXX` escaping of
+//! non-ASCII characters.
+//!
+//! ```rust
+//! use props_util::Props;
+//!
+//! let props = Props::from_str("server.port = 9090")?;
+//! let port: u16 = props.get("server.port")?;
+//! let debug: bool = props.get_or("debug", false)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! ### Introspecting Keys
+//!
+//! Every derived type has `required_keys()` and `optional_keys()`, which list the keys a file must contain and the
+//! ones it may leave out. A key is optional if its field is an `Option` or has a `default` or `env`.
+//!
+//! `validate_file(path)` goes further and checks that every field is present and parses, without constructing an
+//! instance. It returns a [`ValidationReport`] with all problems instead of stopping at the first one. `missing_keys(path)`
+//! only lists the required keys absent from a file.
+//!
+//! ### Comparing Instances
+//!
+//! `diff(&other)` returns a [`KeyChange`] for every key whose value differs between two instances, comparing the
+//! values as they are written by `to_hash_map`. Printing the changes when configuration is reloaded keeps the reload
+//! auditable. It is not generated with `no_hashmap`, since it needs `Display` on every field type.
+//!
+//! `fingerprint()` returns a hash of the keys and values that is stable across processes and Rust versions, and
+//! `has_changed(&other)` tells whether two instances differ in any value. Reload logic can use either to skip
+//! notifying subscribers when a file was touched but its content is the same.
+//!
+//! A [`ChangeBus`] hands the changes to the components interested in them. Each component subscribes with a key like
+//! `db.url`, a prefix like `db.*` or `*`, and `publish(&old.diff(&new))` calls it only with the matching changes, and
+//! not at all if its keys are unchanged.
+//!
+//! `redacted_diff(&other)` is `diff` with the values of `secret` fields shown as `***`. An [`AuditLog`] keeps the
+//! last reloads with their time, source and redacted changes, or the error a reload failed with, for admin endpoints
+//! that show the recent configuration history. `with_file(path)` also appends every entry to a file, one line each.
+//!
+//! ### Partial Updates
+//!
+//! `apply_overrides(&map)` re-parses only the keys present in `map` and assigns them to an existing instance, for
+//! example to apply updates pushed from an admin API. The update is all or nothing: if a value fails to parse, no field
+//! changes and the returned [`ValidationReport`] lists every failing key.
+//!
+//! ### config-rs Integration
+//!
+//! With the `config` feature, `PropertiesSource` implements `config::Source`, so properties files can be layered with
+//! the other sources of a config-rs `Config`. Dotted keys become nested paths, `server.port` is read with
+//! `settings.get("server.port")`.
+//!
+//! ### Logging Loads
+//!
+//! With the `tracing` feature, every constructor of a derived type emits an `info` event with the target `props_util`.
+//! It records the source of the properties, the number of keys, the keys that used their `default`, the keys whose
+//! value came from `env` and, unless the type is `no_hashmap`, the effective values with `secret` fields redacted.
+//!
+//! ### Observing Loads
+//!
+//! [`observe::add_load_observer`] registers a callback that runs after every load of every derived type, with the
+//! type name, the source, the time the load took and the error if it failed. This is the place to export metrics about
+//! configuration loads without touching the generated code.
+//!
+//! ### Standard Layering
+//!
+//! `standard("myapp")` loads the layers most services assemble by hand, each overriding the ones before: the `embed`
+//! file if there is one, `/etc/myapp/app.properties`, the per-user file of `load_user`, environment variables like
+//! `MYAPP_SERVER_HOST` for the key `server.host`, and `--server.host=value` arguments. Missing files are skipped.
+//! `standard_with_args` takes the arguments from an iterator instead of the process.
+//!
+//! `layered(include_str!("defaults.properties"), path)` covers the most common case with just two layers: the embedded
+//! defaults, and the file at `path` overriding them if it exists. The embedded file holds a value for every required
+//! key, so they always resolve, and the file on disk only needs the keys that differ.
+//!
+//! ### Tracing Values to Their Source
+//!
+//! `from_file_with_provenance(path)`, `from_files_with_provenance(paths)` and `standard_with_provenance(app, args)` also
+//! return a [`Provenance`] recording, for every field, the [`Origin`] of its value: the file and line, the environment
+//! variable, the command line argument or the `default`. The origins a value overrode are kept too, so a port that was
+//! set in a file but replaced by `env` can be told apart from one that only ever came from `env`.
+//!
+//! ```rust,no_run
+//! # use props_util::Properties;
+//! # #[derive(Properties)]
+//! # struct Config {
+//! #     #[prop(key = "server.port", env = "SERVER_PORT", default = "8080")]
+//! #     port: u16,
+//! # }
+//! let (config, provenance) = Config::from_file_with_provenance("config.properties")?;
+//! println!("{provenance}"); // server.port: env SERVER_PORT
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! `explain(&provenance)` turns the report into one line per field, such as
+//! `server.port = 9090 (from env SERVER_PORT, overriding config.properties:12)`, with `secret` values shown as `***`.
+//!
+//! `diagnostic_dump(&provenance)` writes the key, type, `default`, `env`, effective value and origins of every field as
+//! a single JSON object, with `secret` values and defaults redacted. It can be attached to a support ticket where the
+//! raw properties files, credentials included, used to be pasted. Neither method is generated with `no_hashmap`.
+//!
+//! ### Untrusted Files
+//!
+//! `from_file_limited(path, &limits)` and `from_str_limited(content, &limits)` bound what a load will read, for properties
+//! that come from users rather than from the deployment. A [`Limits`](parse::Limits) caps the size of the input, the
+//! length of a line and the number of keys, and a load that goes over any of them fails with the `LimitExceeded` error
+//! kind as soon as the limit is reached, without reading the rest of the file.
+//!
+//! ```rust,no_run
+//! # use props_util::Properties;
+//! # #[derive(Properties)]
+//! # struct Config {
+//! #     #[prop(key = "server.port", default = "8080")]
+//! #     port: u16,
+//! # }
+//! use props_util::parse::Limits;
+//!
+//! let limits = Limits::new().max_file_size(64 * 1024).max_line_len(1024).max_keys(500);
+//! let config = Config::from_file_limited("uploads/tenant.properties", &limits)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! ### Checking Files in CI
+//!
+//! `schema_json()` describes the key, type, `default`, `env` and reading options of every field as JSON. Saved next to
+//! the application, it lets the `props-util` binary of the `cli` feature check properties files in a CI pipeline
+//! without compiling the application:
+//!
+//! ```sh
+//! cargo install props-util --features cli
+//! props-util validate --schema schema.json config.properties
+//! props-util diff staging.properties production.properties
+//! ```
+//!
+//! `validate` reports every missing required key and every value that doesn't parse into its built in type, such as a
+//! `u16` or a `Vec<bool>`, and warns about keys the schema doesn't know. Values of other types can only be checked by the
+//! application. `diff` prints a `key: old -> new` line for every key that differs between two files. Both exit with 1 if
+//! the file is invalid or the files differ, and 2 if a file can't be read.
+//!
+//! ### Checking Shipped Files at Build Time
+//!
+//! [`build::validate::<Config>(path)`](build::validate) fails the build when a properties file shipped with the crate,
+//! such as its defaults, no longer matches the type that reads it: a required key is missing or a value doesn't parse. It
+//! reruns the build script whenever the file changes. The type has to come from a crate listed under
+//! `[build-dependencies]`, since the build script is compiled before the crate itself.
+//!
+//! ```rust,no_run
+//! # mod app_config {
+//! #     #[derive(props_util::Properties)]
+//! #     pub struct Config {
+//! #         #[prop(key = "server.port")]
+//! #         port: u16,
+//! #     }
+//! # }
+//! // build.rs
+//! fn main() {
+//!     props_util::build::validate::<app_config::Config>("config/defaults.properties");
+//! }
+//! ```
+//!
+//! ### Linting Files
+//!
+//! [`lint_file`] checks a properties file without a type and returns every problem as a [`Diagnostic`] with its line,
+//! column, [`LintKind`] and message, for tools like pre-commit hooks. It reports keys set more than once, lines that
+//! aren't `key=value`, whitespace inside keys, after values or outside of ASCII, keys with non-ASCII characters, and
+//! backslash escapes that Java doesn't know or reads differently, such as a trailing backslash. [`lint_str`] does the
+//! same for content in memory.
+//!
+//! ```rust,no_run
+//! # fn main() -> std::io::Result<()> {
+//! for diagnostic in props_util::lint_file("config.properties")? {
+//!     eprintln!("config.properties:{diagnostic}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ### Readable Errors
+//!
+//! Every parse error carries the key it is about, the part of the value that failed and a hint, through `key()`,
+//! `value()` and `help()` of [`parse::Error`]. With the `fancy-errors` feature, `props_util::fancy::render(&error,
+//! path)` turns an error of `from_file` into a message for the person fixing the file, with the line that set the value
+//! and a caret under the part that failed:
+//!
+//! ```text
+//! error: Error Parsing `ports` with value `80, http, 443` Error Parsing with value `http`
+//!  --> config.properties:3:13
+//!   |
+//! 3 | ports = 80, http, 443
+//!   |             ^^^^
+//!   = help: expected a whole number from 0 to 65535
+//! ```
+//!
+//! `render_str(&error, name, content)` does the same for an error of `from_str`. Errors without a line, such as a
+//! missing key or a value from the environment, are rendered with the message and the hint only.
+//!
+//! ### Error Codes
+//!
+//! [`ErrorCode::of(&error)`](parse::ErrorCode::of) classifies any error of a generated constructor with a stable code:
+//! `MISSING_KEY`, `PARSE_FAILURE`, `MALFORMED_LINE`, `VALIDATION` for values that break a constraint like `min_len`,
+//! `LIMIT_EXCEEDED` and `IO` for files that can't be opened or read. Only `IO` is transient, which `is_transient()`
+//! tells orchestration that decides whether to retry a load or to page someone to fix the configuration. The names from
+//! `as_str()` don't change between versions.
+//!
+//! ### Loading Namespaces
+//!
+//! `from_file_namespaced(path, "tenant.")` loads one instance per namespace from a single file, such as one config per
+//! tenant, and returns them in a `HashMap` keyed by the namespace. The namespaces are discovered from the keys: the
+//! segment after the prefix names the namespace, and the rest of the key is the key of the field, so
+//! `tenant.acme.db.url` is `db.url` of `acme`. Each namespace is loaded like `from`, with its own defaults, and errors
+//! name the full key. `from_namespaced(map, prefix)` does the same for a map.
+//!
+//! ```rust
+//! use props_util::Properties;
+//!
+//! #[derive(Properties, Debug)]
+//! struct TenantConfig {
+//!     #[prop(key = "db.url")]
+//!     db_url: String,
+//!     #[prop(key = "max_users", default = "100")]
+//!     max_users: u32,
+//! }
+//!
+//! let props = props_util::parse_properties("tenant.acme.db.url = postgres://acme\ntenant.globex.db.url = postgres://globex\ntenant.globex.max_users = 5")?;
+//! let tenants = TenantConfig::from_namespaced(props, "tenant.")?;
+//! assert_eq!(tenants["acme"].max_users, 100);
+//! assert_eq!(tenants["globex"].db_url, "postgres://globex");
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! ### Templates in Values
+//!
+//! With the `templates` feature, `#[prop(templates)]` renders `${...}` templates in the values of the fields
+//! before they are parsed. A template names another key, `${user.name}`, or an environment variable, `${env:HOME}`,
+//! and can pipe the value through `lower`, `upper` and `default(text)`, which stands in for a missing or empty value:
+//! `db.url = postgres://${db.host|default(localhost)|lower}/app`. Referenced values are trimmed and rendered first,
+//! so templates can build on each other, and `$${` writes a literal `${`. A reference to a key that is not in the
+//! file, an unknown function or a template that references itself fails the load. Values that no field reads, such
+//! as the keys collected by `rest`, are kept as written, so their templates can't fail it. Field defaults are not
+//! visible to templates, only the keys that were loaded, and in `apply_overrides` only the keys of the overrides.
+//! Templates can't be combined with `lazy`.
+//!
+//! ### Profiles
+//!
+//! Keys can be qualified with a profile to keep the values of every deployment in a single file, such as
+//! `cache.size@prod = 1024` next to `cache.size@dev = 16`. `from_file_with_profile(path, profile)` loads the file
+//! for the active profile: a key qualified with it wins over the unqualified key, wherever it is in the file, and
+//! the keys of other profiles are ignored. The unqualified `cache.size` is the fallback for profiles that don't set
+//! their own value, before the `default` of the field. `from_with_profile(map, profile)` does the same for a map,
+//! and [`runtime::resolve_profile`] resolves a map without a derived type.
+//!
+//! ```rust
+//! use props_util::Properties;
+//!
+//! #[derive(Properties, Debug)]
+//! struct Config {
+//!     #[prop(key = "cache.size", default = "64")]
+//!     cache_size: u32,
+//! }
+//!
+//! let props = props_util::parse_properties("cache.size@prod = 1024\ncache.size@dev = 16")?;
+//! assert_eq!(Config::from_with_profile(props.clone(), "prod")?.cache_size, 1024);
+//! assert_eq!(Config::from_with_profile(props, "test")?.cache_size, 64);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
 //! ## Properties File Format
 //!
 //! The properties file follows a simple key-value format:
@@ -181,7 +723,7 @@
 //! - The format is `key=value`
 //! - Lines starting with `#` or `!` are treated as comments and ignored
 //! - Empty lines are ignored
-//! - Leading and trailing whitespace around both key and value is trimmed
+//! - Leading and trailing whitespace around both key and value is trimmed, unless the field has `trim = false`
 //!
 //! Example:
 //!
@@ -215,387 +757,119 @@
 //! - All fields must have the `#[prop]` attribute
 //! - Properties files must use the `key=value` format
 
-extern crate proc_macro;
-
-use proc_macro::TokenStream;
-use quote::quote;
-use syn::{DeriveInput, Error, Field, LitStr, parse_macro_input, punctuated::Punctuated, token::Comma};
-
-/// Derive macro for automatically implementing properties parsing functionality.
-///
-/// This macro generates implementations for:
-/// - `from_file`: Load properties from a file
-/// - `from`: Create instance from a type that implements Into<HashMap<String, String>>
-/// - `default`: Create instance with default values
-///
-/// # Example
-///
-/// ```rust
-/// use props_util::Properties;
-/// use std::io::Result;
-///
-/// #[derive(Properties, Debug)]
-/// struct Config {
-///     #[prop(key = "server.host", default = "localhost")]
-///     host: String,
-///     #[prop(key = "server.port", default = "8080")]
-///     port: u16,
-/// }
-///
-/// fn main() -> Result<()> {
-///     let config = Config::default()?;
-///     println!("Host: {}", config.host);
-///     println!("Port: {}", config.port);
-///     Ok(())
-/// }
-/// ```
-#[proc_macro_derive(Properties, attributes(prop))]
-pub fn parse_prop_derive(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let struct_name = &input.ident;
-
-    match generate_prop_fns(&input) {
-        Ok(prop_impl) => quote! {
-            impl #struct_name { #prop_impl }
-
-            impl std::convert::Into<std::collections::HashMap<String, String>> for #struct_name {
-                fn into(self) -> std::collections::HashMap<String, String> {
-                    self.into_hash_map()
-                }
-            }
-        }
-        .into(),
-        Err(e) => e.to_compile_error().into(),
-    }
-}
-
-fn extract_named_fields(input: &DeriveInput) -> syn::Result<Punctuated<Field, Comma>> {
-    let fields = match &input.data {
-        syn::Data::Struct(data_struct) => match &data_struct.fields {
-            syn::Fields::Named(fields_named) => &fields_named.named,
-            _ => return Err(Error::new_spanned(&input.ident, "Only named structs are allowd")),
-        },
-        _ => return Err(Error::new_spanned(&input.ident, "Only structs can be used on Properties")),
-    };
-
-    Ok(fields.to_owned())
-}
-
-fn generate_field_init_quote(field_type: &syn::Type, field_name: &proc_macro2::Ident, raw_value_str: proc_macro2::TokenStream, key: LitStr, is_option: bool) -> proc_macro2::TokenStream {
-    // Pregenerated token streams to generate values
-    let vec_parsing = quote! { Self::parse_vec::<_>(&val).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Error Parsing `{}` with value `{}` {}", #key, val, e)))? };
-    let parsing = quote! { Self::parse(&val).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Error Parsing `{}` with value `{}` {}", #key, val, e)))? };
-    let error = quote! { Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("`{}` value is not configured which is required", #key))) };
-
-    match field_type {
-        syn::Type::Path(tpath) if tpath.path.segments.last().is_some_and(|segment| segment.ident == "Vec") => match is_option {
-            false => quote! {
-                #field_name : match #raw_value_str {
-                    Some(val) => #vec_parsing,
-                    None => return #error
-                }
-            },
-            true => quote! {
-                #field_name : match #raw_value_str {
-                    Some(val) => Some(#vec_parsing),
-                    None => None
-                }
-            },
-        },
-        _ => match is_option {
-            false => quote! {
-                #field_name : match #raw_value_str {
-                    Some(val) => #parsing,
-                    None => return #error
-                }
-            },
-            true => quote! {
-                #field_name : match #raw_value_str {
-                    Some(val) => Some(#parsing),
-                    None => None
-                }
-            },
-        },
-    }
-}
-
-fn generate_init_token_streams(fields: Punctuated<Field, Comma>) -> syn::Result<Vec<proc_macro2::TokenStream>> {
-    let mut init_arr: Vec<proc_macro2::TokenStream> = Vec::new();
-
-    for field in fields {
-        let (key, is_env, default) = parse_key_default(&field).map_err(|_| Error::new_spanned(field.clone(), "Expecting `key` and `default` values"))?;
-        let field_name = field.ident.as_ref().to_owned().unwrap();
-        let field_type = &field.ty;
-
-        let val_token_stream = match default {
-            Some(default) => quote! { Some(propmap.get(#key).map(String::to_string).unwrap_or(#default.to_string())) },
-            None => quote! { propmap.get(#key).map(String::to_string) },
-        };
-
-        let val_token_stream = match is_env {
-            Some(env_key) => quote! { std::env::var(#env_key).map(|val| Some(val)).unwrap_or(#val_token_stream) },
-            None => val_token_stream,
-        };
-
-        let init = match field_type {
-            syn::Type::Path(tpath) if tpath.path.segments.last().is_some_and(|segment| segment.ident == "Option") => match tpath.path.segments.last().unwrap().to_owned().arguments {
-                syn::PathArguments::AngleBracketed(arguments) if arguments.args.first().is_some() => match arguments.args.first().unwrap() {
-                    syn::GenericArgument::Type(ftype) => generate_field_init_quote(ftype, field_name, val_token_stream, key, true),
-                    _ => panic!("Option not configured {field_name} properly"),
-                },
-                _ => panic!("Option not configured {field_name} properly"),
-            },
-            _ => generate_field_init_quote(field_type, field_name, val_token_stream, key, false),
-        };
-
-        init_arr.push(init);
-    }
-
-    Ok(init_arr)
-}
-
-fn generate_field_hm_token_stream(key: LitStr, field_type: &syn::Type, field_name: &proc_macro2::Ident, is_option: bool) -> proc_macro2::TokenStream {
-    let field_name_str = field_name.to_string();
-    match field_type {
-        syn::Type::Path(tpath) if tpath.path.segments.last().is_some_and(|segment| segment.ident == "Vec") => match is_option {
-            false => quote! {
-                // When convert to a hashmap, we insert #filed_name and #key. This will be very helpful
-                // when using the resultant Hashmap to construct some other type which may or may not configure key in the props. That type can look up
-                // either #key or #field_name whichever it wants to construct its values.
-                hm.insert(#field_name_str.to_string() ,self.#field_name.iter().map(|s| s.to_string()).collect::<Vec<String>>().join(","));
-                hm.insert(#key.to_string(), self.#field_name.iter().map(|s| s.to_string()).collect::<Vec<String>>().join(","));
-            },
-            true => quote! {
-                if self.#field_name.is_some() {
-                    hm.insert(#field_name_str.to_string() ,self.#field_name.clone().unwrap().iter().map(|s| s.to_string()).collect::<Vec<String>>().join(","));
-                    hm.insert(#key.to_string() ,self.#field_name.unwrap().iter().map(|s| s.to_string()).collect::<Vec<String>>().join(","));
-                }
-            },
-        },
-        _ => match is_option {
-            false => quote! {
-                hm.insert(#field_name_str.to_string(), self.#field_name.clone().to_string());
-                hm.insert(#key.to_string(), self.#field_name.to_string());
-            },
-            true => quote! {
-                if self.#field_name.is_some() {
-                    hm.insert(#field_name_str.to_string(), self.#field_name.clone().unwrap().to_string());
-                    hm.insert(#key.to_string(), self.#field_name.unwrap().to_string());
-                }
-            },
-        },
-    }
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// There is no filesystem on `wasm32-unknown-unknown`, so everything that reads files is left out there
+#[cfg(all(feature = "std", not(all(target_family = "wasm", target_os = "unknown"))))]
+mod audit;
+#[cfg(all(feature = "std", not(all(target_family = "wasm", target_os = "unknown"))))]
+pub mod build;
+#[cfg(feature = "std")]
+mod bus;
+#[cfg(all(feature = "std", not(all(target_family = "wasm", target_os = "unknown"))))]
+mod cache;
+#[doc(hidden)]
+pub mod check;
+#[cfg(all(feature = "config", not(all(target_family = "wasm", target_os = "unknown"))))]
+mod config_source;
+#[cfg(feature = "std")]
+mod diagnostics;
+mod diff;
+#[cfg(feature = "fancy-errors")]
+pub mod fancy;
+#[cfg(feature = "std")]
+mod files;
+#[cfg(feature = "std")]
+mod keys;
+#[cfg(feature = "std")]
+mod lint;
+#[cfg(feature = "std")]
+mod migration;
+#[cfg(feature = "std")]
+pub mod observe;
+pub mod parse;
+#[cfg(feature = "std")]
+mod props;
+#[cfg(feature = "std")]
+mod provenance;
+pub mod runtime;
+mod syntax;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "templates")]
+mod template;
+mod validate;
+mod writer;
+
+#[cfg(all(feature = "std", not(all(target_family = "wasm", target_os = "unknown"))))]
+pub use audit::{AuditEntry, AuditLog};
+#[cfg(feature = "std")]
+pub use bus::{ChangeBus, Subscription};
+#[cfg(all(feature = "std", not(all(target_family = "wasm", target_os = "unknown"))))]
+pub use cache::CachedLoader;
+#[cfg(all(feature = "config", not(all(target_family = "wasm", target_os = "unknown"))))]
+pub use config_source::PropertiesSource;
+pub use diff::KeyChange;
+#[cfg(all(feature = "std", not(all(target_family = "wasm", target_os = "unknown"))))]
+pub use lint::lint_file;
+#[cfg(feature = "std")]
+pub use lint::{Diagnostic, LintKind, lint_str};
+#[cfg(feature = "std")]
+pub use parse::parse_properties;
+#[cfg(feature = "std")]
+pub use migration::{MigratedKey, MigrationReport};
+#[cfg(feature = "std")]
+pub use props::Props;
+#[cfg(feature = "std")]
+pub use provenance::{Origin, Provenance};
+pub use props_util_derive::{Properties, props, props_check};
+pub use validate::ValidationReport;
+
+// Re-exports used by the generated code. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::check;
+    #[cfg(feature = "std")]
+    pub use crate::diagnostics::{FieldSchema, diagnostic_dump, schema_json};
+    pub use crate::diff::{diff, fingerprint};
+    #[cfg(feature = "std")]
+    pub use crate::files::{env_var, parse_content, trim_values};
+    #[cfg(all(feature = "std", not(all(target_family = "wasm", target_os = "unknown"))))]
+    pub use crate::files::{merge_dir, merge_files, merge_glob, read_content, read_each, read_file, read_file_limited, read_first, read_layered, read_standard, read_standard_with_origins, read_user, read_with_local, read_with_origins};
+    #[cfg(feature = "std")]
+    pub use crate::keys::{canonical_keys, load_namespaced, resolve_profile};
+    #[cfg(feature = "std")]
+    pub use crate::migration::migration_report;
+    #[cfg(feature = "std")]
+    pub use crate::observe::observe_load;
+    #[cfg(feature = "std")]
+    pub use crate::provenance::{Origins, explain};
+    #[cfg(feature = "testing")]
+    pub use crate::testing::key_override;
+    #[cfg(feature = "templates")]
+    pub use crate::template::render_templates;
+    pub use alloc::borrow::Cow;
+    #[cfg(feature = "mmap")]
+    pub use memmap2;
+    #[cfg(feature = "serde")]
+    pub use serde;
+    #[cfg(feature = "tracing")]
+    pub use tracing;
 }
 
-fn generate_hashmap_token_streams(fields: Punctuated<Field, Comma>) -> syn::Result<Vec<proc_macro2::TokenStream>> {
-    let mut init_arr: Vec<proc_macro2::TokenStream> = Vec::new();
-
-    for field in fields {
-        let (key, _, _) = parse_key_default(&field).map_err(|e| Error::new_spanned(field.clone(), format!("Error parsing prop {e}")))?;
-        let field_name = field.ident.as_ref().to_owned().unwrap();
-        let field_type = &field.ty;
-
-        let quote = match field_type {
-            syn::Type::Path(tpath) if tpath.path.segments.last().is_some_and(|segment| segment.ident == "Option") => match tpath.path.segments.last().unwrap().to_owned().arguments {
-                syn::PathArguments::AngleBracketed(arguments) if arguments.args.first().is_some() => match arguments.args.first().unwrap() {
-                    syn::GenericArgument::Type(ftype) => generate_field_hm_token_stream(key, ftype, field_name, true),
-                    _ => return Err(Error::new_spanned(field, "Optional {field_name} is not configured properly")),
-                },
-                _ => return Err(Error::new_spanned(field, "Optional {field_name} not configured properly")),
-            },
-            _ => generate_field_hm_token_stream(key, field_type, field_name, false),
-        };
-
-        init_arr.push(quote);
-    }
-
-    Ok(init_arr)
-}
-
-fn generate_prop_fns(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
-    let fields = extract_named_fields(input)?;
-    let init_arr = generate_init_token_streams(fields.clone())?;
-    let ht_arr = generate_hashmap_token_streams(fields)?;
-
-    let new_impl = quote! {
-
-        fn parse_vec<T: std::str::FromStr>(string: &str) -> anyhow::Result<Vec<T>> {
-            Ok(string
-                .split(',')
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty())
-                .map(|s| s.parse::<T>().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Error Parsing with value `{s}`"))))
-                .collect::<std::io::Result<Vec<T>>>()?)
-        }
-
-        fn parse<T : std::str::FromStr>(string : &str) -> anyhow::Result<T> {
-            Ok(string.parse::<T>().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Error Parsing with value `{string}`")))?)
-        }
-
-        /// Loads properties from a file into an instance of this struct.
-        ///
-        /// # Example
-        ///
-        /// ```rust,no_run
-        /// use props_util::Properties;
-        /// use std::io::Result;
-        ///
-        /// #[derive(Properties, Debug)]
-        /// struct Config {
-        ///     #[prop(key = "server.host", default = "localhost")]
-        ///     host: String,
-        ///
-        ///     #[prop(key = "server.port", default = "8080")]
-        ///     port: u16,
-        ///
-        ///     #[prop(key = "debug.enabled", default = "false")]
-        ///     debug: bool,
-        /// }
-        ///
-        /// fn main() -> Result<()> {
-        ///
-        ///     let config = Config::from_file("config.properties")?;
-        ///     println!("Server: {}:{}", config.host, config.port);
-        ///     println!("Debug mode: {}", config.debug);
-        ///     Ok(())
-        /// }
-        /// ```
-        ///
-        pub fn from_file(path : &str) -> std::io::Result<Self> {
-            use std::collections::HashMap;
-            use std::fs;
-            use std::io::{self, ErrorKind}; // Explicitly import ErrorKind
-            use std::path::Path; // Required for AsRef<Path> trait bound
-            use std::{fs::File, io::Read};
-
-            let mut content = String::new();
-
-            let mut file = File::open(path).map_err(|e| std::io::Error::new(e.kind(), format!("Error opening file {}", path)))?;
-            file.read_to_string(&mut content) .map_err(|e| std::io::Error::new(e.kind(), format!("Error Reading File : {}", path)))?;
-
-            let mut propmap = std::collections::HashMap::<String, String>::new();
-            for (line_num, line) in content.lines().enumerate() {
-                let line = line.trim();
-
-                if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
-                    continue;
-                }
-
-                // Find the first '=', handling potential whitespace
-                match line.split_once('=') {
-                    Some((key, value)) => propmap.insert(key.trim().to_string(), value.trim().to_string()),
-                    None => return Err(io::Error::new( ErrorKind::InvalidData, format!("Malformed line {} in '{}' (missing '='): {}", line_num + 1, path, line) )),
-                };
-            }
-
-            Ok(Self { #( #init_arr ),* })
-        }
-
-        fn into_hash_map(self) -> std::collections::HashMap<String, String> {
-            use std::collections::HashMap;
-            let mut hm = HashMap::<String, String>::new();
-            #( #ht_arr )*
-            hm
-        }
-
-        /// Convert from another type that implements `Properties` into this type.
-        ///
-        /// This function uses `into_hash_map` internally to perform the conversion.
-        /// The conversion will succeed only if the source type's keys match this type's keys. All the required keys must be present in the source type.
-        ///
-        ///
-        /// # Example
-        ///
-        /// ```rust,no_run
-        /// use props_util::Properties;
-        /// use std::io::Result;
-        ///
-        /// #[derive(Properties, Debug)]
-        /// struct ServerConfig {
-        ///     #[prop(key = "host", default = "localhost")]
-        ///     host: String,
-        ///     #[prop(key = "port", default = "8080")]
-        ///     port: u16,
-        /// }
-        ///
-        /// #[derive(Properties, Debug)]
-        /// struct ClientConfig {
-        ///     #[prop(key = "host", default = "localhost")]  // Note: using same key as ServerConfig
-        ///     server_host: String,
-        ///     #[prop(key = "port", default = "8080")]      // Note: using same key as ServerConfig
-        ///     server_port: u16,
-        /// }
-        ///
-        /// fn main() -> Result<()> {
-        ///     let server_config = ServerConfig::default()?;
-        ///     let client_config = ClientConfig::from(server_config)?;
-        ///     println!("Server host: {}", client_config.server_host);
-        ///     println!("Server port: {}", client_config.server_port);
-        ///     Ok(())
-        /// }
-        /// ```
-        pub fn from<T>(other: T) -> std::io::Result<Self>
-        where
-            T: Into<std::collections::HashMap<String, String>>
-        {
-            let propmap = other.into();
-            Ok(Self { #( #init_arr ),* })
-        }
-
-        pub fn default() -> std::io::Result<Self> {
-            use std::collections::HashMap;
-            let mut propmap = HashMap::<String, String>::new();
-            Ok(Self { #( #init_arr ),* })
-        }
-    };
-
-    Ok(new_impl)
-}
-
-fn parse_key_default(field: &syn::Field) -> syn::Result<(LitStr, Option<LitStr>, Option<LitStr>)> {
-    let prop_attr = field.attrs.iter().find(|attr| attr.path().is_ident("prop"));
-    let prop_attr = match prop_attr {
-        Some(attr) => attr,
-        None => {
-            // If there is no "prop" attr, simply return the field name with None default
-            let ident = field.ident.to_owned().unwrap();
-            let key = LitStr::new(&ident.to_string(), ident.span());
-            return Ok((key, None, None));
-        }
-    };
-
-    let mut key: Option<LitStr> = None;
-    let mut default: Option<LitStr> = None;
-    let mut env: Option<LitStr> = None;
-
-    // parse the metadata to find `key` and `default` values
-    prop_attr.parse_nested_meta(|meta| {
-        match () {
-            _ if meta.path.is_ident("key") => match key {
-                Some(_) => return Err(meta.error("duplicate 'key' parameter")),
-                None => key = Some(meta.value()?.parse()?),
-            },
-            _ if meta.path.is_ident("default") => match default {
-                Some(_) => return Err(meta.error("duplicate 'default' parameter")),
-                None => default = Some(meta.value()?.parse()?),
-            },
-            _ if meta.path.is_ident("env") => match env {
-                Some(_) => return Err(meta.error("duplicate `env` parameter")),
-                None => env = Some(meta.value()?.parse()?),
-            },
-            _ => return Err(meta.error(format!("unrecognized parameter '{}' in #[prop] attribute", meta.path.get_ident().map(|i| i.to_string()).unwrap_or_else(|| "<?>".into())))),
-        }
-        Ok(())
-    })?;
-
-    // if there is no key, simple use the ident field name
-    let key_str = match key {
-        Some(key) => key,
-        None => match field.ident.to_owned() {
-            Some(key) => LitStr::new(&key.to_string(), key.span()),
-            None => return Err(syn::Error::new_spanned(prop_attr, "Missing 'key' parameter in #[prop] attribute")),
-        },
-    };
-
-    Ok((key_str, env, default))
+/// Common interface implemented by every `#[derive(Properties)]` type.
+///
+/// The derive also generates inherent methods with the same names, so calling `Config::from_file` works without
+/// importing this trait. The trait exists so that generic helpers such as [`CachedLoader`] can work with any derived type.
+#[cfg(feature = "std")]
+pub trait Properties: Sized {
+    /// Loads properties from the file at `path`. See the generated inherent `from_file` for details.
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    fn from_file(path: &str) -> std::io::Result<Self>;
+
+    /// Checks the file at `path` without constructing an instance. See the generated inherent `validate_file` for details.
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    fn validate_file(path: &str) -> std::io::Result<ValidationReport>;
 }
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// What a [`Diagnostic`] found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// A key set more than once, where the last value wins.
+    DuplicateKey,
+    /// A line that is neither blank, a comment nor `key=value`, which fails loading.
+    MalformedLine,
+    /// Whitespace that is easy to miss: inside a key, after a value or any whitespace outside of ASCII.
+    SuspiciousWhitespace,
+    /// A character outside of ASCII in a key.
+    NonAsciiKey,
+    /// A backslash escape Java doesn't know, a broken `\uXXXX` or a trailing backslash, which Java reads as a line
+    /// continuation.
+    UnknownEscape,
+}
+
+/// A problem found by [`lint_file`] or [`lint_str`], at a 1 based line and character column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The line of the problem, starting at 1.
+    pub line: usize,
+    /// The character the problem starts at within the line, starting at 1.
+    pub column: usize,
+    /// What was found, for tools that filter or map the diagnostics.
+    pub kind: LintKind,
+    /// A description of the problem naming the key.
+    pub message: String,
+}
+
+/// Formats as `line:column: message`.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Lints the properties file at `path`, returning every problem ordered by position. Only a file that can't be read
+/// is an error.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn lint_file(path: &str) -> std::io::Result<Vec<Diagnostic>> {
+    let content = std::fs::read_to_string(path).map_err(|e| std::io::Error::new(e.kind(), format!("Error Reading File : {}", path)))?;
+    Ok(lint_str(&content))
+}
+
+/// Lints properties `content` like [`lint_file`].
+///
+/// Nothing here stops the file from loading except a malformed line, the rest are things that load but likely don't
+/// mean what they say, or mean something else to Java.
+pub fn lint_str(content: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut seen = HashMap::<&str, usize>::new();
+    for (i, line) in content.lines().enumerate() {
+        let line_num = i + 1;
+        let mut push = |column: usize, kind: LintKind, message: String| diagnostics.push(Diagnostic { line: line_num, column, kind, message });
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+            continue;
+        }
+        let start = line.len() - line.trim_start().len();
+        let Some(eq) = line.find('=') else {
+            push(column(line, start), LintKind::MalformedLine, format!("`{trimmed}` is not a `key=value` line"));
+            continue;
+        };
+
+        let key = line[..eq].trim();
+        let value_start = eq + 1 + (line[eq + 1..].len() - line[eq + 1..].trim_start().len());
+        if let Some(first) = seen.insert(key, line_num) {
+            push(column(line, start), LintKind::DuplicateKey, format!("`{key}` is already set on line {first}, this value replaces it"));
+        }
+        if let Some(pos) = key.find(char::is_whitespace) {
+            push(column(line, start + pos), LintKind::SuspiciousWhitespace, format!("`{key}` contains whitespace"));
+        }
+        if let Some(pos) = key.find(|c: char| !c.is_ascii()) {
+            push(column(line, start + pos), LintKind::NonAsciiKey, format!("`{key}` contains a character outside of ASCII"));
+        }
+        if let Some(pos) = line.find(|c: char| !c.is_ascii() && (c.is_whitespace() || matches!(c, '\u{200b}' | '\u{feff}'))) {
+            push(
+                column(line, pos),
+                LintKind::SuspiciousWhitespace,
+                format!("`{key}` contains invisible whitespace U+{:04X}", line[pos..].chars().next().unwrap_or_default() as u32),
+            );
+        }
+        if line.len() > value_start && line.trim_end().len() < line.len() {
+            push(column(line, line.trim_end().len()), LintKind::SuspiciousWhitespace, format!("`{key}` has whitespace after its value"));
+        }
+        for pos in escapes(line) {
+            let escape = &line[pos..];
+            let message = match escape.len() {
+                1 => format!("`{key}` ends with a backslash, which Java reads as a line continuation"),
+                _ if escape.starts_with("\\u") => format!("`{key}` has a `\\u` escape without 4 hex digits"),
+                _ => format!("`{key}` has the unknown escape `\\{}`", escape[1..].chars().next().unwrap_or_default()),
+            };
+            push(column(line, pos), LintKind::UnknownEscape, message);
+        }
+    }
+    // The checks of a line run in a fixed order, which isn't the order of their columns
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+    diagnostics
+}
+
+/// Returns the byte position of every backslash in `s` that doesn't start an escape Java knows.
+fn escapes(s: &str) -> Vec<usize> {
+    let mut unknown = Vec::new();
+    let mut chars = s.char_indices();
+    while let Some((pos, c)) = chars.next() {
+        if c != '\\' {
+            continue;
+        }
+        match chars.next() {
+            Some((_, 't' | 'n' | 'r' | 'f' | '\\' | '=' | ':' | '#' | '!' | ' ')) => {}
+            Some((next, 'u')) if s[next + 1..].get(..4).is_some_and(|hex| hex.chars().all(|c| c.is_ascii_hexdigit())) => {}
+            _ => unknown.push(pos),
+        }
+    }
+    unknown
+}
+
+/// Turns the byte position `pos` of `line` into a 1 based character column.
+fn column(line: &str, pos: usize) -> usize {
+    line[..pos].chars().count() + 1
+}
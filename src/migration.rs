@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// A key that was read through its `renamed_from` name during a load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigratedKey {
+    /// The previous name that is still used by the configuration.
+    pub old_key: String,
+    /// The current name the configuration should use instead.
+    pub new_key: String,
+    /// The file the old key was read from, `None` if the properties didn't come from a file.
+    pub source: Option<String>,
+}
+
+/// Legacy keys used during a load, as returned by the generated `from_file_with_report` and `from_files_with_report`.
+///
+/// An empty report means the configuration is up to date.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    keys: Vec<MigratedKey>,
+}
+
+impl MigrationReport {
+    /// Returns whether no legacy keys were used.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns the legacy keys that were used.
+    pub fn keys(&self) -> &[MigratedKey] {
+        &self.keys
+    }
+}
+
+/// Formats as `'old' is deprecated, rename it to 'new' (in 'file')`.
+impl fmt::Display for MigratedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' is deprecated, rename it to '{}'", self.old_key, self.new_key)?;
+        match &self.source {
+            Some(source) => write!(f, " (in '{source}')"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Lists one legacy key per line.
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, key) in self.keys.iter().enumerate() {
+            match i {
+                0 => write!(f, "{key}")?,
+                _ => write!(f, "\n{key}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builds the report for properties merged from `maps` in order, where `sources` names the file of each map.
+///
+/// A renamed key counts as used if no map has the new key and at least one has the old key. The source is the last
+/// map with the old key, since that is the value the merge keeps.
+pub fn migration_report(renamed: &[(&str, &str)], sources: &[&str], maps: &[HashMap<String, String>]) -> MigrationReport {
+    let keys = renamed
+        .iter()
+        .filter(|(_, new)| !maps.iter().any(|map| map.contains_key(*new)))
+        .filter_map(|(old, new)| {
+            let index = maps.iter().rposition(|map| map.contains_key(*old))?;
+            Some(MigratedKey {
+                old_key: old.to_string(),
+                new_key: new.to_string(),
+                source: sources.get(index).map(|source| source.to_string()),
+            })
+        })
+        .collect();
+    MigrationReport { keys }
+}
@@ -0,0 +1,83 @@
+//! Callbacks invoked whenever a derived type is loaded, for exporting metrics about configuration loads.
+//!
+//! Observers are registered once for the whole process and see every load of every derived type, including the
+//! reloads done by [`CachedLoader`](crate::CachedLoader). Until one is registered, loading only pays for a single
+//! atomic load.
+//!
+//! ```rust
+//! use props_util::{Properties, observe};
+//!
+//! #[derive(Properties)]
+//! struct Config {
+//!     #[prop(key = "server.port", default = "8080")]
+//!     port: u16,
+//! }
+//!
+//! observe::add_load_observer(|event| {
+//!     let outcome = if event.error.is_none() { "ok" } else { "error" };
+//!     println!("{} from {} in {:?}: {outcome}", event.type_name, event.source, event.elapsed);
+//! });
+//!
+//! let config = Config::from_str("server.port = 9090")?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::io;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+type Observer = Box<dyn Fn(&LoadEvent<'_>) + Send + Sync>;
+
+static OBSERVERS: RwLock<Vec<Observer>> = RwLock::new(Vec::new());
+static OBSERVED: AtomicBool = AtomicBool::new(false);
+
+/// A finished load of a derived type, passed to every observer.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct LoadEvent<'a> {
+    /// The full name of the loaded type, as returned by [`std::any::type_name`].
+    pub type_name: &'static str,
+    /// Where the properties came from: the path for files, the paths separated by `, ` when several files are merged,
+    /// or a placeholder like `<str>` or `<map>` for other constructors.
+    pub source: &'a str,
+    /// The time the load took, including reading files. Always zero on `wasm32-unknown-unknown`, which has no clock.
+    pub elapsed: Duration,
+    /// The error the load failed with, `None` if it succeeded.
+    pub error: Option<&'a io::Error>,
+}
+
+/// Registers `observer` to be called after every load of every derived type, on the loading thread.
+///
+/// Observers can't be removed. They must not load derived types themselves, since that would call them recursively.
+pub fn add_load_observer(observer: impl Fn(&LoadEvent<'_>) + Send + Sync + 'static) {
+    OBSERVERS.write().unwrap_or_else(|e| e.into_inner()).push(Box::new(observer));
+    OBSERVED.store(true, Ordering::Release);
+}
+
+#[doc(hidden)]
+pub fn observe_load<T>(type_name: &'static str, source: impl FnOnce() -> String, load: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+    if !OBSERVED.load(Ordering::Acquire) {
+        return load();
+    }
+
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    let start = std::time::Instant::now();
+    let result = load();
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    let elapsed = start.elapsed();
+    #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+    let elapsed = Duration::ZERO;
+
+    let source = source();
+    let event = LoadEvent {
+        type_name,
+        source: &source,
+        elapsed,
+        error: result.as_ref().err(),
+    };
+    for observer in OBSERVERS.read().unwrap_or_else(|e| e.into_inner()).iter() {
+        observer(&event);
+    }
+    result
+}
@@ -0,0 +1,782 @@
+//! Parsing of properties text and values. Only needs `alloc`, so it is available without the `std` feature.
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Range, RangeInclusive};
+use core::str::FromStr;
+
+/// What went wrong while parsing properties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A line is neither empty, a comment nor a `key=value` pair.
+    MalformedLine,
+    /// A required key has no value and no default.
+    MissingKey,
+    /// A value could not be parsed into the field type.
+    InvalidValue,
+    /// The input is larger than a configured [`Limits`].
+    LimitExceeded,
+    /// A value parsed but breaks a constraint of the field, such as `min_len`, `empty = "error"` or a range that
+    /// starts after it ends.
+    ConstraintViolated,
+}
+
+impl ErrorKind {
+    /// Returns the stable code of this kind.
+    pub fn code(self) -> ErrorCode {
+        match self {
+            ErrorKind::MalformedLine => ErrorCode::MalformedLine,
+            ErrorKind::MissingKey => ErrorCode::MissingKey,
+            ErrorKind::InvalidValue => ErrorCode::ParseFailure,
+            ErrorKind::LimitExceeded => ErrorCode::LimitExceeded,
+            ErrorKind::ConstraintViolated => ErrorCode::Validation,
+        }
+    }
+}
+
+/// A stable, machine readable classification of why a load failed, for orchestration that decides whether to retry.
+///
+/// Every code except [`ErrorCode::Io`] means the configuration itself has to be fixed, so retrying won't help. The
+/// names returned by [`ErrorCode::as_str`] won't change between versions and can be matched on in logs and alerts.
+///
+/// ```rust
+/// use props_util::parse::ErrorCode;
+/// use props_util::Properties;
+///
+/// #[derive(Properties, Debug)]
+/// struct Config {
+///     #[prop(key = "server.port")]
+///     port: u16,
+/// }
+///
+/// let error = Config::from_str("server.port = eighty").unwrap_err();
+/// assert_eq!(ErrorCode::of(&error).as_str(), "PARSE_FAILURE");
+/// assert!(!ErrorCode::of(&error).is_transient());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// `MISSING_KEY`: a required key has no value and no default.
+    MissingKey,
+    /// `PARSE_FAILURE`: a value could not be parsed into the field type.
+    ParseFailure,
+    /// `MALFORMED_LINE`: a line is neither empty, a comment nor a `key=value` pair, or isn't valid UTF-8.
+    MalformedLine,
+    /// `IO`: the file could not be opened or read, which may be transient.
+    Io,
+    /// `VALIDATION`: a value parsed but breaks a constraint of the field.
+    Validation,
+    /// `LIMIT_EXCEEDED`: the input is larger than a configured [`Limits`].
+    LimitExceeded,
+}
+
+impl ErrorCode {
+    /// Returns the code of an error returned by a generated constructor. Errors that don't come from parsing, such as
+    /// a missing file or a permission problem, are [`ErrorCode::Io`].
+    #[cfg(feature = "std")]
+    pub fn of(error: &std::io::Error) -> Self {
+        match error.get_ref().and_then(|e| e.downcast_ref::<Error>()) {
+            Some(error) => error.code(),
+            None => ErrorCode::Io,
+        }
+    }
+
+    /// Returns the name of the code, like `MISSING_KEY`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::MissingKey => "MISSING_KEY",
+            ErrorCode::ParseFailure => "PARSE_FAILURE",
+            ErrorCode::MalformedLine => "MALFORMED_LINE",
+            ErrorCode::Io => "IO",
+            ErrorCode::Validation => "VALIDATION",
+            ErrorCode::LimitExceeded => "LIMIT_EXCEEDED",
+        }
+    }
+
+    /// Returns whether the failure may go away on its own, so that retrying the load makes sense. Only true for
+    /// [`ErrorCode::Io`].
+    pub fn is_transient(self) -> bool {
+        self == ErrorCode::Io
+    }
+}
+
+/// Formats as the name of the code, like `MISSING_KEY`.
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned by the parsing functions in this module.
+///
+/// With the `std` feature this converts into a `std::io::Error` of kind `InvalidData`, which is what the generated
+/// constructors return.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+    key: Option<String>,
+    value: Option<String>,
+    line: Option<usize>,
+    help: Option<String>,
+}
+
+impl Error {
+    fn new(kind: ErrorKind, message: String) -> Self {
+        Self {
+            kind,
+            message,
+            key: None,
+            value: None,
+            line: None,
+            help: None,
+        }
+    }
+
+    fn for_key(kind: ErrorKind, key: &str, message: String) -> Self {
+        Self {
+            key: Some(key.to_string()),
+            ..Self::new(kind, message)
+        }
+    }
+
+    /// Returns the kind of this error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the stable code of this error.
+    pub fn code(&self) -> ErrorCode {
+        self.kind.code()
+    }
+
+    /// Returns the key whose value is missing or invalid, if the error is about a single key.
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Returns a hint on how to fix the value, such as the values a `bool` accepts or the `help` of the field.
+    pub fn help(&self) -> Option<&str> {
+        self.help.as_deref()
+    }
+
+    /// Returns the part of the value that failed to parse, which is a single element for lists and maps.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    /// Returns the line of a malformed line error.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Replaces the hint of this error, as configured with `#[prop(help = "...")]`.
+    #[doc(hidden)]
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    /// Prefixes the key of this error with the namespace it was loaded from, so the message names the key as it is
+    /// written in the file.
+    #[doc(hidden)]
+    pub fn under(mut self, prefix: &str) -> Self {
+        if let Some(key) = self.key.take() {
+            let full_key = format!("{prefix}{key}");
+            self.message = self.message.replacen(&format!("`{key}`"), &format!("`{full_key}`"), 1);
+            self.key = Some(full_key);
+        }
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn missing_key(key: &str) -> Self {
+        Self::for_key(ErrorKind::MissingKey, key, format!("`{key}` value is not configured which is required"))
+    }
+
+    #[doc(hidden)]
+    pub fn empty_value(key: &str) -> Self {
+        Self::for_key(ErrorKind::ConstraintViolated, key, format!("`{key}` is configured with an empty value"))
+    }
+
+    #[doc(hidden)]
+    pub fn invalid_utf8(line_num: Option<usize>, source: &str) -> Self {
+        Self {
+            line: line_num,
+            ..Self::new(ErrorKind::MalformedLine, format!("Error Reading File : {source} (invalid UTF-8)"))
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn malformed_line(line_num: usize, source: Option<&str>, line: &str) -> Self {
+        let message = match source {
+            Some(source) => format!("Malformed line {line_num} in '{source}' (missing '='): {line}"),
+            None => format!("Malformed line {line_num} (missing '='): {line}"),
+        };
+        Self {
+            line: Some(line_num),
+            ..Self::new(ErrorKind::MalformedLine, message)
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn invalid_template(key: &str, reason: &str) -> Self {
+        Self::for_key(ErrorKind::InvalidValue, key, format!("`{key}` has an invalid template: {reason}"))
+    }
+
+    fn too_short(key: &str, len: usize, min_len: usize) -> Self {
+        let elements = if min_len == 1 { "element" } else { "elements" };
+        Self::for_key(ErrorKind::ConstraintViolated, key, format!("`{key}` needs at least {min_len} {elements}, found {len}"))
+    }
+
+    #[doc(hidden)]
+    pub fn wrong_len(key: &str, len: usize, expected: usize) -> Self {
+        let elements = if expected == 1 { "element" } else { "elements" };
+        Self::for_key(ErrorKind::InvalidValue, key, format!("`{key}` needs exactly {expected} {elements}, found {len}"))
+    }
+
+    fn reversed_range(key: &str, value: &str) -> Self {
+        Self::for_key(ErrorKind::ConstraintViolated, key, format!("`{key}` range `{value}` starts after it ends"))
+    }
+
+    fn limit_exceeded(source: Option<&str>, what: fmt::Arguments<'_>) -> Self {
+        let message = match source {
+            Some(source) => format!("{what} in '{source}'"),
+            None => format!("{what}"),
+        };
+        Self::new(ErrorKind::LimitExceeded, message)
+    }
+
+    fn invalid_value(key: &str, value: &str, element: &str) -> Self {
+        let message = format!("Error Parsing `{key}` with value `{value}` Error Parsing with value `{element}`");
+        Self {
+            value: Some(element.to_string()),
+            ..Self::for_key(ErrorKind::InvalidValue, key, message)
+        }
+    }
+
+    /// Takes over the hint of `other`, the error of a single element.
+    fn with_help_of(mut self, other: Error) -> Self {
+        self.help = other.help;
+        self
+    }
+
+    /// Hints at the values of `T` if it is a primitive type.
+    fn with_type_hint<T>(mut self) -> Self {
+        let int = |min: i128, max: u128| Some(format!("expected a whole number from {min} to {max}"));
+        self.help = match core::any::type_name::<T>() {
+            "bool" => Some("expected `true` or `false`".to_string()),
+            "char" => Some("expected a single character".to_string()),
+            "f32" | "f64" => Some("expected a number like `1.5`".to_string()),
+            "u8" => int(0, u8::MAX.into()),
+            "u16" => int(0, u16::MAX.into()),
+            "u32" => int(0, u32::MAX.into()),
+            "u64" => int(0, u64::MAX.into()),
+            "u128" => int(0, u128::MAX),
+            "usize" => int(0, usize::MAX as u128),
+            "i8" => int(i8::MIN.into(), i8::MAX as u128),
+            "i16" => int(i16::MIN.into(), i16::MAX as u128),
+            "i32" => int(i32::MIN.into(), i32::MAX as u128),
+            "i64" => int(i64::MIN.into(), i64::MAX as u128),
+            "i128" => int(i128::MIN, i128::MAX as u128),
+            "isize" => int(isize::MIN as i128, isize::MAX as u128),
+            _ => None,
+        };
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl core::error::Error for Error {}
+
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(e: Error) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+    }
+}
+
+/// Bounds on the size of properties input, for loading files that come from users.
+///
+/// Every limit is off until it is set. Loads that go over a limit fail with [`ErrorKind::LimitExceeded`] as soon as it
+/// is reached, before the rest of the input is read.
+///
+/// ```rust
+/// use props_util::parse::{ErrorKind, Limits};
+///
+/// let limits = Limits::new().max_file_size(64 * 1024).max_line_len(1024).max_keys(2);
+/// assert!(limits.check("a = 1\nb = 2").is_ok());
+/// assert_eq!(limits.check("a = 1\nb = 2\nc = 3").unwrap_err().kind(), ErrorKind::LimitExceeded);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    max_file_size: Option<usize>,
+    max_line_len: Option<usize>,
+    max_keys: Option<usize>,
+}
+
+impl Limits {
+    /// Creates limits that allow any input.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the whole input to `bytes`.
+    pub fn max_file_size(mut self, bytes: usize) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Limits every line to `bytes`, not counting the line ending.
+    pub fn max_line_len(mut self, bytes: usize) -> Self {
+        self.max_line_len = Some(bytes);
+        self
+    }
+
+    /// Limits the number of `key=value` lines to `keys`. A repeated key counts every time.
+    pub fn max_keys(mut self, keys: usize) -> Self {
+        self.max_keys = Some(keys);
+        self
+    }
+
+    /// Checks properties text against the limits without parsing it. Malformed lines are left for the parser to report.
+    pub fn check(&self, content: &str) -> Result<(), Error> {
+        self.check_size(None, content.len())?;
+        let mut keys = 0;
+        for (line_num, line) in content.lines().enumerate() {
+            self.check_line(None, line_num + 1, line.len())?;
+            if let Ok(Some(_)) = parse_line_untrimmed(line) {
+                keys += 1;
+                self.check_keys(None, keys)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_size(&self, source: Option<&str>, size: usize) -> Result<(), Error> {
+        match self.max_file_size {
+            Some(max) if size > max => Err(Error::limit_exceeded(source, format_args!("Input of {size} bytes is larger than the limit of {max} bytes"))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the length of a line without its line ending.
+    pub(crate) fn check_line(&self, source: Option<&str>, line_num: usize, len: usize) -> Result<(), Error> {
+        match self.max_line_len {
+            Some(max) if len > max => Err(Error::limit_exceeded(source, format_args!("Line {line_num} is longer than the limit of {max} bytes"))),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_keys(&self, source: Option<&str>, keys: usize) -> Result<(), Error> {
+        match self.max_keys {
+            Some(max) if keys > max => Err(Error::limit_exceeded(source, format_args!("Input has more than the limit of {max} keys"))),
+            _ => Ok(()),
+        }
+    }
+
+    /// The number of bytes to read at most for the next line: enough to tell that it goes over `max_line_len`.
+    #[cfg(all(feature = "std", not(all(target_family = "wasm", target_os = "unknown"))))]
+    pub(crate) fn line_read_len(&self) -> u64 {
+        self.max_line_len.map_or(u64::MAX, |max| max as u64 + 2)
+    }
+}
+
+/// Parses a single line, returning `None` for empty and comment lines.
+///
+/// Lines starting with `#` or `!` are comments. Otherwise the line is split at the first `=` and whitespace around
+/// both key and value is trimmed. A line without `=` is malformed, and the caller decides how to report it.
+pub fn parse_line(line: &str) -> Result<Option<(&str, &str)>, &str> {
+    Ok(parse_line_untrimmed(line)?.map(|(key, value)| (key, value.trim())))
+}
+
+/// Parses a single line like `parse_line`, but keeps the value exactly as it follows the `=`, only without the line
+/// ending. The generated constructors read files this way so that `#[prop(trim = false)]` fields see the whitespace.
+pub fn parse_line_untrimmed(line: &str) -> Result<Option<(&str, &str)>, &str> {
+    crate::syntax::split_line(line)
+}
+
+/// Strips a trailing comment from a raw value, as requested by `#[prop(inline_comments)]`, and trims it if `trim`.
+///
+/// A `#` at the start of the value or after whitespace starts the comment, so `8080 # admin port` is `8080` while
+/// `https://host/#top` is kept whole. `\#` stands for a literal `#` anywhere in the value.
+pub fn strip_inline_comment(value: &str, trim: bool) -> Cow<'_, str> {
+    let (end, escaped) = crate::syntax::comment_start(value);
+    let value = &value[..end];
+    let value = if trim { value.trim() } else { value };
+    match escaped {
+        true => Cow::Owned(value.replace("\\#", "#")),
+        false => Cow::Borrowed(value),
+    }
+}
+
+/// Parses properties text into a map of slices borrowed from `content`. Later duplicates of a key win.
+pub fn parse_str(content: &str) -> Result<BTreeMap<&str, &str>, Error> {
+    let mut propmap = parse_str_untrimmed(content)?;
+    propmap.values_mut().for_each(|value| *value = value.trim());
+    Ok(propmap)
+}
+
+/// Parses properties text into an owned map, with the same rules as `parse_str` and the generated constructors.
+///
+/// ```rust
+/// let props = props_util::parse_properties("# server\nserver.port = 9090\nserver.host=localhost")?;
+/// assert_eq!(props["server.port"], "9090");
+/// assert_eq!(props.len(), 2);
+/// # Ok::<(), props_util::parse::Error>(())
+/// ```
+#[cfg(feature = "std")]
+pub fn parse_properties(content: &str) -> Result<std::collections::HashMap<String, String>, Error> {
+    Ok(parse_str(content)?.into_iter().map(|(key, value)| (key.to_string(), value.to_string())).collect())
+}
+
+/// Parses properties text like `parse_str`, keeping the whitespace around values as `parse_line_untrimmed` does.
+pub fn parse_str_untrimmed(content: &str) -> Result<BTreeMap<&str, &str>, Error> {
+    let mut propmap = BTreeMap::new();
+    for (line_num, line) in content.lines().enumerate() {
+        if let Some((key, value)) = parse_line_untrimmed(line).map_err(|line| Error::malformed_line(line_num + 1, None, line))? {
+            propmap.insert(key, value);
+        }
+    }
+    Ok(propmap)
+}
+
+/// Parses the value configured for `key`.
+pub fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, Error> {
+    value.parse::<T>().map_err(|_| Error::invalid_value(key, value, value).with_type_hint::<T>())
+}
+
+/// Parses the comma separated value configured for `key`, skipping empty elements.
+///
+/// An element in double quotes may contain commas, so `"Smith, John", "Doe, Jane"` has two elements. Inside the
+/// quotes `\"` stands for a quote and `\\` for a backslash, and a quoted empty element `""` is kept.
+pub fn parse_vec<T: FromStr>(key: &str, value: &str) -> Result<Vec<T>, Error> {
+    split_list(value).into_iter().map(|s| s.parse::<T>().map_err(|_| Error::invalid_value(key, value, &s).with_type_hint::<T>())).collect()
+}
+
+/// The number syntax a field accepts besides what `FromStr` reads, as configured with `#[prop(decimal_comma)]`,
+/// `#[prop(underscores)]` and `#[prop(radix_prefixes)]`.
+///
+/// ```rust
+/// use props_util::parse::{NumberSyntax, parse_number};
+///
+/// let syntax = NumberSyntax::new().underscores(true).radix_prefixes(true);
+/// assert_eq!(parse_number::<u64>("max_bytes", "1_000_000", syntax)?, 1_000_000);
+/// assert_eq!(parse_number::<u32>("mask", "0o755", syntax)?, 0o755);
+/// # Ok::<(), props_util::parse::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NumberSyntax {
+    decimal_comma: bool,
+    underscores: bool,
+    radix_prefixes: bool,
+}
+
+impl NumberSyntax {
+    /// Creates a syntax that accepts only what `FromStr` reads.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts a decimal comma, as in `3,14`, in a value with a single `,` and no `.`.
+    pub fn decimal_comma(mut self, decimal_comma: bool) -> Self {
+        self.decimal_comma = decimal_comma;
+        self
+    }
+
+    /// Accepts `_` between digits, as in `1_000_000`. A value starting with `_` is left as is.
+    pub fn underscores(mut self, underscores: bool) -> Self {
+        self.underscores = underscores;
+        self
+    }
+
+    /// Accepts integers in hexadecimal, octal and binary with a `0x`, `0o` or `0b` prefix, as in `0x1F`, `-0o755` or
+    /// `0b1010`.
+    pub fn radix_prefixes(mut self, radix_prefixes: bool) -> Self {
+        self.radix_prefixes = radix_prefixes;
+        self
+    }
+
+    /// Parses `value` after rewriting it into what `FromStr` reads, if the syntax changes anything.
+    fn parse<T: FromStr>(self, value: &str) -> Option<T> {
+        let mut value = Cow::Borrowed(value);
+        if self.underscores && value.contains('_') && !value.starts_with('_') {
+            value = Cow::Owned(value.replace('_', ""));
+        }
+        if self.decimal_comma && value.matches(',').count() == 1 && !value.contains('.') {
+            value = Cow::Owned(value.replacen(',', ".", 1));
+        }
+        if self.radix_prefixes {
+            // Written out in decimal, the number goes through `FromStr` of `T`, which checks its range
+            if let Some(decimal) = radix_to_decimal(&value) {
+                value = Cow::Owned(decimal);
+            }
+        }
+        match value {
+            Cow::Owned(value) => value.parse().ok(),
+            Cow::Borrowed(_) => None,
+        }
+    }
+}
+
+/// Rewrites an integer with a radix prefix, such as `-0x1F`, in decimal.
+fn radix_to_decimal(value: &str) -> Option<String> {
+    let (sign, unsigned) = match value.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", value.strip_prefix('+').unwrap_or(value)),
+    };
+    let radix = match unsigned.get(..2)? {
+        "0x" | "0X" => 16,
+        "0o" | "0O" => 8,
+        "0b" | "0B" => 2,
+        _ => return None,
+    };
+    let digits = &unsigned[2..];
+    // `from_str_radix` would take another sign after the prefix
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+    Some(format!("{sign}{}", u128::from_str_radix(digits, radix).ok()?))
+}
+
+/// Parses the value configured for `key` like `parse_value`, also accepting the number syntax of `syntax` if the
+/// value doesn't parse as is.
+pub fn parse_number<T: FromStr>(key: &str, value: &str, syntax: NumberSyntax) -> Result<T, Error> {
+    parse_value(key, value).or_else(|e| syntax.parse(value).ok_or(e))
+}
+
+/// Parses the elements of the value like `parse_vec`, each accepting the number syntax like `parse_number`. Elements
+/// with a decimal comma have to be quoted, as in `"1,5", "2,5"`.
+pub fn parse_vec_number<T: FromStr>(key: &str, value: &str, syntax: NumberSyntax) -> Result<Vec<T>, Error> {
+    split_list(value).into_iter().map(|s| parse_number(key, &s, syntax).map_err(|e| Error::invalid_value(key, value, &s).with_help_of(e))).collect()
+}
+
+/// Parses the value into the intermediate type `V` of `#[prop(via = "...")]` and converts it with `TryFrom`, failing
+/// like an unparsable value if the conversion does.
+pub fn parse_via<V: FromStr, T: TryFrom<V>>(key: &str, value: &str) -> Result<T, Error> {
+    T::try_from(parse_value::<V>(key, value)?).map_err(|_| Error::invalid_value(key, value, value))
+}
+
+/// Parses the elements of the value like `parse_vec` into `V` and converts each with `TryFrom`.
+pub fn parse_vec_via<V: FromStr, T: TryFrom<V>>(key: &str, value: &str) -> Result<Vec<T>, Error> {
+    split_list(value).into_iter().map(|s| parse_via::<V, T>(key, &s).map_err(|_| Error::invalid_value(key, value, &s))).collect()
+}
+
+/// Converts the value with `From<String>`, for `#[prop(from_string)]` fields whose type doesn't implement `FromStr`.
+pub fn from_string<T: From<String>>(value: &str) -> T {
+    T::from(value.to_string())
+}
+
+/// Splits the value into elements like `parse_vec` and converts each with `From<String>`.
+pub fn from_string_vec<T: From<String>>(value: &str) -> Vec<T> {
+    split_list(value).into_iter().map(|s| T::from(s.into_owned())).collect()
+}
+
+/// Parses the comma separated value configured for `key` like `parse_vec` into an array, failing unless it has
+/// exactly `N` elements.
+pub fn parse_array<T: FromStr, const N: usize>(key: &str, value: &str) -> Result<[T; N], Error> {
+    let values = parse_vec::<T>(key, value)?;
+    let len = values.len();
+    values.try_into().map_err(|_| Error::wrong_len(key, len, N))
+}
+
+/// Joins `values` into a list that `parse_vec` splits back into the same elements, quoting the elements that need it.
+pub fn format_vec<T: fmt::Display>(values: impl IntoIterator<Item = T>) -> String {
+    let mut list = String::new();
+    for (i, value) in values.into_iter().enumerate() {
+        if i > 0 {
+            list.push(',');
+        }
+        let value = value.to_string();
+        match value.is_empty() || value.contains([',', '"']) || value.trim() != value {
+            true => list.push_str(&format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))),
+            false => list.push_str(&value),
+        }
+    }
+    list
+}
+
+/// Parses the inline map configured for `key`, like `env=prod;team=core` when `entry_sep` is `;` and `kv_sep` is `=`.
+///
+/// Whitespace around keys and values is trimmed and empty entries are skipped. An entry without `kv_sep` fails.
+pub fn parse_map<K: FromStr, V: FromStr, M: FromIterator<(K, V)>>(key: &str, value: &str, entry_sep: &str, kv_sep: &str) -> Result<M, Error> {
+    value
+        .split(entry_sep)
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (k, v) = entry.split_once(kv_sep).ok_or_else(|| Error::invalid_value(key, value, entry))?;
+            let k = k.trim().parse::<K>().map_err(|_| Error::invalid_value(key, value, entry))?;
+            let v = v.trim().parse::<V>().map_err(|_| Error::invalid_value(key, value, entry))?;
+            Ok((k, v))
+        })
+        .collect()
+}
+
+/// Writes `entries` as an inline map that `parse_map` reads back, sorted so the result doesn't depend on the iteration
+/// order of the map.
+pub fn format_map<K: fmt::Display, V: fmt::Display>(entries: impl IntoIterator<Item = (K, V)>, entry_sep: &str, kv_sep: &str) -> String {
+    let mut entries = entries.into_iter().map(|(k, v)| format!("{k}{kv_sep}{v}")).collect::<Vec<_>>();
+    entries.sort();
+    entries.join(entry_sep)
+}
+
+/// Parses the range configured for `key` into an inclusive range, failing if it starts after it ends.
+///
+/// The bounds are separated by `..=` or, if both are integers, by `-`, as in `9000-9100`. A `-` at the start of a
+/// bound is its sign, so `-10--5` is a range of negative numbers. Other bounds need `..=`, since the `-` of an
+/// exponent like `1e-5` can't be told apart from the separator.
+pub fn parse_range_inclusive<T: FromStr + PartialOrd>(key: &str, value: &str) -> Result<RangeInclusive<T>, Error> {
+    let bounds = value.split_once("..=").or_else(|| {
+        let i = value.get(1..)?.find('-')? + 1;
+        Some((&value[..i], &value[i + 1..])).filter(|(start, end)| is_integer(start) && is_integer(end))
+    });
+    let (start, end) = parse_bounds(key, value, bounds)?;
+    match start <= end {
+        true => Ok(start..=end),
+        false => Err(Error::reversed_range(key, value)),
+    }
+}
+
+/// Parses the range configured for `key` into a half open range, with the bounds separated by `..` as in `0..10`.
+/// The end may equal the start for an empty range. A `-` is not a separator here, since `9000-9100` reads as
+/// inclusive.
+pub fn parse_range<T: FromStr + PartialOrd>(key: &str, value: &str) -> Result<Range<T>, Error> {
+    let (start, end) = parse_bounds(key, value, value.split_once(".."))?;
+    match start <= end {
+        true => Ok(start..end),
+        false => Err(Error::reversed_range(key, value)),
+    }
+}
+
+/// Writes an inclusive range the way `parse_range_inclusive` reads it back: `9000-9100` for integers, `..=` between
+/// other bounds.
+pub fn format_range_inclusive<T: fmt::Display>(range: &RangeInclusive<T>) -> String {
+    let (start, end) = (range.start().to_string(), range.end().to_string());
+    match is_integer(&start) && is_integer(&end) {
+        true => format!("{start}-{end}"),
+        false => format!("{start}..={end}"),
+    }
+}
+
+fn parse_bounds<T: FromStr>(key: &str, value: &str, bounds: Option<(&str, &str)>) -> Result<(T, T), Error> {
+    let (start, end) = bounds.ok_or_else(|| Error::invalid_value(key, value, value))?;
+    let parse = |bound: &str| bound.trim().parse::<T>().map_err(|_| Error::invalid_value(key, value, bound.trim()).with_type_hint::<T>());
+    Ok((parse(start)?, parse(end)?))
+}
+
+/// Returns whether `bound` is an integer with an optional sign, ignoring the whitespace around it.
+fn is_integer(bound: &str) -> bool {
+    let bound = bound.trim();
+    let digits = bound.strip_prefix(['-', '+']).unwrap_or(bound);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Parses the `|` separated flags configured for `key` into a set, as in `features = ssl|gzip|http2`. Empty flags are
+/// skipped.
+pub fn parse_flag_set<T: FromStr, S: FromIterator<T>>(key: &str, value: &str) -> Result<S, Error> {
+    value
+        .split('|')
+        .map(str::trim)
+        .filter(|flag| !flag.is_empty())
+        .map(|flag| flag.parse::<T>().map_err(|_| Error::invalid_value(key, value, flag)))
+        .collect()
+}
+
+/// Writes `flags` as a sorted `|` separated list that `parse_flag_set` reads back.
+pub fn format_flag_set<T: fmt::Display>(flags: impl IntoIterator<Item = T>) -> String {
+    let mut flags = flags.into_iter().map(|flag| flag.to_string()).collect::<Vec<_>>();
+    flags.sort();
+    flags.join("|")
+}
+
+/// Parses the `|` separated flag names configured for `key` into a type generated by `bitflags!`. Names are matched
+/// regardless of their ASCII case, so `ssl|gzip` sets `SSL` and `GZIP`.
+#[cfg(feature = "bitflags")]
+pub fn parse_bitflags<T: bitflags::Flags>(key: &str, value: &str) -> Result<T, Error> {
+    let mut flags = T::empty();
+    for name in value.split('|').map(str::trim).filter(|name| !name.is_empty()) {
+        let flag = T::FLAGS.iter().find(|flag| flag.is_named() && flag.name().eq_ignore_ascii_case(name));
+        let flag = flag.ok_or_else(|| {
+            let names = T::FLAGS.iter().filter(|flag| flag.is_named()).map(|flag| flag.name()).collect::<Vec<_>>();
+            Error::invalid_value(key, value, name).with_help(&format!("expected `|` separated flags out of: {}", names.join(", ")))
+        })?;
+        flags.insert(T::from_bits_retain(flag.value().bits()));
+    }
+    Ok(flags)
+}
+
+/// Writes the names of the flags set in `flags`, separated by `|`, in the order they are declared.
+#[cfg(feature = "bitflags")]
+pub fn format_bitflags<T: bitflags::Flags>(flags: &T) -> String {
+    flags.iter_names().map(|(name, _)| name).collect::<Vec<_>>().join("|")
+}
+
+fn split_list(value: &str) -> Vec<Cow<'_, str>> {
+    let mut elements = Vec::new();
+    let mut rest = Some(value);
+    while let Some(list) = rest {
+        let (element, quoted, after) = crate::syntax::split_element(list);
+        match quoted {
+            true => elements.push(unescape(element)),
+            false if !element.is_empty() => elements.push(Cow::Borrowed(element)),
+            false => {}
+        }
+        rest = after;
+    }
+    elements
+}
+
+fn unescape(s: &str) -> Cow<'_, str> {
+    if !s.contains('\\') {
+        return Cow::Borrowed(s);
+    }
+
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(next @ ('"' | '\\'))) => {
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
+/// Fails if `values` parsed for `key` has fewer than `min_len` elements, as required by `#[prop(min_len = ...)]`.
+pub fn check_min_len<T>(key: &str, values: Vec<T>, min_len: usize) -> Result<Vec<T>, Error> {
+    match values.len() < min_len {
+        true => Err(Error::too_short(key, values.len(), min_len)),
+        false => Ok(values),
+    }
+}
+
+/// Removes repeated elements, keeping the first occurrence of each, as requested by `#[prop(dedup)]`.
+pub fn dedup<T: PartialEq>(values: Vec<T>) -> Vec<T> {
+    let mut unique = Vec::with_capacity(values.len());
+    for value in values {
+        if !unique.contains(&value) {
+            unique.push(value);
+        }
+    }
+    unique
+}
+
+/// Sorts the elements, as requested by `#[prop(sorted)]`. Equal elements keep their order.
+pub fn sorted<T: Ord>(mut values: Vec<T>) -> Vec<T> {
+    values.sort();
+    values
+}
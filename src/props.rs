@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::io::Result;
+use std::str::FromStr;
+
+use crate::KeyChange;
+use crate::parse;
+
+/// Untyped properties, for reading a few keys without defining a struct.
+///
+/// Values are parsed on access with the same rules as the derived constructors.
+///
+/// ```rust
+/// use props_util::Props;
+///
+/// let props = Props::from_str("server.port = 9090\nserver.hosts = a, b")?;
+/// let port: u16 = props.get("server.port")?;
+/// let hosts: Vec<String> = props.get_vec("server.hosts")?;
+/// assert_eq!(port, 9090);
+/// assert_eq!(hosts, ["a", "b"]);
+/// assert!(!props.get_or("debug", false)?);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Props {
+    map: HashMap<String, String>,
+}
+
+impl Props {
+    /// Reads the properties file at `path`.
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    pub fn from_file(path: &str) -> Result<Self> {
+        Ok(crate::runtime::read_properties(path)?.into())
+    }
+
+    /// Parses properties text, using the same format as `from_file`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(content: &str) -> Result<Self> {
+        Ok(parse::parse_properties(content)?.into())
+    }
+
+    /// Returns the raw value of `key`.
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.map.get(key).map(String::as_str)
+    }
+
+    /// Parses the value of `key`, failing if it is missing.
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<T> {
+        match self.get_str(key) {
+            Some(value) => Ok(parse::parse_value(key, value)?),
+            None => Err(parse::Error::missing_key(key).into()),
+        }
+    }
+
+    /// Parses the value of `key`, returning `default` if it is missing. A value that doesn't parse is still an error.
+    pub fn get_or<T: FromStr>(&self, key: &str, default: T) -> Result<T> {
+        Ok(self.get_opt(key)?.unwrap_or(default))
+    }
+
+    /// Parses the value of `key`, returning `None` if it is missing.
+    pub fn get_opt<T: FromStr>(&self, key: &str) -> Result<Option<T>> {
+        match self.get_str(key) {
+            Some(value) => Ok(Some(parse::parse_value(key, value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses the comma separated value of `key`, skipping empty elements. Fails if the key is missing.
+    pub fn get_vec<T: FromStr>(&self, key: &str) -> Result<Vec<T>> {
+        match self.get_str(key) {
+            Some(value) => Ok(parse::parse_vec(key, value)?),
+            None => Err(parse::Error::missing_key(key).into()),
+        }
+    }
+
+    /// Returns the keys starting with `prefix`, with the prefix stripped.
+    ///
+    /// This hands a component its own slice of a larger file: the result of `subset("db.")` can be passed to the
+    /// `from` of a type that expects `url` instead of `db.url`.
+    pub fn subset(&self, prefix: &str) -> Props {
+        let map = self.iter_prefix(prefix).map(|(key, value)| (key.to_string(), value.to_string())).collect::<HashMap<_, _>>();
+        map.into()
+    }
+
+    /// Iterates over all keys, in no particular order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.map.keys().map(String::as_str)
+    }
+
+    /// Iterates over all key value pairs, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.map.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Iterates over the pairs whose key starts with `prefix`, yielding keys with the prefix stripped.
+    ///
+    /// This enumerates dynamic keys such as feature flags, `feature.dark_mode = true` is yielded as `("dark_mode", "true")`.
+    pub fn iter_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.iter().filter_map(move |(key, value)| Some((key.strip_prefix(prefix)?, value)))
+    }
+
+    /// Returns the keys whose values differ between `self` and `other`, sorted by key, like the generated `diff`.
+    pub fn diff(&self, other: &Props) -> Vec<KeyChange> {
+        let values = |props: &Props| props.map.iter().map(|(key, value)| (key.clone(), value.clone())).collect();
+        crate::diff::diff(values(self), values(other))
+    }
+
+    /// Returns whether `key` has a value.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns whether there are no keys.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl From<HashMap<String, String>> for Props {
+    fn from(map: HashMap<String, String>) -> Self {
+        Self { map }
+    }
+}
+
+/// Allows passing `Props` to the generated `from` of any derived type.
+impl From<Props> for HashMap<String, String> {
+    fn from(props: Props) -> Self {
+        props.map
+    }
+}
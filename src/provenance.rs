@@ -0,0 +1,105 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Where a value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Origin {
+    /// The `default` of the field.
+    Default,
+    /// The line `line` of the properties file at `path`, counting from 1.
+    File { path: String, line: usize },
+    /// The line `line` of the `embed` file compiled into the binary.
+    Embedded { line: usize },
+    /// The environment variable with this name.
+    Env(String),
+    /// The command line argument with this name, such as `--server.port`.
+    Arg(String),
+    /// An override set through `props_util::testing`.
+    Override,
+}
+
+/// Formats as `default`, `config.properties:12`, `embedded:3`, `env SERVER_PORT`, `argument --server.port` or
+/// `test override`.
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::File { path, line } => write!(f, "{path}:{line}"),
+            Self::Embedded { line } => write!(f, "embedded:{line}"),
+            Self::Env(name) => write!(f, "env {name}"),
+            Self::Arg(name) => write!(f, "argument {name}"),
+            Self::Override => write!(f, "test override"),
+        }
+    }
+}
+
+/// The origin of every field of a load, as returned by the generated `from_file_with_provenance`,
+/// `from_files_with_provenance` and `standard_with_provenance`.
+///
+/// Fields are listed in declaration order. A field that has a value in several sources also keeps the origins it
+/// overrode, the ones with the highest precedence first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Provenance {
+    fields: Vec<(String, Vec<Origin>)>,
+}
+
+impl Provenance {
+    /// Returns where the value of `key` came from, `None` if the key is unknown or had no value, like an `Option` field
+    /// that wasn't set.
+    pub fn origin(&self, key: &str) -> Option<&Origin> {
+        self.origins(key).first()
+    }
+
+    /// Returns the origins that also had a value for `key` but were overridden by `origin`.
+    pub fn overridden(&self, key: &str) -> &[Origin] {
+        self.origins(key).get(1..).unwrap_or_default()
+    }
+
+    /// Iterates over the keys that had a value along with where it came from, in declaration order of the fields.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Origin)> {
+        self.fields.iter().filter_map(|(key, origins)| Some((key.as_str(), origins.first()?)))
+    }
+
+    fn origins(&self, key: &str) -> &[Origin] {
+        self.fields.iter().find(|(field, _)| field == key).map_or(&[], |(_, origins)| origins.as_slice())
+    }
+
+    #[doc(hidden)]
+    pub fn __push(&mut self, key: &str, origins: Vec<Origin>) {
+        self.fields.push((key.to_string(), origins));
+    }
+}
+
+/// Lists one `key: origin` line per key that had a value.
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (key, origin)) in self.iter().enumerate() {
+            match i {
+                0 => write!(f, "{key}: {origin}")?,
+                _ => write!(f, "\n{key}: {origin}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The origins of every key of merged properties, the last one being the value the merge kept.
+pub type Origins = HashMap<String, Vec<Origin>>;
+
+/// Describes every key of `provenance` that has a value in `values` on its own line, as in
+/// `server.port = 9090 (from env SERVER_PORT, overriding config.properties:12)`.
+pub fn explain(values: &BTreeMap<String, String>, provenance: &Provenance) -> String {
+    let mut lines = Vec::new();
+    for (key, origins) in &provenance.fields {
+        let (Some(value), Some(origin)) = (values.get(key), origins.first()) else {
+            continue;
+        };
+        let overridden = origins[1..].iter().map(ToString::to_string).collect::<Vec<_>>();
+        lines.push(match overridden.is_empty() {
+            true => format!("{key} = {value} (from {origin})"),
+            false => format!("{key} = {value} (from {origin}, overriding {})", overridden.join(", ")),
+        });
+    }
+    lines.join("\n")
+}
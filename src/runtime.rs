@@ -0,0 +1,39 @@
+//! The runtime the derive generates calls into, for reading properties without a derived type, and a [`Writer`] for
+//! producing them.
+//!
+//! Everything here is shared with the generated constructors, so a map read through this module follows exactly the
+//! same rules as the fields of a struct: comments, trimming, quoted list elements and inline maps.
+//!
+//! ```rust
+//! use props_util::runtime;
+//!
+//! let props = runtime::parse_properties("server.hosts = a, b\nlabels = env=prod;team=core")?;
+//! let hosts: Vec<String> = runtime::parse_vec("server.hosts", &props["server.hosts"])?;
+//! let labels: std::collections::BTreeMap<String, String> = runtime::parse_map("labels", &props["labels"], ";", "=")?;
+//! assert_eq!(hosts, ["a", "b"]);
+//! assert_eq!(labels["team"], "core");
+//! # Ok::<(), props_util::runtime::Error>(())
+//! ```
+
+#[cfg(feature = "std")]
+pub use crate::Props;
+#[cfg(feature = "std")]
+pub use crate::keys::resolve_profile;
+#[cfg(feature = "std")]
+pub use crate::parse::parse_properties;
+pub use crate::parse::{Error, ErrorCode, ErrorKind, Limits, format_map, format_vec, parse_line, parse_map, parse_str, parse_value, parse_vec};
+pub use crate::writer::Writer;
+
+/// Reads the properties file at `path` into an owned map, trimming the whitespace around values like `from_file`.
+#[cfg(all(feature = "std", not(all(target_family = "wasm", target_os = "unknown"))))]
+pub fn read_properties(path: &str) -> std::io::Result<std::collections::HashMap<String, String>> {
+    read_properties_limited(path, &Limits::new())
+}
+
+/// Reads the properties file at `path` like [`read_properties`], failing as soon as it goes over `limits`.
+#[cfg(all(feature = "std", not(all(target_family = "wasm", target_os = "unknown"))))]
+pub fn read_properties_limited(path: &str, limits: &Limits) -> std::io::Result<std::collections::HashMap<String, String>> {
+    let mut propmap = crate::files::read_file_limited(path, limits)?;
+    crate::files::trim_values(&mut propmap, &[]);
+    Ok(propmap)
+}
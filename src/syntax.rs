@@ -0,0 +1,205 @@
+//! The syntax of properties files: how lines split into keys and values, what counts as whitespace, where inline
+//! comments start, how lists split into elements and which spelling of a `case_insensitive` key wins.
+//!
+//! The derive crate compiles this file too, through `#[path]`, since `props_check!` reads files while expanding and a
+//! proc-macro can't depend on the runtime. Both sides follow the same rules that way. Everything here only uses `core`
+//! and is `const`, so the compile time checks of `props_util::check` can call it as well.
+
+/// Splits `line` at the first `=` into the trimmed key and the value exactly as it follows the `=`, only without the
+/// line ending. Returns `None` for empty lines and comments, which start with `#` or `!`, and the trimmed line as the
+/// error if it has no `=`.
+pub const fn split_line(line: &str) -> Result<Option<(&str, &str)>, &str> {
+    let line = match line.as_bytes() {
+        [.., b'\r', b'\n'] => line.split_at(line.len() - 2).0,
+        [.., b'\n' | b'\r'] => line.split_at(line.len() - 1).0,
+        _ => line,
+    };
+    let trimmed = trim(line);
+    if let [] | [b'#' | b'!', ..] = trimmed.as_bytes() {
+        return Ok(None);
+    }
+
+    let line = trim_start(line);
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            let (key, value) = line.split_at(i);
+            return Ok(Some((trim(key), value.split_at(1).1)));
+        }
+        i += 1;
+    }
+    Err(trimmed)
+}
+
+/// Trims whitespace from both ends like `str::trim`, which isn't `const`.
+pub const fn trim(value: &str) -> &str {
+    trim_end(trim_start(value))
+}
+
+/// Trims leading whitespace like `str::trim_start`.
+pub const fn trim_start(value: &str) -> &str {
+    let bytes = value.as_bytes();
+    let mut start = 0;
+    while start < bytes.len() {
+        let (c, len) = decode(bytes, start);
+        if !c.is_whitespace() {
+            break;
+        }
+        start += len;
+    }
+    value.split_at(start).1
+}
+
+/// Trims trailing whitespace like `str::trim_end`.
+pub const fn trim_end(value: &str) -> &str {
+    let bytes = value.as_bytes();
+    let mut end = bytes.len();
+    while end > 0 {
+        let mut start = end - 1;
+        while start > 0 && bytes[start] & 0xc0 == 0x80 {
+            start -= 1;
+        }
+        if !decode(bytes, start).0.is_whitespace() {
+            break;
+        }
+        end = start;
+    }
+    value.split_at(end).0
+}
+
+/// Returns where the inline comment of `value` starts for `#[prop(inline_comments)]`, its length if it has none, and
+/// whether the value before it has a `\#`, which stands for a literal `#`.
+///
+/// A `#` at the start of the value or after whitespace starts the comment, so `8080 # admin port` is `8080` while
+/// `https://host/#top` is kept whole.
+pub const fn comment_start(value: &str) -> (usize, bool) {
+    let bytes = value.as_bytes();
+    let mut escaped = false;
+    let mut prev_space = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'#' if prev_space => return (i, escaped),
+            b'\\' if i + 1 < bytes.len() && bytes[i + 1] == b'#' => escaped = true,
+            _ => {}
+        }
+        let (c, len) = decode(bytes, i);
+        prev_space = c.is_whitespace();
+        i += len;
+    }
+    (bytes.len(), escaped)
+}
+
+/// Splits the first element off a comma separated `list`, returning it, whether it was quoted and the rest of the
+/// list after its `,`, `None` if it was the last one.
+///
+/// A `"` at the start of an element quotes it up to the closing `"`, commas included, if only whitespace or a `,`
+/// follows. Inside the quotes `\"` stands for a quote and `\\` for a backslash. A quoted element is returned without
+/// its quotes and still escaped, an unquoted one trimmed and possibly empty.
+pub const fn split_element(list: &str) -> (&str, bool, Option<&str>) {
+    if let [b'"', ..] = trim_start(list).as_bytes()
+        && let Some((inner, after)) = split_quoted(trim_start(list).split_at(1).1)
+    {
+        match trim_start(after).as_bytes() {
+            [] => return (inner, true, None),
+            [b',', ..] => return (inner, true, Some(trim_start(after).split_at(1).1)),
+            // A quote only starts a quoted element at its beginning, `5" screen` is taken literally
+            _ => {}
+        }
+    }
+
+    let bytes = list.as_bytes();
+    let mut end = 0;
+    while end < bytes.len() && bytes[end] != b',' {
+        end += 1;
+    }
+    let (element, after) = list.split_at(end);
+    match after.as_bytes() {
+        [] => (trim(element), false, None),
+        _ => (trim(element), false, Some(after.split_at(1).1)),
+    }
+}
+
+/// Splits the text after an opening quote at the closing quote, skipping escaped quotes.
+const fn split_quoted(s: &str) -> Option<(&str, &str)> {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'"' => {
+                let (inner, after) = s.split_at(i);
+                return Some((inner, after.split_at(1).1));
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Returns whether `spelling` of the `case_insensitive` key `known` replaces the `previous` one: the exact spelling wins
+/// over differently cased ones, and among those the one that sorts first, so the result doesn't depend on the order of
+/// the keys.
+pub const fn spelling_wins(known: &str, previous: &str, spelling: &str) -> bool {
+    !bytes_eq(previous.as_bytes(), known.as_bytes()) && (bytes_eq(spelling.as_bytes(), known.as_bytes()) || bytes_lt(spelling.as_bytes(), previous.as_bytes()))
+}
+
+/// Decodes the character of the UTF-8 `bytes` starting at `i`, returning it along with its length.
+const fn decode(bytes: &[u8], i: usize) -> (char, usize) {
+    let len = utf8_len(bytes[i]);
+    let mut c = match len {
+        1 => bytes[i] as u32,
+        2 => (bytes[i] & 0x1f) as u32,
+        3 => (bytes[i] & 0x0f) as u32,
+        _ => (bytes[i] & 0x07) as u32,
+    };
+    let mut k = 1;
+    while k < len && i + k < bytes.len() {
+        c = (c << 6) | (bytes[i + k] & 0x3f) as u32;
+        k += 1;
+    }
+    match char::from_u32(c) {
+        Some(c) => (c, len),
+        None => (char::REPLACEMENT_CHARACTER, len),
+    }
+}
+
+/// Returns the length of the UTF-8 character starting with the byte `first`.
+pub const fn utf8_len(first: u8) -> usize {
+    match first {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        _ => 4,
+    }
+}
+
+/// Compares bytes like `==`, which isn't `const` for slices.
+pub const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Compares bytes lexicographically like `<[u8]>::lt`, which isn't `const`.
+pub const fn bytes_lt(a: &[u8], b: &[u8]) -> bool {
+    let mut i = 0;
+    while i < a.len() && i < b.len() {
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+        i += 1;
+    }
+    a.len() < b.len()
+}
@@ -0,0 +1,103 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+
+use crate::files::env_var;
+use crate::parse::Error;
+
+/// Copies `propmap`, rendering the `${...}` templates in the values of `keys` and the keys they reference for
+/// `#[prop(templates)]`. Other values are copied as they are, so a broken template that no field reads doesn't fail.
+///
+/// A template names another key, `${user.name}`, or an environment variable, `${env:HOME}`, followed by functions
+/// separated by `|`: `lower`, `upper` and `default(text)`, which stands in when the value is missing or empty. Referenced
+/// values are trimmed and rendered first, so templates can build on each other. `$${` is a literal `${`.
+pub fn render_templates<'a, K, V>(propmap: impl IntoIterator<Item = (&'a K, &'a V)>, keys: &[&str]) -> Result<HashMap<String, String>, Error>
+where
+    K: Borrow<str> + ?Sized + 'a,
+    V: AsRef<str> + ?Sized + 'a,
+{
+    let raw = propmap.into_iter().map(|(key, value)| (key.borrow(), value.as_ref())).collect::<HashMap<_, _>>();
+    let mut renderer = Renderer {
+        raw: &raw,
+        rendered: HashMap::new(),
+        stack: Vec::new(),
+    };
+    for key in keys {
+        if let Some((key, _)) = raw.get_key_value(key) {
+            renderer.render(key)?;
+        }
+    }
+    let mut rendered = renderer.rendered;
+    for (key, value) in &raw {
+        if !rendered.contains_key(*key) {
+            rendered.insert(key.to_string(), value.to_string());
+        }
+    }
+    Ok(rendered)
+}
+
+struct Renderer<'a> {
+    raw: &'a HashMap<&'a str, &'a str>,
+    rendered: HashMap<String, String>,
+    /// The keys being rendered, to report templates that reference themselves.
+    stack: Vec<&'a str>,
+}
+
+impl<'a> Renderer<'a> {
+    fn render(&mut self, key: &'a str) -> Result<&str, Error> {
+        if !self.rendered.contains_key(key) {
+            if let Some(pos) = self.stack.iter().position(|k| *k == key) {
+                let cycle = self.stack[pos..].iter().chain([&key]).map(|k| format!("`{k}`")).collect::<Vec<_>>();
+                return Err(Error::invalid_template(self.stack[pos], &format!("it references itself through {}", cycle.join(" -> "))));
+            }
+            self.stack.push(key);
+            let value = self.render_value(key, self.raw[key])?;
+            self.stack.pop();
+            self.rendered.insert(key.to_string(), value);
+        }
+        Ok(&self.rendered[key])
+    }
+
+    fn render_value(&mut self, key: &'a str, mut value: &'a str) -> Result<String, Error> {
+        let mut rendered = String::with_capacity(value.len());
+        while let Some(start) = value.find("${") {
+            if value[..start].ends_with('$') {
+                rendered.push_str(&value[..start - 1]);
+                rendered.push_str("${");
+                value = &value[start + 2..];
+                continue;
+            }
+            rendered.push_str(&value[..start]);
+            let Some(len) = value[start + 2..].find('}') else {
+                return Err(Error::invalid_template(key, "`${` has no closing `}`"));
+            };
+            rendered.push_str(&self.expand(key, &value[start + 2..start + 2 + len])?);
+            value = &value[start + 3 + len..];
+        }
+        rendered.push_str(value);
+        Ok(rendered)
+    }
+
+    /// Expands the template `expr`, the text between `${` and `}` in the value of `key`.
+    fn expand(&mut self, key: &'a str, expr: &'a str) -> Result<String, Error> {
+        let mut parts = expr.split('|').map(str::trim);
+        let source = parts.next().unwrap_or_default();
+        let (mut value, missing) = match source.strip_prefix("env:") {
+            Some(name) => (env_var(name.trim()), format!("the environment variable `{}`, which is not set", name.trim())),
+            None => match self.raw.get_key_value(source) {
+                Some((&source, _)) => (Some(self.render(source)?.trim().to_string()), String::new()),
+                None => (None, format!("`{source}`, which is not configured")),
+            },
+        };
+        for function in parts {
+            value = match function {
+                "lower" => value.map(|value| value.to_lowercase()),
+                "upper" => value.map(|value| value.to_uppercase()),
+                _ => match function.strip_prefix("default(").and_then(|function| function.strip_suffix(')')) {
+                    Some(default) => value.filter(|value| !value.is_empty()).or_else(|| Some(default.to_string())),
+                    None => return Err(Error::invalid_template(key, &format!("unknown function `{function}`, expected `lower`, `upper` or `default(...)`"))),
+                },
+            };
+        }
+        value.ok_or_else(|| Error::invalid_template(key, &format!("it references {missing}")))
+    }
+}
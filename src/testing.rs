@@ -0,0 +1,140 @@
+//! Scoped overrides of properties and environment variables for tests.
+//!
+//! Overrides are stored per thread, so tests running in parallel don't see each other's values, and each one is
+//! reverted when the returned [`OverrideGuard`] is dropped. Generated constructors consult them before anything else:
+//! a key override wins over env, files and defaults, and an env override replaces the real environment variable.
+//!
+//! ```rust
+//! use props_util::{Properties, testing};
+//!
+//! #[derive(Properties)]
+//! struct Config {
+//!     #[prop(key = "server.port", default = "8080")]
+//!     port: u16,
+//!     #[prop(env = "APP_NAME", default = "app")]
+//!     name: String,
+//! }
+//!
+//! {
+//!     let _port = testing::override_key("server.port", "9090");
+//!     let _name = testing::override_env("APP_NAME", "test-app");
+//!     let config = Config::default()?;
+//!     assert_eq!(config.port, 9090);
+//!     assert_eq!(config.name, "test-app");
+//! }
+//!
+//! assert_eq!(Config::default()?.port, 8080);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{Origin, Provenance};
+
+thread_local! {
+    static KEYS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+    static ENV: RefCell<HashMap<String, Option<String>>> = RefCell::new(HashMap::new());
+}
+
+enum Target {
+    Key(String, Option<String>),
+    Env(String, Option<Option<String>>),
+}
+
+/// Reverts an override when dropped, restoring whatever value was overridden before.
+#[must_use = "the override is reverted as soon as the guard is dropped"]
+pub struct OverrideGuard {
+    target: Option<Target>,
+    // Overrides are thread local, so the guard must be dropped on the thread that created it
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl OverrideGuard {
+    fn new(target: Target) -> Self {
+        Self {
+            target: Some(target),
+            _not_send: std::marker::PhantomData,
+        }
+    }
+}
+
+impl Drop for OverrideGuard {
+    fn drop(&mut self) {
+        match self.target.take() {
+            Some(Target::Key(key, previous)) => KEYS.with_borrow_mut(|keys| match previous {
+                Some(previous) => drop(keys.insert(key, previous)),
+                None => drop(keys.remove(&key)),
+            }),
+            Some(Target::Env(name, previous)) => ENV.with_borrow_mut(|env| match previous {
+                Some(previous) => drop(env.insert(name, previous)),
+                None => drop(env.remove(&name)),
+            }),
+            None => {}
+        }
+    }
+}
+
+/// Overrides the value of the property `key` on the current thread until the guard is dropped.
+pub fn override_key(key: &str, value: &str) -> OverrideGuard {
+    let previous = KEYS.with_borrow_mut(|keys| keys.insert(key.to_string(), value.to_string()));
+    OverrideGuard::new(Target::Key(key.to_string(), previous))
+}
+
+/// Makes the environment variable `name` appear set to `value` on the current thread until the guard is dropped.
+///
+/// The process environment is left untouched.
+pub fn override_env(name: &str, value: &str) -> OverrideGuard {
+    set_env(name, Some(value.to_string()))
+}
+
+/// Makes the environment variable `name` appear unset on the current thread until the guard is dropped.
+pub fn unset_env(name: &str) -> OverrideGuard {
+    set_env(name, None)
+}
+
+fn set_env(name: &str, value: Option<String>) -> OverrideGuard {
+    let previous = ENV.with_borrow_mut(|env| env.insert(name.to_string(), value));
+    OverrideGuard::new(Target::Env(name.to_string(), previous))
+}
+
+#[doc(hidden)]
+pub fn key_override(key: &str) -> Option<String> {
+    KEYS.with_borrow(|keys| keys.get(key).cloned())
+}
+
+/// Returns `Some` if `name` is overridden, holding `None` if the variable is overridden to be unset.
+pub(crate) fn env_override(name: &str) -> Option<Option<String>> {
+    ENV.with_borrow(|env| env.get(name).cloned())
+}
+
+/// Renders `values` for a snapshot assertion, as written by the generated `snapshot`: one `key = value` line per key in
+/// key order, followed by the origin of the value and the ones it overrode when `provenance` knows them.
+///
+/// Files are shown by their name only, so the output doesn't depend on the temporary directory a test wrote them to.
+#[doc(hidden)]
+pub fn snapshot(values: &BTreeMap<String, String>, provenance: &Provenance) -> String {
+    let stable = |origin: &Origin| match origin {
+        Origin::File { path, line } => {
+            let name = std::path::Path::new(path).file_name().map_or_else(|| path.clone(), |name| name.to_string_lossy().into_owned());
+            format!("{name}:{line}")
+        }
+        origin => origin.to_string(),
+    };
+
+    let mut lines = Vec::new();
+    for (key, value) in values {
+        let mut origins = provenance.origin(key).into_iter().chain(provenance.overridden(key)).map(stable);
+        lines.push(match origins.next() {
+            Some(origin) => {
+                let overridden = origins.collect::<Vec<_>>();
+                match overridden.is_empty() {
+                    true => format!("{key} = {value}  # {origin}"),
+                    false => format!("{key} = {value}  # {origin}, overriding {}", overridden.join(", ")),
+                }
+            }
+            None => format!("{key} = {value}"),
+        });
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,45 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::parse::Error;
+
+/// Every problem found by a generated `validate_file` or `apply_overrides`.
+///
+/// Validation resolves each field like the constructors do, but keeps going after a field fails, so one run reports
+/// all missing keys and unparseable values at once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    problems: Vec<Error>,
+}
+
+impl ValidationReport {
+    #[doc(hidden)]
+    pub fn push(&mut self, problem: Error) {
+        self.problems.push(problem);
+    }
+
+    /// Returns whether no problems were found.
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Returns the problems, in the order of the struct fields.
+    pub fn problems(&self) -> &[Error] {
+        &self.problems
+    }
+}
+
+/// Lists one problem per line.
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, problem) in self.problems.iter().enumerate() {
+            match i {
+                0 => write!(f, "{problem}")?,
+                _ => write!(f, "\n{problem}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for ValidationReport {}
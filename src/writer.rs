@@ -0,0 +1,118 @@
+use alloc::string::String;
+use core::fmt::Write;
+
+/// Writes key value pairs as `.properties` text with the escaping of Java's `Properties.store`, for files read by Java
+/// programs.
+///
+/// Spaces in keys, a leading space in values, `\`, `=`, `:`, `#`, `!` and control characters like tabs and line
+/// breaks are escaped with a backslash, so any key and value survives `Properties.load`. props-util itself reads
+/// values literally and doesn't undo these escapes.
+///
+/// ```rust
+/// use props_util::runtime::Writer;
+///
+/// let text = Writer::new().separator(" = ").header("Generated, do not edit").ascii(true).write([("greeting", "héllo: world")]);
+/// assert_eq!(text, "# Generated, do not edit\ngreeting = h\\u00E9llo\\: world\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Writer {
+    separator: String,
+    header: Option<String>,
+    ascii: bool,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Writer {
+    /// Creates a writer that separates keys and values with `=`, writes no header and keeps non-ASCII characters.
+    pub fn new() -> Self {
+        Self {
+            separator: "=".into(),
+            header: None,
+            ascii: false,
+        }
+    }
+
+    /// Separates keys and values with `separator`, such as `" = "` or `": "`.
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Writes `comment` as `#` comment lines before the entries, one per line of the comment.
+    pub fn header(mut self, comment: &str) -> Self {
+        self.header = Some(comment.into());
+        self
+    }
+
+    /// Escapes every character outside of printable ASCII as `\uXXXX`, which is what `Properties.store` does for files
+    /// that are read as ISO 8859-1.
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Writes `entries` in their order, one `key=value` line each.
+    pub fn write<K: AsRef<str>, V: AsRef<str>>(&self, entries: impl IntoIterator<Item = (K, V)>) -> String {
+        let mut text = String::new();
+        if let Some(header) = &self.header {
+            for line in header.lines() {
+                text.push_str("# ");
+                self.push_comment(&mut text, line);
+                text.push('\n');
+            }
+        }
+        for (key, value) in entries {
+            self.push_escaped(&mut text, key.as_ref(), true);
+            text.push_str(&self.separator);
+            self.push_escaped(&mut text, value.as_ref(), false);
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Writes `entries` like [`Writer::write`] to `out`.
+    #[cfg(feature = "std")]
+    pub fn write_to<K: AsRef<str>, V: AsRef<str>>(&self, mut out: impl std::io::Write, entries: impl IntoIterator<Item = (K, V)>) -> std::io::Result<()> {
+        out.write_all(self.write(entries).as_bytes())
+    }
+
+    fn push_comment(&self, text: &mut String, line: &str) {
+        for c in line.chars() {
+            match c {
+                c if self.ascii && !c.is_ascii() => push_unicode(text, c),
+                c => text.push(c),
+            }
+        }
+    }
+
+    fn push_escaped(&self, text: &mut String, s: &str, is_key: bool) {
+        for (i, c) in s.chars().enumerate() {
+            match c {
+                ' ' if is_key || i == 0 => text.push_str("\\ "),
+                '\\' | '=' | ':' | '#' | '!' => {
+                    text.push('\\');
+                    text.push(c);
+                }
+                '\t' => text.push_str("\\t"),
+                '\n' => text.push_str("\\n"),
+                '\r' => text.push_str("\\r"),
+                '\x0c' => text.push_str("\\f"),
+                c if c.is_control() || (self.ascii && !c.is_ascii()) => push_unicode(text, c),
+                c => text.push(c),
+            }
+        }
+    }
+}
+
+/// Writes `c` as `\uXXXX` escapes, using a surrogate pair outside of the basic multilingual plane like Java does.
+fn push_unicode(text: &mut String, c: char) {
+    let mut units = [0; 2];
+    for unit in c.encode_utf16(&mut units) {
+        let _ = write!(text, "\\u{unit:04X}");
+    }
+}
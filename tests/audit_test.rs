@@ -0,0 +1,52 @@
+use props_util::{AuditLog, KeyChange, Properties};
+
+#[derive(Properties)]
+struct Service {
+    #[prop(key = "db.password", secret)]
+    password: String,
+    #[prop(key = "server.port")]
+    port: u16,
+}
+
+#[test]
+fn audit_log_test() -> anyhow::Result<()> {
+    let temp_file = tempfile::NamedTempFile::new()?;
+    let log = AuditLog::new(2).with_file(temp_file.path());
+
+    let old = Service::from_str("db.password = hunter2\nserver.port = 80")?;
+    let new = Service::from_str("db.password = swordfish\nserver.port = 81")?;
+    assert_eq!(
+        old.redacted_diff(&new),
+        [
+            KeyChange {
+                key: "db.password".into(),
+                old: Some("***".into()),
+                new: Some("***".into())
+            },
+            KeyChange {
+                key: "server.port".into(),
+                old: Some("80".into()),
+                new: Some("81".into())
+            },
+        ]
+    );
+    log.record_reload("first.properties", old.diff(&old))?;
+    log.record_reload("second.properties", old.redacted_diff(&new))?;
+    log.record_failure("third.properties", &Service::from_str("server.port = 81").err().unwrap())?;
+
+    // The first reload was evicted from memory but is still in the file
+    let entries = log.entries();
+    assert_eq!(entries.iter().map(|entry| entry.source.as_str()).collect::<Vec<_>>(), ["second.properties", "third.properties"]);
+    assert!(entries[0].is_success());
+    assert!(!entries[1].is_success());
+    assert!(entries[1].changes.is_empty());
+
+    let lines = std::fs::read_to_string(temp_file.path())?;
+    let lines = lines.lines().map(|line| line.split_once(' ').unwrap().1).collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "first.properties reloaded without changes");
+    assert_eq!(lines[1], "second.properties reloaded: db.password: *** -> ***, server.port: 80 -> 81");
+    assert!(lines[2].starts_with("third.properties failed: "));
+    assert!(!lines.concat().contains("hunter2") && !lines.concat().contains("swordfish"));
+    Ok(())
+}
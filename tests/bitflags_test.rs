@@ -0,0 +1,34 @@
+#![cfg(feature = "bitflags")]
+
+use props_util::Properties;
+
+bitflags::bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Features: u8 {
+        const SSL = 1;
+        const GZIP = 1 << 1;
+        const HTTP2 = 1 << 2;
+    }
+}
+
+#[derive(Properties)]
+struct BitflagsTest {
+    #[prop(flags)]
+    features: Features,
+    #[prop(flags, default = "gzip")]
+    fallback: Option<Features>,
+}
+
+#[test]
+fn bitflags_test() -> anyhow::Result<()> {
+    let t = BitflagsTest::from_str("features = ssl|GZIP | http2")?;
+    assert_eq!(t.features, Features::all());
+    assert_eq!(t.fallback, Some(Features::GZIP));
+    assert_eq!(t.to_hash_map()["features"], "SSL|GZIP|HTTP2");
+    assert_eq!(BitflagsTest::from(&t)?.features, t.features);
+
+    assert_eq!(BitflagsTest::from_str("features =")?.features, Features::empty());
+    let e = BitflagsTest::from_str("features = ssl|brotli").err().unwrap();
+    assert_eq!(e.to_string(), "Error Parsing `features` with value `ssl|brotli` Error Parsing with value `brotli`");
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use props_util::Properties;
+
+#[derive(Properties)]
+struct Shipped {
+    #[prop(key = "server.port")]
+    port: u16,
+    #[prop(key = "server.host", default = "localhost")]
+    host: String,
+}
+
+#[test]
+fn build_validate_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("defaults.properties");
+    let path = path.to_str().unwrap();
+
+    std::fs::write(path, "server.port = 8080")?;
+    props_util::build::validate::<Shipped>(path);
+
+    std::fs::write(path, "server.port = eighty")?;
+    let e = props_util::build::try_validate::<Shipped>(path).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+    assert!(e.to_string().contains("doesn't match") && e.to_string().contains("`server.port`"));
+    assert!(std::panic::catch_unwind(|| props_util::build::validate::<Shipped>(path)).is_err());
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::sync::{Arc, Mutex};
+
+use props_util::{ChangeBus, Properties};
+
+#[derive(Properties)]
+struct Service {
+    #[prop(key = "db.url")]
+    db_url: String,
+    #[prop(key = "db.pool", default = "4")]
+    pool: u32,
+    #[prop(key = "server.port")]
+    port: u16,
+}
+
+#[test]
+fn change_bus_test() -> anyhow::Result<()> {
+    let bus = ChangeBus::new();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let record = |name: &'static str| {
+        let seen = seen.clone();
+        move |changes: &[props_util::KeyChange]| seen.lock().unwrap().push((name, changes.iter().map(|change| change.key.clone()).collect::<Vec<_>>()))
+    };
+    bus.subscribe("db.*", record("db"));
+    let server = bus.subscribe("server.port", record("server"));
+    bus.subscribe("*", record("all"));
+
+    let old = Service::from_str("db.url = a\nserver.port = 80")?;
+    bus.publish(&old.diff(&Service::from_str("db.url = b\ndb.pool = 8\nserver.port = 80")?));
+    bus.publish(&old.diff(&Service::from_str("db.url = a\nserver.port = 81")?));
+    bus.publish(&old.diff(&old));
+    assert_eq!(
+        seen.lock().unwrap().drain(..).collect::<Vec<_>>(),
+        [
+            ("db", vec!["db.pool".to_string(), "db.url".to_string()]),
+            ("all", vec!["db.pool".to_string(), "db.url".to_string()]),
+            ("server", vec!["server.port".to_string()]),
+            ("all", vec!["server.port".to_string()]),
+        ]
+    );
+
+    assert!(bus.unsubscribe(server));
+    assert!(!bus.unsubscribe(server));
+    bus.publish(&old.diff(&Service::from_str("db.url = a\nserver.port = 81")?));
+    assert_eq!(seen.lock().unwrap().as_slice(), [("all", vec!["server.port".to_string()])]);
+    Ok(())
+}
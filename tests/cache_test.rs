@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use props_util::{CachedLoader, Properties};
+
+#[derive(Properties)]
+struct Cached {
+    name: String,
+}
+
+#[test]
+fn cached_loader_test() -> anyhow::Result<()> {
+    let temp_file = tempfile::NamedTempFile::new()?;
+    std::fs::write(&temp_file, "name=first")?;
+
+    let loader = CachedLoader::<Cached>::new(temp_file.path());
+    let first = loader.load()?;
+    let again = loader.load()?;
+    assert_eq!(first.name, "first");
+    assert!(Arc::ptr_eq(&first, &again));
+
+    // Different length guarantees a change even on filesystems with coarse mtimes
+    std::fs::write(&temp_file, "name=changed")?;
+    let changed = loader.load()?;
+    assert_eq!(changed.name, "changed");
+    assert!(!Arc::ptr_eq(&first, &changed));
+
+    loader.invalidate();
+    assert!(!Arc::ptr_eq(&changed, &loader.load()?));
+    Ok(())
+}
+
+#[test]
+fn last_good_test() -> anyhow::Result<()> {
+    let temp_file = tempfile::NamedTempFile::new()?;
+    let loader = CachedLoader::<Cached>::new(temp_file.path());
+    std::fs::write(&temp_file, "no name")?;
+    assert!(loader.load_or_last_good().is_err());
+
+    std::fs::write(&temp_file, "name=good")?;
+    let good = loader.load_or_last_good()?;
+    assert!(loader.last_error().is_none());
+
+    std::fs::write(&temp_file, "name=broken\nnot a pair")?;
+    assert!(Arc::ptr_eq(&good, &loader.load_or_last_good()?));
+    assert!(loader.last_error().unwrap().to_string().contains("Malformed line 2"));
+    assert!(loader.load().is_err());
+
+    std::fs::write(&temp_file, "name=fixed")?;
+    assert_eq!(loader.load_or_last_good()?.name, "fixed");
+    assert!(loader.last_error().is_none());
+    Ok(())
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "cli")]
+
+use std::process::Command;
+
+use props_util::Properties;
+
+#[derive(Properties)]
+struct Service {
+    #[prop(key = "server.port")]
+    port: u16,
+    #[prop(key = "server.hosts", default = "localhost")]
+    hosts: Vec<String>,
+    #[prop(key = "tls.enabled")]
+    tls: Option<bool>,
+    #[prop(key = "color")]
+    color: Option<[u8; 3]>,
+}
+
+fn run(args: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_props-util")).args(args).output().unwrap();
+    (output.status.code(), String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[test]
+fn validate_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let schema = dir.path().join("schema.json");
+    std::fs::write(&schema, Service::schema_json())?;
+    let schema = schema.to_str().unwrap();
+    let file = dir.path().join("service.properties");
+    let file = file.to_str().unwrap();
+
+    std::fs::write(file, "server.port = 8080\ntls.enabled = true\ncolor = 1, 2, 3\nextra = 1")?;
+    assert_eq!(run(&["validate", "--schema", schema, file]), (Some(0), String::new()));
+
+    std::fs::write(file, "tls.enabled = yes\ncolor = 1, 2")?;
+    let (code, stdout) = run(&["validate", "--schema", schema, file]);
+    assert_eq!(code, Some(1));
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(stdout.contains("`server.port` value is not configured which is required"));
+    assert!(stdout.contains("`color` needs exactly 3 elements, found 2"));
+
+    assert_eq!(run(&["validate", "--schema", "missing.json", file]).0, Some(2));
+    Ok(())
+}
+
+#[derive(Properties)]
+struct Tuned {
+    #[prop(key = "port", radix_prefixes)]
+    port: u16,
+    #[prop(key = "admin.port", inline_comments)]
+    admin_port: u16,
+    #[prop(key = "cache.size", empty = "none", default = "8")]
+    cache_size: u32,
+    #[prop(key = "tag", empty = "error")]
+    tag: Option<String>,
+    #[prop(key = "raw", trim = false)]
+    raw: Option<u8>,
+    #[prop(key = "timeout", via = "u32")]
+    timeout: Option<std::num::NonZeroU32>,
+    #[prop(key = "workers", renamed_from = "threads")]
+    workers: u8,
+}
+
+#[test]
+fn validate_reading_options_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let schema = dir.path().join("schema.json");
+    std::fs::write(&schema, Tuned::schema_json())?;
+    let schema = schema.to_str().unwrap();
+    let file = dir.path().join("tuned.properties");
+    let file = file.to_str().unwrap();
+
+    // Files the application loads are valid
+    std::fs::write(file, "port = 0x50\nadmin.port = 80 # http\ncache.size=\nraw =7\ntimeout = 30\nthreads = 4")?;
+    let tuned = Tuned::from_file(file)?;
+    assert_eq!((tuned.port, tuned.admin_port, tuned.cache_size, tuned.raw, tuned.workers), (80, 80, 8, Some(7), 4));
+    assert_eq!(run(&["validate", "--schema", schema, file]), (Some(0), String::new()));
+
+    std::fs::write(file, "port = 0x50\nadmin.port = 80 # http\ntag =\nraw = 7\ntimeout = soon\nworkers = 4")?;
+    let (code, stdout) = run(&["validate", "--schema", schema, file]);
+    assert_eq!(code, Some(1));
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(stdout.contains("`tag` is configured with an empty value"));
+    assert!(stdout.contains("`raw`"));
+    assert!(stdout.contains("`timeout`"));
+    Ok(())
+}
+
+#[test]
+fn diff_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let [a, b] = ["a.properties", "b.properties"].map(|name| dir.path().join(name));
+    std::fs::write(&a, "server.port = 8080\nname = x")?;
+    std::fs::write(&b, "server.port = 9090\nname = x")?;
+    let [a, b] = [&a, &b].map(|path| path.to_str().unwrap());
+
+    assert_eq!(run(&["diff", a, a]), (Some(0), String::new()));
+    assert_eq!(run(&["diff", a, b]), (Some(1), "server.port: 8080 -> 9090\n".to_string()));
+    assert_eq!(run(&["diff", a]).0, Some(2));
+    Ok(())
+}
@@ -0,0 +1,21 @@
+#![cfg(feature = "config")]
+
+use props_util::PropertiesSource;
+
+#[test]
+fn config_source_test() -> anyhow::Result<()> {
+    let settings = config::Config::builder()
+        .add_source(PropertiesSource::file("examples/test.properties"))
+        .add_source(PropertiesSource::from_content("server.port = 9090\nname = override"))
+        .add_source(PropertiesSource::file("examples/missing.properties").required(false))
+        .build()?;
+
+    assert_eq!(settings.get::<String>("name")?, "override");
+    assert_eq!(settings.get::<u16>("server.port")?, 9090);
+    assert_eq!(settings.get::<Vec<u32>>("option_vec1").ok(), None);
+    assert_eq!(settings.get::<String>("option_vec1")?, "8, 9, 10");
+
+    let missing = config::Config::builder().add_source(PropertiesSource::file("examples/missing.properties")).build();
+    assert!(missing.is_err());
+    Ok(())
+}
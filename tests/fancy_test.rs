@@ -0,0 +1,44 @@
+#![cfg(feature = "fancy-errors")]
+
+use props_util::Properties;
+use props_util::fancy::{render, render_str};
+
+#[derive(Properties, Debug)]
+struct Fancy {
+    #[prop(key = "log.level", help = "expected one of: trace, debug, info")]
+    level: String,
+    #[prop(key = "ports", default = "80")]
+    ports: Vec<u16>,
+}
+
+#[test]
+fn render_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("app.properties");
+    let path = path.to_str().unwrap();
+
+    std::fs::write(path, "log.level = info\nports = 80\nports = 80, http, 443\n")?;
+    let rendered = render(&Fancy::from_file(path).unwrap_err(), path);
+    let expected = format!(
+        "error: Error Parsing `ports` with value `80, http, 443` Error Parsing with value `http`
+ --> {path}:3:13
+  |
+3 | ports = 80, http, 443
+  |             ^^^^
+  = help: expected a whole number from 0 to 65535
+"
+    );
+    assert_eq!(rendered, expected);
+
+    std::fs::write(path, "ports = 80\n")?;
+    let rendered = render(&Fancy::from_file(path).unwrap_err(), path);
+    assert_eq!(rendered, "error: `log.level` value is not configured which is required\n  = help: expected one of: trace, debug, info\n");
+
+    let content = "log.level = info\n\n\n\n\n\n\n\n\n  not a pair\n";
+    let rendered = render_str(&Fancy::from_str(content).unwrap_err(), "app.properties", content);
+    assert_eq!(rendered, "error: Malformed line 10 (missing '='): not a pair\n  --> app.properties:10:3\n   |\n10 |   not a pair\n   |   ^^^^^^^^^^\n");
+
+    let missing = Fancy::from_file("missing.properties").unwrap_err();
+    assert_eq!(render(&missing, "missing.properties"), format!("error: {missing}\n"));
+    Ok(())
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "global-replace")]
+
+use std::sync::Arc;
+
+use props_util::Properties;
+
+#[derive(Properties)]
+#[prop(global)]
+struct Replaceable {
+    name: String,
+}
+
+#[test]
+fn replace_global_test() -> anyhow::Result<()> {
+    assert!(Replaceable::replace_global(Replaceable { name: "first".into() }).is_none());
+    assert!(!Replaceable::try_init_global("examples/test.properties")?);
+
+    let first = Replaceable::global_arc();
+    assert!(std::ptr::eq(Replaceable::global(), &*first));
+    let previous = Replaceable::replace_global(Replaceable { name: "second".into() }).unwrap();
+    assert!(Arc::ptr_eq(&first, &previous));
+
+    // Earlier readers still see the old instance, and `global` keeps the one set at initialization
+    assert_eq!(first.name, "first".to_string());
+    assert_eq!(Replaceable::global().name, "first".to_string());
+    assert_eq!(Replaceable::global_arc().name, "second".to_string());
+
+    let second = Arc::downgrade(&Replaceable::global_arc());
+    let previous = Replaceable::replace_global(Replaceable { name: "third".into() }).unwrap();
+    assert_eq!(previous.name, "second".to_string());
+    assert_eq!(Replaceable::global_arc().name, "third".to_string());
+
+    // Replaced instances are freed once the last reader drops them
+    drop(previous);
+    assert!(second.upgrade().is_none());
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use props_util::Properties;
+use props_util::{Origin, Properties};
 
 #[derive(Properties)]
 struct A {
@@ -32,7 +32,7 @@ fn basic_parsing() -> anyhow::Result<()> {
 
 #[test]
 fn conversion_test() -> anyhow::Result<()> {
-    let b = B::from(A::default()?).unwrap();
+    let b = B::from(&A::default()?).unwrap();
     assert_eq!(b.name_string, "props-util".to_string());
     assert_eq!(b.option_vec1, Some(vec![1, 2, 3]));
     assert_eq!(b.option_vec2, Some(vec![4, 5, 6]));
@@ -63,7 +63,7 @@ fn file_test() -> anyhow::Result<()> {
     assert_eq!(a.option_vec1, Some(vec![8, 9, 10]));
     assert_eq!(a.option_vec2, Some(vec![8, 9, 10]));
 
-    let b = B::from(a)?;
+    let b = B::from(&a)?;
     assert_eq!(b.name_string, "test".to_string());
     assert_eq!(b.option_vec1, Some(vec![8, 9, 10]));
     assert_eq!(b.option_vec2, Some(vec![8, 9, 10]));
@@ -93,6 +93,40 @@ fn env_test() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[derive(Properties)]
+#[prop(env, env_prefix = "DERIVED_")]
+struct DerivedEnvTest {
+    #[prop(key = "server.host", default = "localhost")]
+    host: String,
+    #[prop(key = "max-connections", default = "1")]
+    max_connections: u32,
+    #[prop(env = "DERIVED_EXPLICIT", default = "a")]
+    explicit: String,
+}
+
+#[derive(Properties)]
+struct FieldEnvTest {
+    #[prop(key = "field.env.name", env, default = "default")]
+    name: String,
+}
+
+#[test]
+fn derived_env_test() -> anyhow::Result<()> {
+    assert_eq!(DerivedEnvTest::default()?.host, "localhost");
+
+    unsafe {
+        std::env::set_var("DERIVED_SERVER_HOST", "example.com");
+        std::env::set_var("DERIVED_MAX_CONNECTIONS", "8");
+        std::env::set_var("DERIVED_EXPLICIT", "b");
+        std::env::set_var("FIELD_ENV_NAME", "from-env");
+    }
+
+    let t = DerivedEnvTest::default()?;
+    assert_eq!((t.host.as_str(), t.max_connections, t.explicit.as_str()), ("example.com", 8, "b"));
+    assert_eq!(FieldEnvTest::default()?.name, "from-env");
+    Ok(())
+}
+
 #[derive(Properties, Debug)]
 struct EnvFailTest {
     #[prop(env = "NAME_FAIL")]
@@ -122,3 +156,1485 @@ fn env_fail_test() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn malformed_file_test() -> anyhow::Result<()> {
+    let temp_file = tempfile::NamedTempFile::new()?;
+    std::fs::write(&temp_file, "# comment\r\nname = crlf\r\n\r\nbroken line\r\n")?;
+
+    let err = A::from_file(temp_file.path().to_str().unwrap()).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("Malformed line 4"));
+
+    std::fs::write(&temp_file, "# comment\r\nname = crlf\r\n")?;
+    assert_eq!(A::from_file(temp_file.path().to_str().unwrap())?.name, "crlf");
+    Ok(())
+}
+
+#[test]
+fn into_hash_map_test() -> anyhow::Result<()> {
+    // Fields whose key matches the field name are present once, `option_vec1` and `option_vec3` are None
+    let hm = HashMap::from(&A::default()?);
+    assert_eq!(hm.len(), 2);
+    assert_eq!(hm.get("option_vec2"), Some(&"4,5,6".to_string()));
+
+    let hm = HashMap::from(&B::from(&A::default()?)?);
+    assert_eq!(hm.get("name"), Some(&"props-util".to_string()));
+    assert_eq!(hm.get("name_string"), Some(&"props-util".to_string()));
+
+    #[allow(deprecated)]
+    let owned = A::default()?.into_hash_map();
+    assert_eq!(owned, HashMap::from(&A::default()?));
+    Ok(())
+}
+
+#[test]
+fn to_hash_map_test() -> anyhow::Result<()> {
+    let a = A::from_file("examples/test.properties")?;
+    let hm = a.to_hash_map();
+    assert_eq!(hm.get("option_vec1"), Some(&"8,9,10".to_string()));
+
+    // Converting through a reference leaves `a` usable
+    let b = B::from(&a)?;
+    assert_eq!(b.name_string, a.name);
+    assert_eq!(b.option_vec2, a.option_vec2);
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(lazy)]
+struct LazyTest {
+    name: String,
+    #[prop(default = "not-a-number")]
+    port: u16,
+    option_vec1: Option<Vec<u32>>,
+}
+
+#[test]
+fn lazy_test() -> anyhow::Result<()> {
+    // `port` never parses, but that only matters once it is accessed
+    let lazy = LazyTestLazy::from_file("examples/test.properties")?;
+    assert_eq!(lazy.name()?, "test");
+    assert_eq!(lazy.option_vec1()?, &Some(vec![8, 9, 10]));
+    assert!(lazy.port().is_err());
+    assert!(LazyTest::from_file("examples/test.properties").is_err());
+    Ok(())
+}
+
+#[test]
+fn from_glob_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    for (name, content) in [("20-b.properties", "name=b\noption_vec3=x"), ("10-a.properties", "name=a\noption_vec1=1"), ("30-c.txt", "name=c"), ("00.properties", "name=0")] {
+        std::fs::write(dir.path().join(name), content)?;
+    }
+    let dir = dir.path().to_str().unwrap();
+
+    let a = A::from_glob(&format!("{dir}/*-?.properties"))?;
+    assert_eq!(a.name, "b");
+    assert_eq!(a.option_vec1, Some(vec![1]));
+
+    assert_eq!(A::from_glob(&format!("{dir}/*.properties"))?.name, "b");
+    assert_eq!(A::from_glob(&format!("{dir}/0*"))?.name, "0");
+    assert_eq!(A::from_glob(&format!("{dir}/*.missing"))?.name, "props-util");
+    assert!(A::from_glob(&format!("{dir}/*/a.properties")).is_err());
+    Ok(())
+}
+
+#[test]
+fn from_dir_merged_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    std::fs::write(dir.path().join("10-base.properties"), "name=base\noption_vec1=1")?;
+    std::fs::write(dir.path().join("20-override.properties"), "name=override")?;
+    std::fs::write(dir.path().join("README"), "not = properties")?;
+    let path = dir.path().to_str().unwrap();
+
+    let a = A::from_dir_merged(path)?;
+    assert_eq!(a.name, "override");
+    assert_eq!(a.option_vec1, Some(vec![1]));
+
+    std::fs::write(dir.path().join("30-broken.properties"), "broken line")?;
+    let err = A::from_dir_merged(path).err().unwrap();
+    assert!(err.to_string().contains("30-broken.properties"), "{err}");
+    assert!(A::from_dir_merged(dir.path().join("missing").to_str().unwrap()).is_err());
+    Ok(())
+}
+
+#[test]
+fn from_file_with_local_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let base = dir.path().join("config.properties");
+    std::fs::write(&base, "name=base\noption_vec3=s")?;
+    let base = base.to_str().unwrap();
+
+    let a = A::from_file_with_local(base)?;
+    assert_eq!(a.name, "base");
+
+    std::fs::write(dir.path().join("config.local.properties"), "name=local")?;
+    let a = A::from_file_with_local(base)?;
+    assert_eq!(a.name, "local");
+    assert_eq!(a.option_vec3, Some(vec!["s".to_string()]));
+
+    assert!(A::from_file_with_local(dir.path().join("missing.properties").to_str().unwrap()).is_err());
+    Ok(())
+}
+
+#[test]
+fn from_files_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let paths = (0..8)
+        .map(|i| {
+            let path = dir.path().join(format!("{i}.properties"));
+            std::fs::write(&path, format!("name=file{i}\noption_vec3=s{i}")).map(|_| path.to_str().unwrap().to_string())
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    let mut paths = paths.iter().map(String::as_str).collect::<Vec<_>>();
+    paths.insert(0, "examples/test.properties");
+
+    // Later files win, keys only present in earlier files survive the merge
+    let a = A::from_files(&paths)?;
+    assert_eq!(a.name, "file7");
+    assert_eq!(a.option_vec1, Some(vec![8, 9, 10]));
+    assert_eq!(a.option_vec3, Some(vec!["s7".to_string()]));
+
+    paths.push("examples/missing.properties");
+    assert!(A::from_files(&paths).is_err());
+    Ok(())
+}
+
+// Does not implement `Display`, which would be required to convert into a map
+#[derive(Debug, PartialEq)]
+struct Level(u8);
+
+impl std::str::FromStr for Level {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Level)
+    }
+}
+
+#[derive(Properties)]
+#[prop(no_hashmap)]
+struct NoHashMapTest {
+    #[prop(default = "3")]
+    level: Level,
+}
+
+#[test]
+fn no_hashmap_test() -> anyhow::Result<()> {
+    assert_eq!(NoHashMapTest::default()?.level, Level(3));
+    Ok(())
+}
+
+#[test]
+fn from_str_test() -> anyhow::Result<()> {
+    let b = B::from_str("# inline\nname = from-str\noption_vec3 = x, y")?;
+    assert_eq!(b.name_string, "from-str".to_string());
+    assert_eq!(b.option_vec1, Some(vec![1, 2, 3]));
+    assert_eq!(b.option_vec3, Some(vec!["x".into(), "y".into()]));
+
+    // The io::Error wraps the parse error, so the kind of failure stays available
+    let err = B::from_str("option_vec1 = 1, 2").err().unwrap();
+    let kind = err.get_ref().and_then(|e| e.downcast_ref::<props_util::parse::Error>()).map(|e| e.kind());
+    assert_eq!(kind, Some(props_util::parse::ErrorKind::MissingKey));
+    assert_eq!(err.to_string(), "`name` value is not configured which is required");
+
+    let err = B::from_str("name = x\noption_vec1 = 1, a").err().unwrap();
+    assert_eq!(err.to_string(), "Error Parsing `option_vec1` with value `1, a` Error Parsing with value `a`");
+    Ok(())
+}
+
+#[test]
+fn from_map_test() -> anyhow::Result<()> {
+    let mut hm = HashMap::<&str, &str>::new();
+    hm.insert("name", "borrowed");
+    hm.insert("option_vec1", "7");
+
+    let b = B::from_map(&hm)?;
+    assert_eq!(b.name_string, "borrowed".to_string());
+    assert_eq!(b.option_vec1, Some(vec![7]));
+    assert_eq!(hm.len(), 2);
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(embed = "examples/test.properties")]
+struct EmbedTest {
+    name: String,
+    option_vec2: Vec<u32>,
+}
+
+#[test]
+fn embed_test() -> anyhow::Result<()> {
+    let t = EmbedTest::from_embedded()?;
+    assert_eq!(t.name, "test".to_string());
+    assert_eq!(t.option_vec2, vec![8, 9, 10]);
+    Ok(())
+}
+
+#[derive(Properties)]
+struct CheckTest {
+    name: String,
+    option_vec1: Vec<u8>,
+    #[prop(default = "true")]
+    enabled: bool,
+    #[prop(env = "CHECK_TEST_PORT")]
+    port: i16,
+    missing: Option<f64>,
+}
+
+props_util::props_check!(CheckTest, "examples/test.properties");
+props_util::props_check!(A, "examples/test.properties");
+
+#[test]
+fn props_check_test() {
+    // `CheckTest::__props_check` is a `const fn`, so the same checks also run at runtime
+    CheckTest::__props_check(&[("name", "x"), ("option_vec1", "1, 255,"), ("port", "-7")]);
+    let result = std::panic::catch_unwind(|| CheckTest::__props_check(&[("name", "x"), ("option_vec1", "256")]));
+    assert!(result.is_err());
+    let result = std::panic::catch_unwind(|| CheckTest::__props_check(&[("option_vec1", "1")]));
+    assert!(result.is_err());
+}
+
+#[derive(Properties, Debug)]
+struct QuotedCheckTest {
+    ports: Vec<u32>,
+}
+
+props_util::props_check!(QuotedCheckTest, "examples/check.properties");
+
+#[derive(Properties, Debug)]
+#[prop(inline_comments)]
+struct CommentCheckTest {
+    #[prop(key = "admin.port")]
+    port: u16,
+    #[prop(key = "admin.tag")]
+    tag: char,
+}
+
+props_util::props_check!(CommentCheckTest, "examples/check.properties");
+
+#[derive(Properties, Debug)]
+#[prop(case_insensitive)]
+struct CaseCheckTest {
+    #[prop(key = "server.port")]
+    port: u16,
+}
+
+props_util::props_check!(CaseCheckTest, "examples/check.properties");
+
+#[derive(Properties, Debug)]
+struct EmptyCheckTest {
+    #[prop(key = "cache.port", empty = "none", default = "80")]
+    port: u16,
+    #[prop(key = "cache.port", empty = "none")]
+    optional_port: Option<u16>,
+    #[prop(key = "cache.size", empty = "error", default = "16")]
+    size: u32,
+}
+
+props_util::props_check!(EmptyCheckTest, "examples/check.properties");
+
+#[derive(Properties, Debug)]
+struct UntrimmedCheckTest {
+    #[prop(key = "raw.port", trim = false)]
+    port: u16,
+    #[prop(key = "base.port")]
+    base_port: u16,
+}
+
+props_util::props_check!(UntrimmedCheckTest, "examples/check.properties");
+
+#[test]
+fn props_check_quoted_test() -> anyhow::Result<()> {
+    assert_eq!(QuotedCheckTest::from_file("examples/check.properties")?.ports, [1, 2, 3]);
+    QuotedCheckTest::__props_check(&[("ports", r#""1" , "2",, 3"#)]);
+    // A quoted comma is part of the element, like at runtime
+    let result = std::panic::catch_unwind(|| QuotedCheckTest::__props_check(&[("ports", r#""1,2", 3"#)]));
+    assert!(result.is_err());
+    assert!(QuotedCheckTest::from_str(r#"ports = "1,2", 3"#).is_err());
+    Ok(())
+}
+
+#[test]
+fn props_check_case_insensitive_test() -> anyhow::Result<()> {
+    assert_eq!(CaseCheckTest::from_file("examples/check.properties")?.port, 8081);
+    CaseCheckTest::__props_check(&[("server.port", "80"), ("SERVER.PORT", "x")]);
+    let result = std::panic::catch_unwind(|| CaseCheckTest::__props_check(&[("Server.Port", "80"), ("SERVER.PORT", "x")]));
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn props_check_empty_test() -> anyhow::Result<()> {
+    let config = EmptyCheckTest::from_file("examples/check.properties")?;
+    assert_eq!((config.port, config.optional_port, config.size), (80, None, 16));
+    let result = std::panic::catch_unwind(|| EmptyCheckTest::__props_check(&[("cache.size", "")]));
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn props_check_inline_comments_test() -> anyhow::Result<()> {
+    let config = CommentCheckTest::from_file("examples/check.properties")?;
+    assert_eq!((config.port, config.tag), (8080, '#'));
+    let result = std::panic::catch_unwind(|| CommentCheckTest::__props_check(&[("admin.port", "80#80 # admin"), ("admin.tag", "x")]));
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn props_check_untrimmed_test() -> anyhow::Result<()> {
+    assert_eq!(UntrimmedCheckTest::from_file("examples/check.properties")?.port, 8082);
+    // Loading fails on the space, so the check has to as well
+    assert!(UntrimmedCheckTest::from_str("raw.port = 8082").is_err());
+    // The check gets the values as written and trims them like the fields do
+    UntrimmedCheckTest::__props_check(&[("raw.port", "8082"), ("base.port", " 80 ")]);
+    let result = std::panic::catch_unwind(|| UntrimmedCheckTest::__props_check(&[("raw.port", " 8082"), ("base.port", "80")]));
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn props_macro_test() -> anyhow::Result<()> {
+    let b = props_util::props!(B { "name" => "inline", "option_vec1" => "7, 8" })?;
+    assert_eq!(b.name_string, "inline".to_string());
+    assert_eq!(b.option_vec1, Some(vec![7, 8]));
+    assert_eq!(b.option_vec2, Some(vec![1, 2, 3]));
+
+    let a = props_util::props!(A {})?;
+    assert_eq!(a.name, "props-util".to_string());
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(global)]
+struct GlobalTest {
+    name: String,
+}
+
+#[test]
+fn global_test() -> anyhow::Result<()> {
+    assert!(std::panic::catch_unwind(GlobalTest::global).is_err());
+
+    assert!(GlobalTest::try_init_global("examples/test.properties")?);
+    assert!(!GlobalTest::try_init_global("examples/missing.properties")?);
+    let global = GlobalTest::global();
+    assert_eq!(global.name, "test".to_string());
+    assert!(std::ptr::eq(global, GlobalTest::global()));
+
+    let err = GlobalTest::set_global(GlobalTest { name: "other".into() }).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(GlobalTest::global().name, "test".to_string());
+    Ok(())
+}
+
+#[test]
+fn from_file_or_default_test() -> anyhow::Result<()> {
+    assert_eq!(A::from_file_or_default("examples/missing.properties")?.name, "props-util".to_string());
+    assert_eq!(A::from_file_or_default("examples/test.properties")?.name, "test".to_string());
+
+    let temp_file = tempfile::NamedTempFile::new()?;
+    std::fs::write(&temp_file, "broken line")?;
+    assert!(A::from_file_or_default(temp_file.path().to_str().unwrap()).is_err());
+    Ok(())
+}
+
+#[test]
+fn from_first_of_test() -> anyhow::Result<()> {
+    let (a, path) = A::from_first_of(&["examples/missing.properties", "examples/test.properties", "Cargo.toml"])?;
+    assert_eq!(path, "examples/test.properties");
+    assert_eq!(a.name, "test".to_string());
+
+    let err = A::from_first_of(&["examples/missing.properties", "~/missing.properties"]).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    // Malformed files are not skipped
+    assert!(A::from_first_of(&["Cargo.toml", "examples/test.properties"]).is_err());
+    Ok(())
+}
+
+#[test]
+fn from_pairs_test() -> anyhow::Result<()> {
+    let b = B::from_pairs([("name", "first"), ("option_vec3", "a"), ("name", "second")])?;
+    assert_eq!(b.name_string, "second".to_string());
+    assert_eq!(b.option_vec3, Some(vec!["a".to_string()]));
+
+    let b = B::from_pairs(vec![("name".to_string(), String::from("owned"))])?;
+    assert_eq!(b.name_string, "owned".to_string());
+    assert!(B::from_pairs(Vec::<(&str, &str)>::new()).is_err());
+    Ok(())
+}
+
+#[derive(Properties)]
+struct RestTest {
+    name: String,
+    #[prop(key = "option_vec1")]
+    numbers: Vec<u32>,
+    #[prop(rest)]
+    other: HashMap<String, String>,
+}
+
+#[test]
+fn rest_test() -> anyhow::Result<()> {
+    let t = RestTest::from_file("examples/test.properties")?;
+    assert_eq!(t.numbers, vec![8, 9, 10]);
+    assert_eq!(t.other, HashMap::from([("option_vec2".to_string(), "8,             9,              10".to_string())]));
+
+    let t = RestTest::from_str("name = x\noption_vec1 = 1\nvendor.a = 1\nvendor.b = 2")?;
+    assert_eq!(t.other.len(), 2);
+    assert_eq!(t.other.get("vendor.b"), Some(&"2".to_string()));
+
+    // Leftover keys are passed through verbatim when converting back into a map
+    let hm = t.to_hash_map();
+    assert_eq!(hm.get("vendor.a"), Some(&"1".to_string()));
+    assert!(!hm.contains_key("other"));
+
+    assert!(RestTest::default().is_err());
+    Ok(())
+}
+
+#[test]
+fn required_keys_test() {
+    assert_eq!(B::required_keys(), ["name"]);
+    assert_eq!(B::optional_keys(), ["option_vec1", "option_vec2", "option_vec3"]);
+    assert_eq!(EnvFailTest::required_keys(), [] as [&str; 0]);
+    assert_eq!(RestTest::required_keys(), ["name", "option_vec1"]);
+    assert!(RestTest::optional_keys().is_empty());
+}
+
+#[test]
+fn validate_file_test() -> anyhow::Result<()> {
+    let temp_file = tempfile::NamedTempFile::new()?;
+    std::fs::write(&temp_file, "option_vec1 = 1, x\noption_vec2 = 2")?;
+
+    let report = B::validate_file(temp_file.path().to_str().unwrap())?;
+    assert!(!report.is_valid());
+    let kinds = report.problems().iter().map(|e| e.kind()).collect::<Vec<_>>();
+    assert_eq!(kinds, [props_util::parse::ErrorKind::MissingKey, props_util::parse::ErrorKind::InvalidValue]);
+    assert_eq!(report.to_string(), "`name` value is not configured which is required\nError Parsing `option_vec1` with value `1, x` Error Parsing with value `x`");
+
+    assert!(B::validate_file("examples/test.properties")?.is_valid());
+    assert!(B::validate_file("examples/missing.properties").is_err());
+    Ok(())
+}
+
+#[test]
+fn missing_keys_test() -> anyhow::Result<()> {
+    let temp_file = tempfile::NamedTempFile::new()?;
+    std::fs::write(&temp_file, "option_vec1 = 1")?;
+    assert_eq!(RestTest::missing_keys(temp_file.path().to_str().unwrap())?, ["name".to_string()]);
+    assert!(B::missing_keys("examples/test.properties")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn diff_test() -> anyhow::Result<()> {
+    let old = B::from_str("name = a\noption_vec3 = x")?;
+    let new = B::from_str("name = b\noption_vec1 = 1, 2, 3")?;
+
+    let changes = old.diff(&new);
+    let keys = changes.iter().map(|c| c.key.as_str()).collect::<Vec<_>>();
+    assert_eq!(keys, ["name", "option_vec3"]);
+    assert_eq!(changes[0].to_string(), "name: a -> b");
+    assert_eq!(
+        changes[1],
+        props_util::KeyChange {
+            key: "option_vec3".into(),
+            old: Some("x".into()),
+            new: None
+        }
+    );
+
+    assert!(new.diff(&new).is_empty());
+    Ok(())
+}
+
+#[test]
+fn fingerprint_test() -> anyhow::Result<()> {
+    let old = B::from_str("name = a\noption_vec1 = 1, 2")?;
+    let touched = B::from_str("# reformatted\noption_vec1=1,2\nname=a")?;
+    let new = B::from_str("name = a\noption_vec1 = 1, 3")?;
+
+    assert_eq!(old.fingerprint(), touched.fingerprint());
+    assert!(!old.has_changed(&touched));
+    assert_ne!(old.fingerprint(), new.fingerprint());
+    assert!(old.has_changed(&new));
+    // Fingerprints may be persisted, so the hash must not change between releases
+    assert_eq!(old.fingerprint(), 16604402307276632530);
+    Ok(())
+}
+
+#[test]
+fn apply_overrides_test() -> anyhow::Result<()> {
+    let mut b = B::from_str("name = a\noption_vec3 = x")?;
+    b.apply_overrides(&HashMap::from([("name", "b"), ("option_vec1", "7"), ("unknown", "1")]))?;
+    assert_eq!(b.name_string, "b".to_string());
+    assert_eq!(b.option_vec1, Some(vec![7]));
+    assert_eq!(b.option_vec3, Some(vec!["x".to_string()]));
+
+    // Nothing is assigned if any key fails
+    let report = b.apply_overrides(&HashMap::from([("name", "c"), ("option_vec1", "x"), ("option_vec2", "y")])).err().unwrap();
+    assert_eq!(report.problems().len(), 2);
+    assert_eq!(b.name_string, "b".to_string());
+    assert_eq!(b.option_vec1, Some(vec![7]));
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(patch)]
+struct PatchTest {
+    #[prop(key = "server.port", default = "8080")]
+    port: u16,
+    name: Option<String>,
+    hosts: Vec<String>,
+}
+
+#[test]
+fn patch_test() -> anyhow::Result<()> {
+    let mut t = PatchTest::from_str("name = base\nhosts = a, b")?;
+
+    let patch = PatchTestPatch::from_str("server.port = 9090")?;
+    assert_eq!(patch.port, Some(9090));
+    assert!(patch.name.is_none() && patch.hosts.is_none());
+    t.apply(patch);
+    assert_eq!(t.port, 9090);
+    assert_eq!(t.name, Some("base".to_string()));
+
+    t.apply(PatchTestPatch { name: Some(None), ..Default::default() });
+    assert_eq!(t.name, None);
+    assert_eq!(t.hosts, vec!["a".to_string(), "b".to_string()]);
+
+    assert!(PatchTestPatch::from_str("server.port = x").is_err());
+    Ok(())
+}
+
+#[derive(Properties)]
+struct RenamedTest {
+    #[prop(key = "server.bind", renamed_from = "server.host")]
+    bind: String,
+    #[prop(key = "server.port", renamed_from = "port", default = "80")]
+    port: u16,
+}
+
+#[test]
+fn renamed_from_test() -> anyhow::Result<()> {
+    let t = RenamedTest::from_str("server.host = old-host\nport = 8080")?;
+    assert_eq!(t.bind, "old-host".to_string());
+    assert_eq!(t.port, 8080);
+
+    // The new key wins when both are present
+    let t = RenamedTest::from_str("server.host = old-host\nserver.bind = new-host")?;
+    assert_eq!(t.bind, "new-host".to_string());
+    assert_eq!(t.port, 80);
+
+    let hm = HashMap::from([("server.host", "h"), ("server.port", "1"), ("port", "2")]);
+    assert_eq!(RenamedTest::renamed_keys_in(&hm), [("server.host", "server.bind")]);
+    RenamedTest::__props_check(&[("server.host", "h")]);
+    Ok(())
+}
+
+#[test]
+fn migration_report_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let base = dir.path().join("base.properties");
+    let local = dir.path().join("local.properties");
+    std::fs::write(&base, "server.host = base\nport = 1")?;
+    std::fs::write(&local, "server.host = local\nserver.port = 2")?;
+    let (base, local) = (base.to_str().unwrap(), local.to_str().unwrap());
+
+    let (t, report) = RenamedTest::from_file_with_report(base)?;
+    assert_eq!(t.port, 1);
+    assert_eq!(report.keys().len(), 2);
+    assert_eq!(report.keys()[0].to_string(), format!("'server.host' is deprecated, rename it to 'server.bind' (in '{base}')"));
+
+    let (t, report) = RenamedTest::from_files_with_report(&[base, local])?;
+    assert_eq!((t.bind.as_str(), t.port), ("local", 2));
+    let migrated = props_util::MigratedKey {
+        old_key: "server.host".into(),
+        new_key: "server.bind".into(),
+        source: Some(local.into()),
+    };
+    assert_eq!(report.keys(), [migrated]);
+
+    assert!(B::from_file_with_report("examples/test.properties")?.1.is_empty());
+    Ok(())
+}
+
+#[derive(Properties)]
+struct ProvenanceTest {
+    #[prop(key = "server.port", env = "PROVENANCE_TEST_PORT", default = "80")]
+    port: u16,
+    #[prop(key = "server.host", default = "localhost")]
+    host: String,
+    name: Option<String>,
+    #[prop(key = "db.password", secret, default = "changeme")]
+    password: String,
+}
+
+#[test]
+fn provenance_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let base = dir.path().join("base.properties");
+    let local = dir.path().join("local.properties");
+    std::fs::write(&base, "# base\nserver.port = 1\nname = base")?;
+    std::fs::write(&local, "name = local")?;
+    let (base, local) = (base.to_str().unwrap(), local.to_str().unwrap());
+
+    let (t, provenance) = ProvenanceTest::from_files_with_provenance(&[base, local])?;
+    assert_eq!((t.port, t.name.as_deref()), (1, Some("local")));
+    assert_eq!(provenance.origin("server.port"), Some(&Origin::File { path: base.into(), line: 2 }));
+    assert_eq!(provenance.overridden("server.port"), [Origin::Default]);
+    assert_eq!(provenance.origin("server.host"), Some(&Origin::Default));
+    assert_eq!(provenance.origin("name"), Some(&Origin::File { path: local.into(), line: 1 }));
+    assert_eq!(provenance.overridden("name"), [Origin::File { path: base.into(), line: 3 }]);
+    assert_eq!(provenance.to_string(), format!("server.port: {base}:2\nserver.host: default\nname: {local}:1\ndb.password: default"));
+
+    unsafe { std::env::set_var("PROVENANCE_TEST_PORT", "2") };
+    let (t, provenance) = ProvenanceTest::from_file_with_provenance(local)?;
+    assert_eq!(t.port, 2);
+    assert_eq!(provenance.origin("server.port").unwrap().to_string(), "env PROVENANCE_TEST_PORT");
+
+    let (t, provenance) = ProvenanceTest::from_files_with_provenance(&[base, local])?;
+    let explained = format!("server.port = 2 (from env PROVENANCE_TEST_PORT, overriding {base}:2, default)\nserver.host = localhost (from default)\nname = local (from {local}:1, overriding {base}:3)\ndb.password = *** (from default)");
+    assert_eq!(t.explain(&provenance), explained);
+
+    let dump = t.diagnostic_dump(&provenance);
+    assert!(dump.starts_with("{\n  \"type\": \"ProvenanceTest\",\n  \"fields\": [\n"));
+    assert!(dump.contains(&format!(
+        r#"{{"key": "server.port", "type": "u16", "required": false, "default": "80", "env": "PROVENANCE_TEST_PORT", "secret": false, "value": "2", "origin": "env PROVENANCE_TEST_PORT", "overridden": ["{base}:2", "default"]}}"#
+    )));
+    assert!(dump.contains(r#""key": "name", "type": "Option<String>", "required": false, "default": null"#));
+    assert!(dump.contains(r#""key": "db.password", "type": "String", "required": false, "default": "***", "env": null, "secret": true, "value": "***""#));
+    assert!(!dump.contains("changeme"));
+    assert!(ProvenanceTest::from_file_with_provenance(&format!("{base}.missing")).is_err());
+
+    let (_, provenance) = ProvenanceTest::from_files_with_provenance(&[])?;
+    assert_eq!(provenance.origin("name"), None);
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(try_from(A, B))]
+struct TryFromTest {
+    name: String,
+    #[prop(default = "1")]
+    option_vec1: Vec<u32>,
+}
+
+#[test]
+fn try_from_test() -> anyhow::Result<()> {
+    let a = A::from_file("examples/test.properties")?;
+    let t = TryFromTest::try_from(&a)?;
+    assert_eq!(t.name, a.name);
+    assert_eq!(t.option_vec1, vec![8, 9, 10]);
+
+    let b: TryFromTest = (&B::from_str("name = b")?).try_into()?;
+    assert_eq!(b.name, "b".to_string());
+    assert_eq!(b.option_vec1, vec![1, 2, 3]);
+
+    let t = TryFromTest::convert_from(&b)?;
+    assert_eq!(t.name, "b".to_string());
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(display)]
+struct DisplayTest {
+    #[prop(key = "server.port")]
+    port: u16,
+    #[prop(key = "db.password", secret)]
+    password: String,
+    hosts: Vec<String>,
+    token: Option<String>,
+}
+
+#[test]
+fn display_test() -> anyhow::Result<()> {
+    let t = DisplayTest::from_str("server.port = 8080\ndb.password = hunter2\nhosts = a, b")?;
+    assert_eq!(t.to_string(), "db.password=***\nhosts=a,b\nserver.port=8080\n");
+    assert_eq!(t.password, "hunter2");
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(debug)]
+struct DebugTest {
+    #[prop(key = "server.port")]
+    port: u16,
+    #[prop(key = "db.password", secret)]
+    password: String,
+    token: Option<String>,
+}
+
+#[test]
+fn debug_test() -> anyhow::Result<()> {
+    let t = DebugTest::from_str("server.port = 8080\ndb.password = hunter2")?;
+    assert_eq!(format!("{t:?}"), r#"DebugTest { port: 8080, password: ***, token: None }"#);
+    assert!(!format!("{t:#?}").contains("hunter2"));
+    Ok(())
+}
+
+#[derive(Properties, Debug)]
+#[prop(before_parse = "rename_legacy_keys", after_parse = "HookTest::post_init")]
+struct HookTest {
+    #[prop(key = "server.port")]
+    port: u16,
+    #[prop(key = "server.url", default = "")]
+    url: String,
+}
+
+fn rename_legacy_keys(propmap: &mut HashMap<String, String>) {
+    if let Some(port) = propmap.remove("port") {
+        propmap.entry("server.port".to_string()).or_insert(port);
+    }
+}
+
+impl HookTest {
+    fn post_init(&mut self) -> std::io::Result<()> {
+        if self.port == 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "port must not be 0"));
+        }
+        self.url = format!("http://localhost:{}", self.port);
+        Ok(())
+    }
+}
+
+#[test]
+fn parse_hooks_test() -> anyhow::Result<()> {
+    let t = HookTest::from_str("port = 8080")?;
+    assert_eq!((t.port, t.url.as_str()), (8080, "http://localhost:8080"));
+
+    let t = HookTest::from_map(&HashMap::from([("port", "1"), ("server.port", "2")]))?;
+    assert_eq!(t.port, 2);
+
+    let err = HookTest::from_str("port = 0").unwrap_err();
+    assert_eq!(err.to_string(), "port must not be 0");
+    Ok(())
+}
+
+#[derive(Properties)]
+struct MapWithTest {
+    #[prop(key = "server.host", map_with = "lowercase", default = "LOCALHOST")]
+    host: String,
+    #[prop(key = "server.url", map_with = "trim_slash")]
+    url: Option<String>,
+    #[prop(map_with = "sorted")]
+    ports: Vec<u16>,
+}
+
+fn lowercase(value: String) -> String {
+    value.to_lowercase()
+}
+
+fn trim_slash(value: String) -> String {
+    value.trim_end_matches('/').to_string()
+}
+
+fn sorted(mut value: Vec<u16>) -> Vec<u16> {
+    value.sort();
+    value
+}
+
+#[test]
+fn map_with_test() -> anyhow::Result<()> {
+    let t = MapWithTest::from_str("server.host = Example.COM\nserver.url = http://a/b//\nports = 9, 1, 5")?;
+    assert_eq!(t.host, "example.com");
+    assert_eq!(t.url.as_deref(), Some("http://a/b"));
+    assert_eq!(t.ports, [1, 5, 9]);
+
+    let mut t = MapWithTest::from_str("ports = 2")?;
+    assert_eq!((t.host.as_str(), t.url.as_deref()), ("localhost", None));
+    t.apply_overrides(&HashMap::from([("server.host", "OTHER")]))?;
+    assert_eq!(t.host, "other");
+    Ok(())
+}
+
+#[derive(Properties)]
+struct MinLenTest {
+    #[prop(min_len = 1)]
+    seed_nodes: Vec<String>,
+    #[prop(min_len = 2, default = "1, 2")]
+    ports: Option<Vec<u16>>,
+}
+
+#[test]
+fn min_len_test() -> anyhow::Result<()> {
+    let t = MinLenTest::from_str("seed_nodes = a")?;
+    assert_eq!((t.seed_nodes, t.ports), (vec!["a".to_string()], Some(vec![1, 2])));
+
+    let e = MinLenTest::from_str("seed_nodes = , ").err().unwrap();
+    assert_eq!(e.to_string(), "`seed_nodes` needs at least 1 element, found 0");
+    let e = MinLenTest::from_str("seed_nodes = a\nports = 1").err().unwrap();
+    assert_eq!(e.to_string(), "`ports` needs at least 2 elements, found 1");
+    assert_eq!(MinLenTest::from_str("seed_nodes =").err().unwrap().kind(), std::io::ErrorKind::InvalidData);
+    Ok(())
+}
+
+#[derive(Properties)]
+struct DedupTest {
+    #[prop(dedup, min_len = 2)]
+    allowlist: Vec<String>,
+    #[prop(dedup, default = "3, 1, 3, 2, 1")]
+    ports: Option<Vec<u16>>,
+}
+
+#[test]
+fn dedup_test() -> anyhow::Result<()> {
+    let t = DedupTest::from_str("allowlist = b, a, b, c, a")?;
+    assert_eq!(t.allowlist, ["b", "a", "c"]);
+    assert_eq!(t.ports, Some(vec![3, 1, 2]));
+
+    // Duplicates don't count towards `min_len`
+    assert!(DedupTest::from_str("allowlist = a, a").is_err());
+    Ok(())
+}
+
+#[derive(Properties)]
+struct SortedTest {
+    #[prop(sorted)]
+    features: Vec<String>,
+    #[prop(sorted, dedup, default = "3, 1, 3, 2")]
+    ports: Vec<u16>,
+}
+
+#[test]
+fn sorted_test() -> anyhow::Result<()> {
+    let a = SortedTest::from_str("features = search, billing, audit")?;
+    let b = SortedTest::from_str("features = audit, search, billing")?;
+    assert_eq!(a.features, ["audit", "billing", "search"]);
+    assert_eq!(a.ports, [1, 2, 3]);
+    assert!(!a.has_changed(&b));
+    Ok(())
+}
+
+#[derive(Properties)]
+struct QuotedListTest {
+    names: Vec<String>,
+}
+
+#[test]
+fn quoted_list_test() -> anyhow::Result<()> {
+    let t = QuotedListTest::from_str(r#"names = "Smith, John","Doe, Jane""#)?;
+    assert_eq!(t.names, ["Smith, John", "Doe, Jane"]);
+
+    let t = QuotedListTest::from_str(r#"names = "say \"hi\"" , plain, "", 5" screen, "a\\b""#)?;
+    assert_eq!(t.names, [r#"say "hi""#, "plain", "", r#"5" screen"#, r"a\b"]);
+
+    // Elements are quoted again when converting back, so the list survives a round trip
+    let copy = QuotedListTest::from(&t)?;
+    assert_eq!(copy.names, t.names);
+    Ok(())
+}
+
+#[derive(Properties)]
+struct InlineMapTest {
+    labels: HashMap<String, String>,
+    #[prop(entry_sep = ",", kv_sep = ":", default = "b:2, a:1")]
+    weights: std::collections::BTreeMap<String, u32>,
+    limits: Option<HashMap<String, u64>>,
+}
+
+#[test]
+fn inline_map_test() -> anyhow::Result<()> {
+    let t = InlineMapTest::from_str("labels = env=prod; team = core;")?;
+    assert_eq!(t.labels, HashMap::from([("env".into(), "prod".into()), ("team".into(), "core".into())]));
+    assert_eq!(t.weights, [("a".to_string(), 1), ("b".to_string(), 2)].into());
+    assert_eq!(t.limits, None);
+    assert_eq!(t.to_hash_map()["labels"], "env=prod;team=core");
+    assert_eq!(t.to_hash_map()["weights"], "a:1,b:2");
+    assert_eq!(InlineMapTest::from(&t)?.labels, t.labels);
+
+    let e = InlineMapTest::from_str("labels = env=prod\nlimits = conns=ten").err().unwrap();
+    assert_eq!(e.to_string(), "Error Parsing `limits` with value `conns=ten` Error Parsing with value `conns=ten`");
+    assert!(InlineMapTest::from_str("labels = env").is_err());
+    Ok(())
+}
+
+#[derive(Properties)]
+struct RangeTest {
+    port_range: std::ops::RangeInclusive<u16>,
+    #[prop(default = "-10..-5")]
+    offsets: std::ops::Range<i32>,
+    ids: Option<std::ops::RangeInclusive<u64>>,
+    scale: Option<std::ops::RangeInclusive<f64>>,
+}
+
+#[test]
+fn range_test() -> anyhow::Result<()> {
+    let t = RangeTest::from_str("port_range = 9000-9100\nids = 1 ..= 1")?;
+    assert_eq!((t.port_range, t.offsets, t.ids), (9000..=9100, -10..-5, Some(1..=1)));
+
+    let t = RangeTest::from_str("port_range = 9000 - 9000\noffsets = -3..-1")?;
+    assert_eq!((t.port_range.clone(), t.offsets.clone()), (9000..=9000, -3..-1));
+    assert_eq!(t.to_hash_map()["port_range"], "9000-9000");
+    assert_eq!(RangeTest::from(&t)?.offsets, -3..-1);
+
+    let e = RangeTest::from_str("port_range = 9100-9000").err().unwrap();
+    assert_eq!(e.to_string(), "`port_range` range `9100-9000` starts after it ends");
+    assert!(RangeTest::from_str("port_range = 9000").is_err());
+    assert!(RangeTest::from_str("port_range = 9000-x").is_err());
+
+    // A `-` only separates the bounds of an inclusive range of integers
+    assert!(RangeTest::from_str("port_range = 1-2\noffsets = -3--1").is_err());
+    assert!(RangeTest::from_str("port_range = 1-2\nscale = 1e-5-2").is_err());
+    let t = RangeTest::from_str("port_range = 1-2\nscale = 1e-5..=2")?;
+    assert_eq!(t.scale, Some(1e-5..=2.0));
+    assert_eq!(t.to_hash_map()["scale"], "0.00001..=2");
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Feature {
+    Ssl,
+    Gzip,
+}
+
+impl std::str::FromStr for Feature {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ssl" => Ok(Self::Ssl),
+            "gzip" => Ok(Self::Gzip),
+            _ => Err(format!("unknown feature {s}")),
+        }
+    }
+}
+
+#[derive(Properties)]
+#[prop(no_hashmap)]
+struct FlagSetTest {
+    #[prop(flags)]
+    features: std::collections::BTreeSet<Feature>,
+    #[prop(flags, default = "b|a")]
+    names: Option<std::collections::HashSet<String>>,
+}
+
+#[test]
+fn flag_set_test() -> anyhow::Result<()> {
+    let t = FlagSetTest::from_str("features = gzip | ssl|gzip")?;
+    assert_eq!(t.features, [Feature::Ssl, Feature::Gzip].into());
+    assert_eq!(t.names, Some(["a".to_string(), "b".to_string()].into()));
+    assert!(FlagSetTest::from_str("features = ssl|http3").is_err());
+    assert!(FlagSetTest::from_str("features =")?.features.is_empty());
+    assert_eq!(props_util::parse::format_flag_set(["b", "a"]), "a|b");
+    Ok(())
+}
+
+#[derive(Properties)]
+struct EmptyTest {
+    #[prop(empty = "none", default = "8080")]
+    port: u16,
+    #[prop(empty = "none")]
+    name: Option<String>,
+    #[prop(empty = "error", default = "x")]
+    token: String,
+    #[prop(empty = "as_is", default = "x")]
+    suffix: String,
+}
+
+#[test]
+fn empty_test() -> anyhow::Result<()> {
+    let t = EmptyTest::from_str("port =\nname =\nsuffix =")?;
+    assert_eq!((t.port, t.name, t.token.as_str(), t.suffix.as_str()), (8080, None, "x", ""));
+
+    let e = EmptyTest::from_str("token =").err().unwrap();
+    assert_eq!(e.to_string(), "`token` is configured with an empty value");
+
+    let mut t = EmptyTest::from_str("port = 1\nname = a")?;
+    assert!(t.apply_overrides(&HashMap::from([("token", "")])).is_err());
+    t.apply_overrides(&HashMap::from([("name", "")]))?;
+    assert_eq!((t.port, t.name), (1, None));
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(before_parse = "keep_keys")]
+struct TrimTest {
+    #[prop(trim = false)]
+    indent: String,
+    #[prop(trim = false, default = "> ")]
+    prompt: String,
+    name: String,
+}
+
+fn keep_keys(_: &mut HashMap<String, String>) {}
+
+#[test]
+fn trim_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("trim.properties");
+    std::fs::write(&path, "indent=    \r\nname =  padded  \n")?;
+
+    let t = TrimTest::from_file(path.to_str().unwrap())?;
+    assert_eq!((t.indent.as_str(), t.prompt.as_str(), t.name.as_str()), ("    ", "> ", "padded"));
+    let t = TrimTest::from_str("indent=\t-\nname = x ")?;
+    assert_eq!((t.indent.as_str(), t.name.as_str()), ("\t-", "x"));
+    assert_eq!(props_util::Props::from_file(path.to_str().unwrap())?.get_str("name"), Some("padded"));
+    Ok(())
+}
+
+#[derive(Properties, Debug)]
+#[prop(inline_comments)]
+struct InlineCommentTest {
+    port: u16,
+    color: String,
+    url: String,
+    hosts: Vec<String>,
+}
+
+#[test]
+fn inline_comment_test() -> anyhow::Result<()> {
+    let t = InlineCommentTest::from_str("port = 8080 # admin port\ncolor = \\#ff0000 # red\nurl = https://host/#top\nhosts = a, b # two hosts")?;
+    assert_eq!((t.port, t.color.as_str(), t.url.as_str()), (8080, "#ff0000", "https://host/#top"));
+    assert_eq!(t.hosts, ["a", "b"]);
+    assert!(InlineCommentTest::from_str("port = # unset\nurl = x\nhosts = a").is_err());
+    Ok(())
+}
+
+#[test]
+fn limits_test() -> anyhow::Result<()> {
+    use props_util::parse::{ErrorKind, Limits};
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("tenant.properties");
+    std::fs::write(&path, "port = 80\r\nname = é\r\n")?;
+    let path = path.to_str().unwrap();
+    let limit_kind = |e: std::io::Error| e.into_inner().and_then(|e| e.downcast::<props_util::parse::Error>().ok()).map(|e| e.kind());
+
+    let t = InlineCommentTest::from_file_limited(path, &Limits::new().max_line_len(9).max_keys(2).max_file_size(22));
+    assert!(t.is_err_and(|e| e.to_string().contains("not configured")));
+    for limits in [Limits::new().max_line_len(8), Limits::new().max_keys(1), Limits::new().max_file_size(21)] {
+        let e = InlineCommentTest::from_file_limited(path, &limits).unwrap_err();
+        assert_eq!(limit_kind(e), Some(ErrorKind::LimitExceeded));
+    }
+    let content = "port = 1\ncolor = red\nurl = x\nhosts = a";
+    let e = InlineCommentTest::from_str_limited(content, &Limits::new().max_keys(3)).unwrap_err();
+    assert_eq!(limit_kind(e), Some(ErrorKind::LimitExceeded));
+    assert!(InlineCommentTest::from_str_limited(content, &Limits::new().max_keys(4)).is_ok());
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+struct TenantId(String);
+
+impl From<String> for TenantId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Properties)]
+#[prop(no_hashmap)]
+struct FromStringTest {
+    #[prop(from_string)]
+    tenant: TenantId,
+    #[prop(from_string, default = "a, b")]
+    peers: Vec<TenantId>,
+    #[prop(from_string)]
+    parent: Option<TenantId>,
+}
+
+#[test]
+fn from_string_test() -> anyhow::Result<()> {
+    let t = FromStringTest::from_str("tenant = acme")?;
+    assert_eq!((t.tenant, t.parent), (TenantId("acme".into()), None));
+    assert_eq!(t.peers, [TenantId("a".into()), TenantId("b".into())]);
+    assert_eq!(FromStringTest::from_str("tenant = x\nparent = y")?.parent, Some(TenantId("y".into())));
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+enum Priority {
+    Low,
+    High,
+}
+
+impl TryFrom<u8> for Priority {
+    type Error = ();
+
+    fn try_from(level: u8) -> Result<Self, ()> {
+        match level {
+            0 => Ok(Self::Low),
+            1 => Ok(Self::High),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct UserId(u64);
+
+impl From<u64> for UserId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Properties)]
+#[prop(no_hashmap)]
+struct ViaTest {
+    #[prop(via = "u8")]
+    priority: Priority,
+    #[prop(via = "u64", default = "1, 2")]
+    admins: Vec<UserId>,
+    #[prop(via = "u64")]
+    owner: Option<UserId>,
+}
+
+#[test]
+fn via_test() -> anyhow::Result<()> {
+    let t = ViaTest::from_str("priority = 1\nowner = 7")?;
+    assert_eq!((t.priority, t.owner), (Priority::High, Some(UserId(7))));
+    assert_eq!(t.admins, [UserId(1), UserId(2)]);
+    assert!(ViaTest::from_str("priority = 2").is_err());
+    assert!(ViaTest::from_str("priority = 0\nadmins = 1, x").is_err());
+    Ok(())
+}
+
+#[derive(Properties)]
+struct ArrayTest {
+    color: [u8; 3],
+    #[prop(default = "a, b")]
+    split: [String; 2],
+    origin: Option<[i32; 2]>,
+}
+
+#[test]
+fn array_test() -> anyhow::Result<()> {
+    let t = ArrayTest::from_str("color = 255, 128, 0\norigin = -1, 1")?;
+    assert_eq!((t.color, &t.split, t.origin), ([255, 128, 0], &["a".to_string(), "b".to_string()], Some([-1, 1])));
+    assert_eq!(t.to_hash_map().get("color").map(String::as_str), Some("255,128,0"));
+
+    let e = ArrayTest::from_str("color = 1, 2").err().unwrap();
+    assert_eq!(e.to_string(), "`color` needs exactly 3 elements, found 2");
+    assert!(ArrayTest::from_str("color = 1, 2, 3, 4").is_err());
+    Ok(())
+}
+
+#[derive(Properties, Debug)]
+struct HelpTest {
+    #[prop(key = "log.level", help = "expected one of: trace, debug, info")]
+    level: String,
+    #[prop(key = "port", default = "8080")]
+    port: u16,
+    #[prop(key = "debug", default = "false")]
+    debug: Vec<bool>,
+}
+
+#[test]
+fn help_test() -> anyhow::Result<()> {
+    let error = |content: &str| *HelpTest::from_str(content).unwrap_err().into_inner().unwrap().downcast::<props_util::parse::Error>().unwrap();
+    let help = |content: &str| error(content).help().map(str::to_string);
+    assert_eq!(help("port = 1").as_deref(), Some("expected one of: trace, debug, info"));
+    assert_eq!(help("log.level = info\nport = 70000").as_deref(), Some("expected a whole number from 0 to 65535"));
+    assert_eq!(help("log.level = info\ndebug = true, yes").as_deref(), Some("expected `true` or `false`"));
+
+    let error = error("log.level = info\ndebug = true, yes");
+    assert_eq!((error.key(), error.value()), (Some("debug"), Some("yes")));
+    Ok(())
+}
+
+#[test]
+fn error_code_test() -> anyhow::Result<()> {
+    use props_util::parse::ErrorCode;
+
+    let code = |content: &str| ErrorCode::of(&MinLenTest::from_str(content).err().unwrap());
+    assert_eq!(code(""), ErrorCode::MissingKey);
+    assert_eq!(code("seed_nodes = a\nports = 1, x"), ErrorCode::ParseFailure);
+    assert_eq!(code("seed_nodes = a\nports"), ErrorCode::MalformedLine);
+    assert_eq!(code("seed_nodes = a\nports = 1"), ErrorCode::Validation);
+    assert_eq!(ErrorCode::of(&MinLenTest::from_file("examples/missing.properties").err().unwrap()), ErrorCode::Io);
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("latin1.properties");
+    std::fs::write(&path, b"seed_nodes = caf\xe9")?;
+    assert_eq!(ErrorCode::of(&MinLenTest::from_file(path.to_str().unwrap()).err().unwrap()), ErrorCode::MalformedLine);
+
+    let codes = [ErrorCode::MissingKey, ErrorCode::ParseFailure, ErrorCode::MalformedLine, ErrorCode::Io, ErrorCode::Validation, ErrorCode::LimitExceeded];
+    assert_eq!(codes.map(|code| code.to_string()), ["MISSING_KEY", "PARSE_FAILURE", "MALFORMED_LINE", "IO", "VALIDATION", "LIMIT_EXCEEDED"]);
+    assert_eq!(codes.map(ErrorCode::is_transient), [false, false, false, true, false, false]);
+    Ok(())
+}
+
+#[derive(Properties, Debug)]
+struct DecimalCommaTest {
+    #[prop(key = "ratio", decimal_comma)]
+    ratio: f64,
+    #[prop(key = "weights", decimal_comma, default = "\"0,5\"")]
+    weights: Vec<f32>,
+    #[prop(key = "total", default = "1.5")]
+    total: f64,
+}
+
+#[derive(Properties, Debug)]
+#[prop(decimal_comma)]
+struct DecimalCommaStructTest {
+    #[prop(key = "ratio")]
+    ratio: Option<f64>,
+    #[prop(key = "count", default = "3")]
+    count: u32,
+    #[prop(key = "name", default = "a,b")]
+    name: String,
+}
+
+#[test]
+fn decimal_comma_test() -> anyhow::Result<()> {
+    let t = DecimalCommaTest::from_str("ratio = 2,75\nweights = \"1,5\", 2.5, 3")?;
+    assert_eq!((t.ratio, t.weights, t.total), (2.75, vec![1.5, 2.5, 3.0], 1.5));
+    assert_eq!(DecimalCommaTest::from_str("ratio = 2.75")?.weights, [0.5]);
+    assert!(DecimalCommaTest::from_str("ratio = 1,000,5").is_err());
+    assert!(DecimalCommaTest::from_str("ratio = 1\ntotal = 1,5").is_err());
+
+    let t = DecimalCommaStructTest::from_str("ratio = 0,25")?;
+    assert_eq!((t.ratio, t.count, t.name.as_str()), (Some(0.25), 3, "a,b"));
+    assert!(DecimalCommaStructTest::from_str("count = 3,5").is_err());
+    Ok(())
+}
+
+#[derive(Properties, Debug)]
+struct UnderscoresTest {
+    #[prop(key = "max_bytes", underscores)]
+    max_bytes: u64,
+    #[prop(key = "limits", underscores, default = "1_000, 2_000")]
+    limits: Vec<i32>,
+    #[prop(key = "ratio", underscores, decimal_comma, default = "1_000,5")]
+    ratio: f64,
+    #[prop(key = "name", underscores, default = "snake_case")]
+    name: String,
+}
+
+#[test]
+fn underscores_test() -> anyhow::Result<()> {
+    let t = UnderscoresTest::from_str("max_bytes = 1_000_000")?;
+    assert_eq!((t.max_bytes, t.limits, t.ratio, t.name.as_str()), (1_000_000, vec![1000, 2000], 1000.5, "snake_case"));
+    assert_eq!(UnderscoresTest::from_str("max_bytes = 42")?.max_bytes, 42);
+    assert!(UnderscoresTest::from_str("max_bytes = _1000").is_err());
+    assert!(UnderscoresTest::from_str("max_bytes = 1_000\nlimits = 1_x").is_err());
+    Ok(())
+}
+
+#[derive(Properties, Debug)]
+#[prop(radix_prefixes)]
+struct RadixTest {
+    #[prop(key = "mode")]
+    mode: u32,
+    #[prop(key = "offset", default = "-0x10")]
+    offset: i16,
+    #[prop(key = "registers", underscores, default = "0x1F, 0b1010, 0xFF_FF, 7")]
+    registers: Vec<u32>,
+    #[prop(key = "mask", default = "0xff")]
+    mask: Option<u8>,
+}
+
+#[test]
+fn radix_prefixes_test() -> anyhow::Result<()> {
+    let t = RadixTest::from_str("mode = 0o755")?;
+    assert_eq!((t.mode, t.offset, t.registers, t.mask), (0o755, -16, vec![0x1f, 0b1010, 0xffff, 7], Some(0xff)));
+    assert_eq!(RadixTest::from_str("mode = 493")?.mode, 493);
+    assert!(RadixTest::from_str("mode = 0x").is_err());
+    assert!(RadixTest::from_str("mode = 0x-1").is_err());
+    assert!(RadixTest::from_str("mode = 0b102").is_err());
+    assert!(RadixTest::from_str("mode = 1\nmask = 0x100").is_err());
+    assert!(RadixTest::from_str("mode = -0x1").is_err());
+    Ok(())
+}
+
+#[derive(Properties, Debug)]
+struct TenantConfig {
+    #[prop(key = "db.url")]
+    db_url: String,
+    #[prop(key = "max_users", default = "100")]
+    max_users: u32,
+}
+
+#[test]
+fn namespaced_test() -> anyhow::Result<()> {
+    let temp_file = tempfile::NamedTempFile::new()?;
+    std::fs::write(
+        &temp_file,
+        "tenant.acme.db.url = postgres://acme\ntenant.globex.db.url = postgres://globex\ntenant.globex.max_users = 5\ntenant.default = acme\nserver.port = 80\n",
+    )?;
+
+    let tenants = TenantConfig::from_file_namespaced(temp_file.path().to_str().unwrap(), "tenant.")?;
+    let mut names = tenants.keys().map(String::as_str).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["acme", "globex"]);
+    assert_eq!(tenants["acme"].db_url, "postgres://acme");
+    assert_eq!(tenants["acme"].max_users, 100);
+    assert_eq!(tenants["globex"].max_users, 5);
+
+    // Errors name the key as it is written in the file
+    let error = TenantConfig::from_namespaced(props_util::parse_properties("tenant.acme.db.url = a\ntenant.initech.max_users = 5")?, "tenant.").unwrap_err();
+    let error = error.get_ref().and_then(|e| e.downcast_ref::<props_util::parse::Error>()).unwrap();
+    assert_eq!(error.key(), Some("tenant.initech.db.url"));
+    assert!(error.to_string().starts_with("`tenant.initech.db.url` value is not configured"));
+    Ok(())
+}
+
+#[derive(Properties, Debug)]
+struct ProfileConfig {
+    #[prop(key = "cache.size", default = "8")]
+    cache_size: u32,
+    #[prop(key = "log.level")]
+    log_level: String,
+}
+
+#[test]
+fn profile_test() -> anyhow::Result<()> {
+    let temp_file = tempfile::NamedTempFile::new()?;
+    std::fs::write(&temp_file, "cache.size@prod = 1024\ncache.size@dev = 16\nlog.level = info\nlog.level@dev = debug\n")?;
+    let path = temp_file.path().to_str().unwrap();
+
+    let prod = ProfileConfig::from_file_with_profile(path, "prod")?;
+    assert_eq!((prod.cache_size, prod.log_level.as_str()), (1024, "info"));
+    let dev = ProfileConfig::from_file_with_profile(path, "dev")?;
+    assert_eq!((dev.cache_size, dev.log_level.as_str()), (16, "debug"));
+    let test = ProfileConfig::from_file_with_profile(path, "test")?;
+    assert_eq!((test.cache_size, test.log_level.as_str()), (8, "info"));
+
+    // The qualified key wins wherever it is in the file
+    let props = props_util::parse_properties("log.level@prod = warn\nlog.level = info")?;
+    assert_eq!(ProfileConfig::from_with_profile(props, "prod")?.log_level, "warn");
+    Ok(())
+}
+
+#[derive(Properties, Debug)]
+struct LayeredConfig {
+    #[prop(key = "server.host")]
+    host: String,
+    #[prop(key = "server.port")]
+    port: u16,
+}
+
+#[test]
+fn layered_test() -> anyhow::Result<()> {
+    const DEFAULTS: &str = "server.host = localhost\nserver.port = 8080\n";
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("overrides.properties");
+    let path = path.to_str().unwrap();
+
+    // Without the file the defaults alone resolve every key
+    let config = LayeredConfig::layered(DEFAULTS, path)?;
+    assert_eq!((config.host.as_str(), config.port), ("localhost", 8080));
+
+    std::fs::write(path, "server.port = 9090\n")?;
+    let config = LayeredConfig::layered(DEFAULTS, path)?;
+    assert_eq!((config.host.as_str(), config.port), ("localhost", 9090));
+
+    std::fs::write(path, "server.port = 9090\nbroken line\n")?;
+    let err = LayeredConfig::layered(DEFAULTS, path).err().unwrap();
+    assert!(err.to_string().contains("Malformed line 2"));
+    let err = LayeredConfig::layered("server.host\n", path).err().unwrap();
+    assert!(err.to_string().contains("Malformed line 1 in '<embedded>'"));
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(case_insensitive, patch)]
+struct CaseInsensitiveTest {
+    #[prop(key = "server.port")]
+    port: u16,
+    #[prop(key = "server.Host", default = "localhost")]
+    host: String,
+    #[prop(rest)]
+    rest: HashMap<String, String>,
+}
+
+#[test]
+fn case_insensitive_test() -> anyhow::Result<()> {
+    let t = CaseInsensitiveTest::from_str("SERVER.PORT = 1\nserver.host = a\nOther.Key = b")?;
+    assert_eq!((t.port, t.host.as_str()), (1, "a"));
+    assert_eq!(t.rest, HashMap::from([("Other.Key".to_string(), "b".to_string())]));
+
+    // The exact spelling wins, then the variant that sorts first
+    let t = CaseInsensitiveTest::from_map(&HashMap::from([("Server.Port", "2"), ("server.port", "3"), ("SERVER.PORT", "4")]))?;
+    assert_eq!(t.port, 3);
+    let t = CaseInsensitiveTest::from_map(&HashMap::from([("Server.Port", "2"), ("SERVER.PORT", "4")]))?;
+    assert_eq!(t.port, 4);
+
+    let mut t = CaseInsensitiveTest::from_str("Server.Port = 5")?;
+    t.apply_overrides(&HashMap::from([("SERVER.HOST", "b")]))?;
+    assert_eq!(t.host, "b");
+    t.apply(CaseInsensitiveTestPatch::from_str("server.PORT = 6")?);
+    assert_eq!(t.port, 6);
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(rename_all = "kebab-case")]
+struct RenameAllTest {
+    max_connections: u32,
+    #[prop(default = "30")]
+    idle_timeout_secs: u64,
+    #[prop(key = "server.port")]
+    port: u16,
+}
+
+#[derive(Properties)]
+#[prop(rename_all = "SCREAMING_SNAKE_CASE")]
+struct ScreamingTest {
+    max_connections: u32,
+}
+
+#[derive(Properties)]
+#[prop(rename_all = "camelCase")]
+struct CamelTest {
+    max_connections: u32,
+}
+
+#[test]
+fn rename_all_test() -> anyhow::Result<()> {
+    let t = RenameAllTest::from_str("max-connections = 10\nserver.port = 80")?;
+    assert_eq!((t.max_connections, t.idle_timeout_secs, t.port), (10, 30, 80));
+    assert_eq!(RenameAllTest::required_keys(), ["max-connections", "server.port"]);
+    assert_eq!(RenameAllTest::optional_keys(), ["idle-timeout-secs"]);
+
+    assert_eq!(ScreamingTest::from_str("MAX_CONNECTIONS = 1")?.max_connections, 1);
+    assert_eq!(CamelTest::from_str("maxConnections = 2")?.max_connections, 2);
+    assert!(CamelTest::from_str("max_connections = 2").is_err());
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(order = "file, env, default")]
+struct OrderTest {
+    #[prop(env = "ORDER_TEST_NAME", default = "default")]
+    name: String,
+    #[prop(env = "ORDER_TEST_OTHER", default = "default", order = "default,env,file")]
+    other: String,
+}
+
+#[test]
+fn order_test() -> anyhow::Result<()> {
+    unsafe {
+        std::env::set_var("ORDER_TEST_NAME", "env");
+        std::env::set_var("ORDER_TEST_OTHER", "env");
+    }
+
+    let t = OrderTest::from_str("name = file\nother = file")?;
+    assert_eq!((t.name.as_str(), t.other.as_str()), ("file", "default"));
+    assert_eq!(OrderTest::default()?.name, "env");
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(file = "examples/test.properties", file_env = "LOAD_TEST_CONFIG")]
+struct LoadTest {
+    name: String,
+}
+
+#[test]
+fn load_test() -> anyhow::Result<()> {
+    assert_eq!(LoadTest::load()?.name, "test");
+
+    let temp_file = tempfile::NamedTempFile::new()?;
+    std::fs::write(temp_file.path(), "name = from-env")?;
+    unsafe {
+        std::env::set_var("LOAD_TEST_CONFIG", temp_file.path());
+    }
+    assert_eq!(LoadTest::load()?.name, "from-env");
+    Ok(())
+}
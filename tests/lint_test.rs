@@ -0,0 +1,38 @@
+use props_util::{LintKind, lint_file, lint_str};
+
+#[test]
+fn lint_test() -> anyhow::Result<()> {
+    let content = "# comment\nport = 8080\nport = 9090\nno separator\nserver host = a \ndisplay.näme = b\npath = C:\\temp\\data\nbad = \\q and \\u12\nnbsp = x\u{a0}y\ncontinued = a \\\n";
+    let found = lint_str(content).iter().map(|d| (d.line, d.column, d.kind)).collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            (3, 1, LintKind::DuplicateKey),
+            (4, 1, LintKind::MalformedLine),
+            (5, 7, LintKind::SuspiciousWhitespace),
+            (5, 16, LintKind::SuspiciousWhitespace),
+            (6, 10, LintKind::NonAsciiKey),
+            (7, 15, LintKind::UnknownEscape),
+            (8, 7, LintKind::UnknownEscape),
+            (8, 14, LintKind::UnknownEscape),
+            (9, 9, LintKind::SuspiciousWhitespace),
+            (10, 15, LintKind::UnknownEscape),
+        ]
+    );
+
+    let diagnostics = lint_str(content);
+    assert_eq!(diagnostics[0].to_string(), "3:1: `port` is already set on line 2, this value replaces it");
+    assert_eq!(diagnostics[7].message, "`bad` has a `\\u` escape without 4 hex digits");
+    assert_eq!(diagnostics[9].message, "`continued` ends with a backslash, which Java reads as a line continuation");
+
+    // Diagnostics of a single line are ordered by column too
+    let found = lint_str("x = a\\q \n").iter().map(|d| (d.line, d.column, d.kind)).collect::<Vec<_>>();
+    assert_eq!(found, [(1, 6, LintKind::UnknownEscape), (1, 8, LintKind::SuspiciousWhitespace)]);
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("clean.properties");
+    std::fs::write(&path, "a = 1\nb = \\u00e9\\n\n")?;
+    assert!(lint_file(path.to_str().unwrap())?.is_empty());
+    assert!(lint_file("missing.properties").is_err());
+    Ok(())
+}
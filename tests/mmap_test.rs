@@ -0,0 +1,20 @@
+#![cfg(feature = "mmap")]
+
+use props_util::Properties;
+
+#[derive(Properties)]
+struct Mapped {
+    name: String,
+    option_vec1: Option<Vec<u32>>,
+}
+
+#[test]
+fn mmap_file_test() -> anyhow::Result<()> {
+    let a = Mapped::from_file_mmap("examples/test.properties")?;
+    assert_eq!(a.name, "test".to_string());
+    assert_eq!(a.option_vec1, Some(vec![8, 9, 10]));
+
+    let empty = tempfile::NamedTempFile::new()?;
+    assert!(Mapped::from_file_mmap(empty.path().to_str().unwrap()).is_err());
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use std::sync::Mutex;
+
+use props_util::{Properties, observe};
+
+#[derive(Properties)]
+struct Observed {
+    name: String,
+}
+
+static EVENTS: Mutex<Vec<(String, bool)>> = Mutex::new(Vec::new());
+
+#[test]
+fn load_observer_test() {
+    observe::add_load_observer(|event| {
+        if event.type_name.ends_with("::Observed") {
+            EVENTS.lock().unwrap().push((event.source.to_string(), event.error.is_none()));
+        }
+    });
+
+    assert!(Observed::from_file("examples/test.properties").is_ok());
+    assert!(Observed::from_file("examples/missing.properties").is_err());
+    assert!(Observed::from_str("name = a").is_ok());
+    assert!(Observed::from_files(&["examples/test.properties", "examples/secret.properties"]).is_ok());
+
+    let expected = [
+        ("examples/test.properties", true),
+        ("examples/missing.properties", false),
+        ("<str>", true),
+        ("examples/test.properties, examples/secret.properties", true),
+    ];
+    assert_eq!(*EVENTS.lock().unwrap(), expected.map(|(source, ok)| (source.to_string(), ok)));
+}
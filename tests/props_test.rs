@@ -0,0 +1,81 @@
+use props_util::{Properties, Props};
+
+#[derive(Properties)]
+struct Server {
+    #[prop(key = "name")]
+    server_name: String,
+    option_vec1: Vec<u8>,
+}
+
+#[test]
+fn props_get_test() -> anyhow::Result<()> {
+    let props = Props::from_file("examples/test.properties")?;
+    assert_eq!(props.len(), 3);
+    assert_eq!(props.get::<String>("name")?, "test".to_string());
+    assert_eq!(props.get_vec::<u32>("option_vec2")?, vec![8, 9, 10]);
+    assert_eq!(props.get_opt::<u32>("missing")?, None);
+    assert_eq!(props.get_or("missing", 7)?, 7);
+    assert_eq!(props.get_str("name"), Some("test"));
+
+    assert_eq!(props.get::<u32>("missing").err().unwrap().to_string(), "`missing` value is not configured which is required");
+    assert!(props.get::<u32>("name").is_err());
+    assert!(props.get_or("name", 7).is_err());
+    Ok(())
+}
+
+#[test]
+fn props_into_struct_test() -> anyhow::Result<()> {
+    let server = Server::from(Props::from_str("name = s1\noption_vec1 = 1, 2")?)?;
+    assert_eq!(server.server_name, "s1".to_string());
+    assert_eq!(server.option_vec1, vec![1, 2]);
+    Ok(())
+}
+
+#[test]
+fn props_subset_test() -> anyhow::Result<()> {
+    let props = Props::from_str("db.name = main\ndb.option_vec1 = 3\ndbx.name = other\nname = top")?;
+    let db = props.subset("db.");
+    assert_eq!(db.len(), 2);
+    assert_eq!(db.get_str("name"), Some("main"));
+
+    let server = Server::from(db)?;
+    assert_eq!(server.server_name, "main".to_string());
+    assert_eq!(server.option_vec1, vec![3]);
+    assert!(props.subset("cache.").is_empty());
+    Ok(())
+}
+
+#[test]
+fn props_iter_test() -> anyhow::Result<()> {
+    let props = Props::from_str("feature.dark_mode = true\nfeature.beta = false\nfeatures = none")?;
+    let mut keys = props.keys().collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, ["feature.beta", "feature.dark_mode", "features"]);
+
+    let mut flags = props.iter_prefix("feature.").collect::<Vec<_>>();
+    flags.sort();
+    assert_eq!(flags, [("beta", "false"), ("dark_mode", "true")]);
+
+    let server = Server::from_str("name = s1\noption_vec1 = 1")?.to_props();
+    assert_eq!(server.get_str("server_name"), Some("s1"));
+    assert_eq!(server.iter_prefix("option_").collect::<Vec<_>>(), [("vec1", "1")]);
+    Ok(())
+}
+
+#[test]
+fn writer_test() -> anyhow::Result<()> {
+    use props_util::runtime::Writer;
+
+    let entries = [("key with spaces", " padded"), ("path", "C:\\temp"), ("multi", "a\nb\tc"), ("bang!", "#1")];
+    let expected = "key\\ with\\ spaces=\\ padded\npath=C\\:\\\\temp\nmulti=a\\nb\\tc\nbang\\!=\\#1\n";
+    assert_eq!(Writer::new().write(entries), expected);
+
+    let writer = Writer::new().separator(": ").header("first\nsecond").ascii(true);
+    assert_eq!(writer.write([("emoji".to_string(), "😀".to_string())]), "# first\n# second\nemoji: \\uD83D\\uDE00\n");
+    assert_eq!(Writer::new().write([("name", "é")]), "name=é\n");
+
+    let mut out = Vec::new();
+    Writer::new().write_to(&mut out, [("a", "1")])?;
+    assert_eq!(out, b"a=1\n");
+    Ok(())
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "serde")]
+
+use props_util::Properties;
+
+#[derive(Properties, Debug, PartialEq)]
+#[prop(serde)]
+struct Serialized {
+    #[prop(key = "server.port", default = "8080")]
+    port: u16,
+    hosts: Vec<String>,
+    name: Option<String>,
+}
+
+#[test]
+fn serialize_test() -> anyhow::Result<()> {
+    let value = Serialized::from_str("hosts = a, b")?;
+    assert_eq!(serde_json::to_string(&value)?, r#"{"hosts":"a,b","server.port":"8080"}"#);
+    Ok(())
+}
+
+#[test]
+fn deserialize_test() -> anyhow::Result<()> {
+    let value: Serialized = serde_json::from_str(r#"{"hosts":"a,b","name":"n"}"#)?;
+    assert_eq!(
+        value,
+        Serialized {
+            port: 8080,
+            hosts: vec!["a".into(), "b".into()],
+            name: Some("n".into())
+        }
+    );
+    assert_eq!(serde_json::from_str::<Serialized>(&serde_json::to_string(&value)?)?, value);
+
+    let err = serde_json::from_str::<Serialized>(r#"{"server.port":"1"}"#).err().unwrap();
+    assert!(err.to_string().contains("`hosts` value is not configured which is required"));
+    Ok(())
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "templates")]
+
+use props_util::Properties;
+
+#[derive(Properties, Debug)]
+#[prop(templates)]
+struct Templated {
+    #[prop(key = "greeting")]
+    greeting: String,
+    #[prop(key = "db.url")]
+    db_url: String,
+    #[prop(key = "db.pool", default = "4")]
+    pool: u32,
+}
+
+#[test]
+fn templates_test() -> anyhow::Result<()> {
+    let config = Templated::from_str(
+        "user.name = ada \n\
+         greeting = Hello ${user.name|upper}, from ${ team | default(ops) }\n\
+         db.host = ${host.name|default(localhost)|lower}\n\
+         host.name = DB1\n\
+         db.url = postgres://${db.host}:${db.port|default(5432)}/$${not.a.template}\n\
+         pool.size = 8\n\
+         db.pool = ${pool.size}",
+    )?;
+    assert_eq!(config.greeting, "Hello ADA, from ops");
+    assert_eq!(config.db_url, "postgres://db1:5432/${not.a.template}");
+    assert_eq!(config.pool, 8);
+
+    let message = |content: &str| Templated::from_str(content).err().unwrap().to_string();
+    assert_eq!(message("greeting = ${user.name}\ndb.url = a"), "`greeting` has an invalid template: it references `user.name`, which is not configured");
+    assert_eq!(
+        message("greeting = ${user.name|title}\nuser.name = ada\ndb.url = a"),
+        "`greeting` has an invalid template: unknown function `title`, expected `lower`, `upper` or `default(...)`"
+    );
+    assert_eq!(message("greeting = ${user.name\ndb.url = a"), "`greeting` has an invalid template: `${` has no closing `}`");
+    assert!(message("greeting = ${db.url}\ndb.url = ${greeting}").contains("references itself through"));
+    Ok(())
+}
+
+#[derive(Properties, Debug)]
+#[prop(templates)]
+struct TemplatedVendor {
+    #[prop(key = "name")]
+    name: String,
+    #[prop(rest)]
+    rest: std::collections::HashMap<String, String>,
+}
+
+#[test]
+fn unrelated_templates_test() -> anyhow::Result<()> {
+    // Only the fields and the keys they reference are rendered, a broken template elsewhere is kept as written
+    let mut config = TemplatedVendor::from_str("name = ${vendor.name|upper}\nvendor.name = acme\nvendor.url = ${unclosed")?;
+    assert_eq!(config.name, "ACME");
+    assert_eq!(config.rest["vendor.name"], "acme");
+    assert_eq!(config.rest["vendor.url"], "${unclosed");
+
+    config.apply_overrides(&std::collections::HashMap::from([("name", "${team}-${env}"), ("team", "ops"), ("env", "dev")])).unwrap();
+    assert_eq!(config.name, "ops-dev");
+    let report = config.apply_overrides(&std::collections::HashMap::from([("name", "${team}")])).err().unwrap();
+    assert_eq!(report.to_string(), "`name` has an invalid template: it references `team`, which is not configured");
+    assert_eq!(config.name, "ops-dev");
+    Ok(())
+}
+
+#[derive(Properties, Debug)]
+#[prop(templates)]
+struct TemplatedPort {
+    #[prop(key = "templated.port")]
+    port: u16,
+}
+
+// Templates are rendered at runtime, so the compile time check only requires the key
+props_util::props_check!(TemplatedPort, "examples/check.properties");
+
+#[test]
+fn props_check_templates_test() -> anyhow::Result<()> {
+    assert_eq!(TemplatedPort::from_file("examples/check.properties")?.port, 80);
+    let result = std::panic::catch_unwind(|| TemplatedPort::__props_check(&[("templated.port", "eighty")]));
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn env_templates_test() -> anyhow::Result<()> {
+    let _region = props_util::testing::override_env("REGION", "eu-west-1");
+    let _zone = props_util::testing::unset_env("ZONE");
+    let config = Templated::from_str("greeting = ${env:REGION|upper}/${env:ZONE|default(a)}\ndb.url = a")?;
+    assert_eq!(config.greeting, "EU-WEST-1/a");
+
+    let error = Templated::from_str("greeting = ${env:ZONE}\ndb.url = a").err().unwrap();
+    assert_eq!(error.to_string(), "`greeting` has an invalid template: it references the environment variable `ZONE`, which is not set");
+    Ok(())
+}
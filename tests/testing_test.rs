@@ -0,0 +1,127 @@
+#![cfg(feature = "testing")]
+
+use props_util::{Origin, Properties, testing};
+
+#[derive(Properties)]
+struct Overridden {
+    #[prop(key = "server.port", default = "8080")]
+    port: u16,
+    #[prop(env = "OVERRIDDEN_NAME", default = "default-name")]
+    name: String,
+}
+
+#[test]
+fn override_key_test() -> anyhow::Result<()> {
+    {
+        let _outer = testing::override_key("server.port", "1000");
+        {
+            let _inner = testing::override_key("server.port", "2000");
+            assert_eq!(Overridden::default()?.port, 2000);
+            assert_eq!(Overridden::from_pairs([("server.port", "3000")])?.port, 2000);
+        }
+        assert_eq!(Overridden::default()?.port, 1000);
+
+        // Overrides are per thread
+        let port = std::thread::spawn(|| Overridden::default().map(|o| o.port)).join().unwrap()?;
+        assert_eq!(port, 8080);
+    }
+    assert_eq!(Overridden::default()?.port, 8080);
+    Ok(())
+}
+
+#[test]
+fn override_env_test() -> anyhow::Result<()> {
+    unsafe {
+        std::env::set_var("OVERRIDDEN_NAME", "from-env");
+    }
+
+    {
+        let _env = testing::override_env("OVERRIDDEN_NAME", "overridden");
+        assert_eq!(Overridden::default()?.name, "overridden".to_string());
+
+        let _unset = testing::unset_env("OVERRIDDEN_NAME");
+        assert_eq!(Overridden::default()?.name, "default-name".to_string());
+    }
+    assert_eq!(Overridden::default()?.name, "from-env".to_string());
+    Ok(())
+}
+
+#[test]
+fn load_user_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let _home = testing::override_env("HOME", dir.path().join("home").to_str().unwrap());
+    let _xdg = testing::override_env("XDG_CONFIG_HOME", dir.path().join("xdg").to_str().unwrap());
+    assert_eq!(Overridden::load_user("myapp").err().unwrap().kind(), std::io::ErrorKind::NotFound);
+
+    std::fs::create_dir_all(dir.path().join("xdg/myapp"))?;
+    std::fs::write(dir.path().join("xdg/myapp/app.properties"), "server.port = 9000")?;
+    assert_eq!(Overridden::load_user("myapp")?.port, 9000);
+    Ok(())
+}
+
+#[derive(Properties)]
+#[prop(embed = "examples/test.properties")]
+struct StandardTest {
+    name: String,
+    #[prop(key = "server.port", default = "8080")]
+    port: u16,
+    #[prop(key = "server.host", default = "localhost")]
+    host: String,
+}
+
+#[test]
+fn standard_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let _xdg = testing::override_env("XDG_CONFIG_HOME", dir.path().to_str().unwrap());
+    let _port = testing::unset_env("STANDARD_TEST_SERVER_PORT");
+    let _host = testing::unset_env("STANDARD_TEST_SERVER_HOST");
+
+    let t = StandardTest::standard_with_args("standard-test", Vec::new())?;
+    assert_eq!((t.name.as_str(), t.port, t.host.as_str()), ("test", 8080, "localhost"));
+
+    std::fs::create_dir_all(dir.path().join("standard-test"))?;
+    std::fs::write(dir.path().join("standard-test/app.properties"), "name = user\nserver.port = 1")?;
+    let _port = testing::override_env("STANDARD_TEST_SERVER_PORT", "2");
+    let _host = testing::override_env("STANDARD_TEST_SERVER_HOST", "env-host");
+
+    let args = ["--server.host=arg-host", "--verbose", "positional"].map(String::from);
+    let t = StandardTest::standard_with_args("standard-test", args.clone())?;
+    assert_eq!((t.name.as_str(), t.port, t.host.as_str()), ("user", 2, "arg-host"));
+
+    let (_, provenance) = StandardTest::standard_with_provenance("standard-test", args)?;
+    let user = dir.path().join("standard-test/app.properties").to_string_lossy().into_owned();
+    assert_eq!(provenance.origin("name"), Some(&Origin::File { path: user.clone(), line: 1 }));
+    assert_eq!(provenance.overridden("name"), [Origin::Embedded { line: 2 }]);
+    assert_eq!(provenance.origin("server.port"), Some(&Origin::Env("STANDARD_TEST_SERVER_PORT".into())));
+    assert_eq!(provenance.overridden("server.port"), [Origin::File { path: user, line: 2 }, Origin::Default]);
+    assert_eq!(provenance.origin("server.host").unwrap().to_string(), "argument --server.host");
+    Ok(())
+}
+
+#[derive(Properties)]
+struct Snapshot {
+    #[prop(key = "server.port", env = "SNAPSHOT_PORT", default = "8080")]
+    port: u16,
+    #[prop(key = "server.host", default = "localhost")]
+    host: String,
+    #[prop(key = "db.password", secret)]
+    password: String,
+}
+
+#[test]
+fn snapshot_test() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let [base, local] = ["base.properties", "local.properties"].map(|name| dir.path().join(name));
+    std::fs::write(&base, "server.port = 1\ndb.password = hunter2")?;
+    std::fs::write(&local, "\nserver.port = 2")?;
+    let _port = testing::override_env("SNAPSHOT_PORT", "3");
+
+    let paths = [&base, &local].map(|path| path.to_str().unwrap());
+    let (config, provenance) = Snapshot::from_files_with_provenance(&paths)?;
+    let expected = "db.password = ***  # base.properties:2
+server.host = localhost  # default
+server.port = 3  # env SNAPSHOT_PORT, overriding local.properties:2, base.properties:1, default";
+    assert_eq!(config.snapshot(&provenance), expected);
+    assert_eq!(config.snapshot(&Default::default()), "db.password = ***\nserver.host = localhost\nserver.port = 3");
+    Ok(())
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "tracing")]
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use props_util::Properties;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+#[derive(Properties)]
+struct Traced {
+    #[prop(key = "server.port", default = "8080")]
+    port: u16,
+    #[prop(key = "server.host", env = "PROPS_UTIL_TRACING_TEST_HOST", default = "localhost")]
+    host: String,
+    #[prop(key = "db.password", secret)]
+    password: String,
+}
+
+/// Records the fields of every event as their `Debug` output.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<HashMap<String, String>>>>);
+
+impl Visit for Recorder {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.lock().unwrap().last_mut().unwrap().insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        self.0.lock().unwrap().push(HashMap::from([("target".to_string(), event.metadata().target().to_string())]));
+        event.record(&mut self.clone());
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn load_event_test() -> anyhow::Result<()> {
+    let recorder = Recorder::default();
+    let t = tracing::subscriber::with_default(recorder.clone(), || Traced::from_file("examples/secret.properties"))?;
+    assert_eq!(t.password, "hunter2");
+
+    let events = recorder.0.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event["target"], "props_util");
+    assert_eq!(event["message"], "loaded `Traced`");
+    assert_eq!(event["source"], "examples/secret.properties");
+    assert_eq!(event["keys"], "2");
+    assert_eq!(event["defaults"], r#"["server.host"]"#);
+    assert_eq!(event["env"], "[]");
+    assert!(event["values"].contains(r#""db.password": "***""#));
+    assert!(!event["values"].contains("hunter2"));
+    Ok(())
+}